@@ -55,6 +55,7 @@ pub struct ClipboardOperationOptions {
 }
 
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct ClipboardOptions {
   // Wayland-only
   // The number of milliseconds the wl-clipboard commands are allowed
@@ -0,0 +1,92 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::Write;
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use espanso_config::matches::store::{self, MatchStore};
+use tempfile::TempDir;
+
+const FILE_COUNT: usize = 20;
+const MATCHES_PER_FILE: usize = 1000;
+
+fn create_match_files(dir: &Path) -> Vec<String> {
+  let mut paths = Vec::new();
+
+  for file in 0..FILE_COUNT {
+    let mut content = String::new();
+    if file + 1 < FILE_COUNT {
+      writeln!(content, "imports:\n  - \"_file{}.yml\"", file + 1).unwrap();
+    }
+    content.push_str("matches:\n");
+    for index in 0..MATCHES_PER_FILE {
+      writeln!(
+        content,
+        "  - trigger: \":t{file}_{index}\"\n    replace: \"replacement {file} {index}\""
+      )
+      .unwrap();
+    }
+
+    let name = if file == 0 {
+      "base.yml".to_string()
+    } else {
+      format!("_file{file}.yml")
+    };
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+
+    if file == 0 {
+      paths.push(path.to_string_lossy().to_string());
+    }
+  }
+
+  paths
+}
+
+fn bench_query(c: &mut Criterion) {
+  let dir = TempDir::new().unwrap();
+  let paths = create_match_files(dir.path());
+
+  let mut group = c.benchmark_group("match_store_query");
+
+  group.bench_function("uncached", |b| {
+    let (store, _) = store::load_uncached(&paths);
+    b.iter(|| store.query(&paths).matches.len());
+  });
+
+  group.bench_function("cold", |b| {
+    b.iter_batched(
+      || store::load(&paths).0,
+      |store| store.query(&paths).matches.len(),
+      BatchSize::LargeInput,
+    );
+  });
+
+  group.bench_function("warm", |b| {
+    let (store, _) = store::load(&paths);
+    store.query(&paths);
+    b.iter(|| store.query(&paths).matches.len());
+  });
+
+  group.finish();
+}
+
+criterion_group!(benches, bench_query);
+criterion_main!(benches);
@@ -23,3 +23,5 @@ pub(crate) const DEFAULT_SHORTCUT_EVENT_DELAY: usize = 10;
 pub(crate) const DEFAULT_RESTORE_CLIPBOARD_DELAY: usize = 300;
 pub(crate) const DEFAULT_POST_FORM_DELAY: usize = 200;
 pub(crate) const DEFAULT_POST_SEARCH_DELAY: usize = 200;
+pub(crate) const DEFAULT_CLIPBOARD_HISTORY_SIZE: usize = 20;
+pub(crate) const DEFAULT_CLIPBOARD_HISTORY_INTERVAL: usize = 1000;
@@ -28,7 +28,8 @@ mod parse;
 mod path;
 mod resolve;
 pub(crate) mod store;
-mod util;
+mod time;
+pub(crate) mod util;
 
 #[cfg(test)]
 use mockall::{automock, predicate::*};
@@ -123,6 +124,17 @@ pub trait Config: Send + Sync {
   // are typed.
   fn backspace_limit(&self) -> usize;
 
+  // If true, the typed characters are forgotten after a mouse click. Otherwise,
+  // triggers can be completed after clicking, for example to fix a typo.
+  fn reset_buffer_on_click(&self) -> bool;
+
+  // If true, the typed characters are forgotten when the focused application changes.
+  fn reset_buffer_on_focus_change(&self) -> bool;
+
+  // The number of milliseconds without typing after which the typed characters
+  // are forgotten, 0 to keep them indefinitely.
+  fn reset_buffer_idle_timeout(&self) -> usize;
+
   // If false, avoid applying the built-in patches to the current config.
   fn apply_patch(&self) -> bool;
 
@@ -150,6 +162,19 @@ pub trait Config: Send + Sync {
   // If false, avoid showing the `SecureInput`` notification on macOS
   fn secure_input_notification(&self) -> bool;
 
+  // If true, espanso keeps an in-memory history of the clipboard text contents,
+  // which can then be accessed through the `clipboard` variable.
+  // The clipboard is polled in the background to record its history, which on
+  // Wayland or with the xclip backend spawns a process on every check.
+  fn clipboard_history(&self) -> bool;
+
+  // The maximum number of entries kept in the clipboard history.
+  fn clipboard_history_size(&self) -> usize;
+
+  // The number of milliseconds between two checks of the clipboard
+  // when recording its history.
+  fn clipboard_history_interval(&self) -> usize;
+
   // The number of milliseconds to wait after a form has been closed.
   // This is useful to let the target application regain focus
   // after a form has been closed, otherwise the injection might
@@ -224,6 +249,9 @@ pub trait Config: Send + Sync {
         max_form_height: {:?}
         post_search_delay: {:?}
         backspace_limit: {}
+        reset_buffer_on_click: {:?}
+        reset_buffer_on_focus_change: {:?}
+        reset_buffer_idle_timeout: {:?}
         search_trigger: {:?}
         search_shortcut: {:?}
         keyboard_layout: {:?}
@@ -231,6 +259,9 @@ pub trait Config: Send + Sync {
         show_icon: {:?}
         show_notifications: {:?}
         secure_input_notification: {:?}
+        clipboard_history: {:?}
+        clipboard_history_size: {:?}
+        clipboard_history_interval: {:?}
 
         x11_use_xclip_backend: {:?}
         x11_use_xdotool_backend: {:?}
@@ -261,6 +292,9 @@ pub trait Config: Send + Sync {
       self.max_form_height(),
       self.post_search_delay(),
       self.backspace_limit(),
+      self.reset_buffer_on_click(),
+      self.reset_buffer_on_focus_change(),
+      self.reset_buffer_idle_timeout(),
       self.search_trigger(),
       self.search_shortcut(),
       self.keyboard_layout(),
@@ -268,6 +302,9 @@ pub trait Config: Send + Sync {
       self.show_icon(),
       self.show_notifications(),
       self.secure_input_notification(),
+      self.clipboard_history(),
+      self.clipboard_history_size(),
+      self.clipboard_history_interval(),
 
       self.x11_use_xclip_backend(),
       self.x11_use_xdotool_backend(),
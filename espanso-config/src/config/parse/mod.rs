@@ -21,6 +21,8 @@ use anyhow::Result;
 use std::{collections::BTreeMap, convert::TryInto, path::Path};
 use thiserror::Error;
 
+use crate::error::UnknownFieldError;
+
 mod yaml;
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -37,6 +39,9 @@ pub(crate) struct ParsedConfig {
   pub disable_x11_fast_inject: Option<bool>,
   pub word_separators: Option<Vec<String>>,
   pub backspace_limit: Option<usize>,
+  pub reset_buffer_on_click: Option<bool>,
+  pub reset_buffer_on_focus_change: Option<bool>,
+  pub reset_buffer_idle_timeout: Option<usize>,
   pub apply_patch: Option<bool>,
   pub search_trigger: Option<String>,
   pub search_shortcut: Option<String>,
@@ -44,6 +49,9 @@ pub(crate) struct ParsedConfig {
   pub show_notifications: Option<bool>,
   pub show_icon: Option<bool>,
   pub secure_input_notification: Option<bool>,
+  pub clipboard_history: Option<bool>,
+  pub clipboard_history_size: Option<usize>,
+  pub clipboard_history_interval: Option<usize>,
   pub post_form_delay: Option<usize>,
   pub max_form_width: Option<usize>,
   pub max_form_height: Option<usize>,
@@ -74,6 +82,11 @@ pub(crate) struct ParsedConfig {
   pub filter_class: Option<String>,
   pub filter_exec: Option<String>,
   pub filter_os: Option<String>,
+  pub filter_time: Option<String>,
+  pub filter_weekday: Option<Vec<String>>,
+  pub filter_hostname: Option<String>,
+  pub filter_user: Option<String>,
+  pub filter_env: Option<Vec<String>>,
 }
 
 impl ParsedConfig {
@@ -84,6 +97,12 @@ impl ParsedConfig {
       Err(err) => Err(ParsedConfigError::LoadFailed(err).into()),
     }
   }
+
+  /// Find the keys of the given config file that are not recognized
+  pub fn find_unknown_fields(path: &Path) -> Result<Vec<UnknownFieldError>> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(yaml::YAMLConfig::find_unknown_fields(&content))
+  }
 }
 
 #[derive(Error, Debug)]
@@ -22,11 +22,14 @@ use serde::{Deserialize, Serialize};
 use serde_yaml::Mapping;
 use std::convert::TryFrom;
 
-use crate::util::is_yaml_empty;
+use crate::{
+  error::UnknownFieldError,
+  util::{field_names, is_yaml_empty},
+};
 
 use super::ParsedConfig;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub(crate) struct YAMLConfig {
   #[serde(default)]
   pub label: Option<String>,
@@ -82,6 +85,15 @@ pub(crate) struct YAMLConfig {
   #[serde(default)]
   pub backspace_limit: Option<usize>,
 
+  #[serde(default)]
+  pub reset_buffer_on_click: Option<bool>,
+
+  #[serde(default)]
+  pub reset_buffer_on_focus_change: Option<bool>,
+
+  #[serde(default)]
+  pub reset_buffer_idle_timeout: Option<usize>,
+
   #[serde(default)]
   pub apply_patch: Option<bool>,
 
@@ -118,6 +130,15 @@ pub(crate) struct YAMLConfig {
   #[serde(default)]
   pub secure_input_notification: Option<bool>,
 
+  #[serde(default)]
+  pub clipboard_history: Option<bool>,
+
+  #[serde(default)]
+  pub clipboard_history_size: Option<usize>,
+
+  #[serde(default)]
+  pub clipboard_history_interval: Option<usize>,
+
   #[serde(default)]
   pub emulate_alt_codes: Option<bool>,
 
@@ -161,6 +182,21 @@ pub(crate) struct YAMLConfig {
 
   #[serde(default)]
   pub filter_os: Option<String>,
+
+  #[serde(default)]
+  pub filter_time: Option<String>,
+
+  #[serde(default)]
+  pub filter_weekday: Option<Vec<String>>,
+
+  #[serde(default)]
+  pub filter_hostname: Option<String>,
+
+  #[serde(default)]
+  pub filter_user: Option<String>,
+
+  #[serde(default)]
+  pub filter_env: Option<Vec<String>>,
 }
 
 impl YAMLConfig {
@@ -174,6 +210,21 @@ impl YAMLConfig {
 
     Ok(serde_yaml::from_str(yaml)?)
   }
+
+  /// Find the keys of the given config that would be silently ignored by serde
+  pub fn find_unknown_fields(yaml: &str) -> Vec<UnknownFieldError> {
+    let Ok(serde_yaml::Value::Mapping(mapping)) = serde_yaml::from_str(yaml) else {
+      return Vec::new();
+    };
+
+    let known_fields = field_names(&YAMLConfig::default());
+    mapping
+      .iter()
+      .filter_map(|(key, _)| key.as_str())
+      .filter(|key| !known_fields.iter().any(|field| field == key))
+      .map(|key| UnknownFieldError::new(key, "config", &known_fields))
+      .collect()
+  }
 }
 
 impl TryFrom<YAMLConfig> for ParsedConfig {
@@ -195,6 +246,9 @@ impl TryFrom<YAMLConfig> for ParsedConfig {
       evdev_modifier_delay: yaml_config.evdev_modifier_delay,
       word_separators: yaml_config.word_separators,
       backspace_limit: yaml_config.backspace_limit,
+      reset_buffer_on_click: yaml_config.reset_buffer_on_click,
+      reset_buffer_on_focus_change: yaml_config.reset_buffer_on_focus_change,
+      reset_buffer_idle_timeout: yaml_config.reset_buffer_idle_timeout,
       apply_patch: yaml_config.apply_patch,
       keyboard_layout: yaml_config.keyboard_layout.map(|mapping| {
         mapping
@@ -215,6 +269,9 @@ impl TryFrom<YAMLConfig> for ParsedConfig {
       show_icon: yaml_config.show_icon,
       show_notifications: yaml_config.show_notifications,
       secure_input_notification: yaml_config.secure_input_notification,
+      clipboard_history: yaml_config.clipboard_history,
+      clipboard_history_size: yaml_config.clipboard_history_size,
+      clipboard_history_interval: yaml_config.clipboard_history_interval,
 
       pre_paste_delay: yaml_config.pre_paste_delay,
       restore_clipboard_delay: yaml_config.restore_clipboard_delay,
@@ -241,6 +298,11 @@ impl TryFrom<YAMLConfig> for ParsedConfig {
       filter_exec: yaml_config.filter_exec,
       filter_os: yaml_config.filter_os,
       filter_title: yaml_config.filter_title,
+      filter_time: yaml_config.filter_time,
+      filter_weekday: yaml_config.filter_weekday,
+      filter_hostname: yaml_config.filter_hostname,
+      filter_user: yaml_config.filter_user,
+      filter_env: yaml_config.filter_env,
     })
   }
 }
@@ -272,6 +334,9 @@ mod tests {
     evdev_modifier_delay: 40
     word_separators: ["'", "."]
     backspace_limit: 10
+    reset_buffer_on_click: false
+    reset_buffer_on_focus_change: true
+    reset_buffer_idle_timeout: 5000
     apply_patch: false
     keyboard_layout:
       rules: test_rule
@@ -285,6 +350,9 @@ mod tests {
     show_icon: false
     show_notifications: false
     secure_input_notification: false
+    clipboard_history: true
+    clipboard_history_size: 50
+    clipboard_history_interval: 2000
     post_form_delay: 300
     max_form_width: 700
     max_form_height: 500
@@ -305,6 +373,11 @@ mod tests {
     filter_exec: "test6"
     filter_os: "test7"
     filter_title: "test8"
+    filter_time: "09:00-17:00"
+    filter_weekday: ["mon-fri"]
+    filter_hostname: "test9"
+    filter_user: "test10"
+    filter_env: ["test11"]
     "#,
     )
     .unwrap();
@@ -337,6 +410,9 @@ mod tests {
         inject_delay: Some(10),
         key_delay: Some(20),
         backspace_limit: Some(10),
+        reset_buffer_on_click: Some(false),
+        reset_buffer_on_focus_change: Some(true),
+        reset_buffer_idle_timeout: Some(5000),
         apply_patch: Some(false),
         keyboard_layout: Some(keyboard_layout),
         search_trigger: Some("search".to_owned()),
@@ -345,6 +421,9 @@ mod tests {
         show_icon: Some(false),
         show_notifications: Some(false),
         secure_input_notification: Some(false),
+        clipboard_history: Some(true),
+        clipboard_history_size: Some(50),
+        clipboard_history_interval: Some(2000),
         emulate_alt_codes: Some(true),
         post_form_delay: Some(300),
         max_form_width: Some(700),
@@ -371,7 +450,44 @@ mod tests {
         filter_exec: Some("test6".to_string()),
         filter_os: Some("test7".to_string()),
         filter_title: Some("test8".to_string()),
+        filter_time: Some("09:00-17:00".to_string()),
+        filter_weekday: Some(vec!["mon-fri".to_string()]),
+        filter_hostname: Some("test9".to_string()),
+        filter_user: Some("test10".to_string()),
+        filter_env: Some(vec!["test11".to_string()]),
       }
     );
   }
+
+  #[test]
+  fn find_unknown_fields_reports_typos() {
+    let errors: Vec<String> = YAMLConfig::find_unknown_fields(
+      r"
+    backend: Clipboard
+    toggle_kye: ALT
+    something: true
+    ",
+    )
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+
+    assert_eq!(
+      errors,
+      vec![
+        "unknown field `toggle_kye` in config, did you mean `toggle_key`?",
+        "unknown field `something` in config",
+      ]
+    );
+  }
+
+  #[test]
+  fn find_unknown_fields_valid_config() {
+    assert!(YAMLConfig::find_unknown_fields(
+      "backend: Clipboard
+word_separators: [' ']"
+    )
+    .is_empty());
+    assert!(YAMLConfig::find_unknown_fields("").is_empty());
+  }
 }
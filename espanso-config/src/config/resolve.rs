@@ -19,23 +19,35 @@
 
 use super::{
   default::{
+    DEFAULT_CLIPBOARD_HISTORY_INTERVAL, DEFAULT_CLIPBOARD_HISTORY_SIZE,
     DEFAULT_CLIPBOARD_THRESHOLD, DEFAULT_POST_FORM_DELAY, DEFAULT_POST_SEARCH_DELAY,
     DEFAULT_PRE_PASTE_DELAY, DEFAULT_RESTORE_CLIPBOARD_DELAY, DEFAULT_SHORTCUT_EVENT_DELAY,
   },
   parse::ParsedConfig,
   path::calculate_paths,
-  util::os_matches,
+  time::{parse_weekdays, TimeRange},
+  util::{current_hostname, current_username, os_matches, EnvCondition},
   AppProperties, Backend, Config, RMLVOConfig, ToggleKey,
 };
 use crate::{counter::next_id, merge};
 use anyhow::Result;
+use chrono::{Datelike, Local, NaiveDateTime, Weekday};
 use log::error;
 use regex::Regex;
 use std::path::PathBuf;
 use std::{collections::HashSet, path::Path};
 use thiserror::Error;
 
-const STANDARD_INCLUDES: &[&str] = &["../match/**/[!_]*.yml"];
+const STANDARD_INCLUDES: &[&str] = &[
+  "../match/**/[!_]*.yml",
+  "../match/**/[!_]*.json",
+  "../match/**/[!_]*.toml",
+];
+
+// Packages might ship JSON or TOML data files, so only their YAML
+// files are loaded by the standard includes
+const STANDARD_PACKAGE_EXCLUDES: &[&str] =
+  &["../match/packages/**/*.json", "../match/packages/**/*.toml"];
 
 #[derive(Debug, Clone, Default)]
 pub(crate) struct ResolvedConfig {
@@ -50,6 +62,11 @@ pub(crate) struct ResolvedConfig {
   filter_title: Option<Regex>,
   filter_class: Option<Regex>,
   filter_exec: Option<Regex>,
+  filter_time: Option<TimeRange>,
+  filter_weekday: Option<Vec<Weekday>>,
+  filter_hostname: Option<Regex>,
+  filter_user: Option<Regex>,
+  filter_env: Option<Vec<EnvCondition>>,
 }
 
 impl Config for ResolvedConfig {
@@ -76,52 +93,7 @@ impl Config for ResolvedConfig {
   }
 
   fn is_match(&self, app: &AppProperties) -> bool {
-    if self.parsed.filter_os.is_none()
-      && self.parsed.filter_title.is_none()
-      && self.parsed.filter_exec.is_none()
-      && self.parsed.filter_class.is_none()
-    {
-      return false;
-    }
-
-    let is_os_match = if let Some(filter_os) = self.parsed.filter_os.as_deref() {
-      os_matches(filter_os)
-    } else {
-      true
-    };
-
-    let is_title_match = if let Some(title_regex) = self.filter_title.as_ref() {
-      if let Some(title) = app.title {
-        title_regex.is_match(title)
-      } else {
-        false
-      }
-    } else {
-      true
-    };
-
-    let is_exec_match = if let Some(exec_regex) = self.filter_exec.as_ref() {
-      if let Some(exec) = app.exec {
-        exec_regex.is_match(exec)
-      } else {
-        false
-      }
-    } else {
-      true
-    };
-
-    let is_class_match = if let Some(class_regex) = self.filter_class.as_ref() {
-      if let Some(class) = app.class {
-        class_regex.is_match(class)
-      } else {
-        false
-      }
-    } else {
-      true
-    };
-
-    // All the filters that have been specified must be true to define a match
-    is_os_match && is_exec_match && is_title_match && is_class_match
+    self.is_match_at(app, &Local::now().naive_local())
   }
 
   fn backend(&self) -> Backend {
@@ -262,6 +234,18 @@ impl Config for ResolvedConfig {
     self.parsed.backspace_limit.unwrap_or(5)
   }
 
+  fn reset_buffer_on_click(&self) -> bool {
+    self.parsed.reset_buffer_on_click.unwrap_or(true)
+  }
+
+  fn reset_buffer_on_focus_change(&self) -> bool {
+    self.parsed.reset_buffer_on_focus_change.unwrap_or(false)
+  }
+
+  fn reset_buffer_idle_timeout(&self) -> usize {
+    self.parsed.reset_buffer_idle_timeout.unwrap_or(0)
+  }
+
   fn apply_patch(&self) -> bool {
     self.parsed.apply_patch.unwrap_or(true)
   }
@@ -312,6 +296,24 @@ impl Config for ResolvedConfig {
     self.parsed.secure_input_notification.unwrap_or(true)
   }
 
+  fn clipboard_history(&self) -> bool {
+    self.parsed.clipboard_history.unwrap_or(false)
+  }
+
+  fn clipboard_history_size(&self) -> usize {
+    self
+      .parsed
+      .clipboard_history_size
+      .unwrap_or(DEFAULT_CLIPBOARD_HISTORY_SIZE)
+  }
+
+  fn clipboard_history_interval(&self) -> usize {
+    self
+      .parsed
+      .clipboard_history_interval
+      .unwrap_or(DEFAULT_CLIPBOARD_HISTORY_INTERVAL)
+  }
+
   fn emulate_alt_codes(&self) -> bool {
     self
       .parsed
@@ -401,6 +403,41 @@ impl ResolvedConfig {
       None
     };
 
+    let filter_hostname = if let Some(filter_hostname) = config.filter_hostname.as_deref() {
+      Some(Regex::new(filter_hostname)?)
+    } else {
+      None
+    };
+
+    let filter_user = if let Some(filter_user) = config.filter_user.as_deref() {
+      Some(Regex::new(filter_user)?)
+    } else {
+      None
+    };
+
+    let filter_env = if let Some(filter_env) = config.filter_env.as_deref() {
+      Some(
+        filter_env
+          .iter()
+          .map(|condition| EnvCondition::parse(condition))
+          .collect::<Result<Vec<_>>>()?,
+      )
+    } else {
+      None
+    };
+
+    let filter_time = if let Some(filter_time) = config.filter_time.as_deref() {
+      Some(TimeRange::parse(filter_time)?)
+    } else {
+      None
+    };
+
+    let filter_weekday = if let Some(filter_weekday) = config.filter_weekday.as_deref() {
+      Some(parse_weekdays(filter_weekday)?)
+    } else {
+      None
+    };
+
     Ok(Self {
       parsed: config,
       source_path: Some(path.to_owned()),
@@ -409,9 +446,108 @@ impl ResolvedConfig {
       filter_title,
       filter_class,
       filter_exec,
+      filter_time,
+      filter_weekday,
+      filter_hostname,
+      filter_user,
+      filter_env,
     })
   }
 
+  // Time-based filters are evaluated against the given instant, so that
+  // the active config changes as soon as a time window opens or closes.
+  fn is_match_at(&self, app: &AppProperties, now: &NaiveDateTime) -> bool {
+    if self.parsed.filter_os.is_none()
+      && self.parsed.filter_title.is_none()
+      && self.parsed.filter_exec.is_none()
+      && self.parsed.filter_class.is_none()
+      && self.parsed.filter_time.is_none()
+      && self.parsed.filter_weekday.is_none()
+      && self.parsed.filter_hostname.is_none()
+      && self.parsed.filter_user.is_none()
+      && self.parsed.filter_env.is_none()
+    {
+      return false;
+    }
+
+    let is_os_match = if let Some(filter_os) = self.parsed.filter_os.as_deref() {
+      os_matches(filter_os)
+    } else {
+      true
+    };
+
+    let is_hostname_match = if let Some(hostname_regex) = self.filter_hostname.as_ref() {
+      current_hostname().is_some_and(|hostname| hostname_regex.is_match(hostname))
+    } else {
+      true
+    };
+
+    let is_user_match = if let Some(user_regex) = self.filter_user.as_ref() {
+      current_username().is_some_and(|user| user_regex.is_match(user))
+    } else {
+      true
+    };
+
+    let is_env_match = if let Some(env_conditions) = self.filter_env.as_ref() {
+      env_conditions.iter().all(EnvCondition::is_satisfied)
+    } else {
+      true
+    };
+
+    let is_title_match = if let Some(title_regex) = self.filter_title.as_ref() {
+      if let Some(title) = app.title {
+        title_regex.is_match(title)
+      } else {
+        false
+      }
+    } else {
+      true
+    };
+
+    let is_exec_match = if let Some(exec_regex) = self.filter_exec.as_ref() {
+      if let Some(exec) = app.exec {
+        exec_regex.is_match(exec)
+      } else {
+        false
+      }
+    } else {
+      true
+    };
+
+    let is_class_match = if let Some(class_regex) = self.filter_class.as_ref() {
+      if let Some(class) = app.class {
+        class_regex.is_match(class)
+      } else {
+        false
+      }
+    } else {
+      true
+    };
+
+    let is_time_match = if let Some(time_range) = self.filter_time.as_ref() {
+      time_range.contains(now.time())
+    } else {
+      true
+    };
+
+    let is_weekday_match = if let Some(weekdays) = self.filter_weekday.as_ref() {
+      weekdays.contains(&now.weekday())
+    } else {
+      true
+    };
+
+    // All the filters that have been specified must be true to define a match
+    is_os_match
+      && is_hostname_match
+      && is_user_match
+      && is_env_match
+      && is_exec_match
+      && is_title_match
+      && is_class_match
+      && is_time_match
+      && is_weekday_match
+  }
+
   fn merge_parsed(child: &mut ParsedConfig, parent: &ParsedConfig) {
     // Override the None fields with the parent's value
     merge!(
@@ -437,6 +573,9 @@ impl ResolvedConfig {
       evdev_modifier_delay,
       word_separators,
       backspace_limit,
+      reset_buffer_on_click,
+      reset_buffer_on_focus_change,
+      reset_buffer_idle_timeout,
       keyboard_layout,
       search_trigger,
       search_shortcut,
@@ -444,6 +583,9 @@ impl ResolvedConfig {
       show_icon,
       show_notifications,
       secure_input_notification,
+      clipboard_history,
+      clipboard_history_size,
+      clipboard_history_interval,
       emulate_alt_codes,
       post_form_delay,
       max_form_width,
@@ -461,7 +603,12 @@ impl ResolvedConfig {
       filter_title,
       filter_class,
       filter_exec,
-      filter_os
+      filter_os,
+      filter_time,
+      filter_weekday,
+      filter_hostname,
+      filter_user,
+      filter_env
     );
   }
 
@@ -513,7 +660,22 @@ impl ResolvedConfig {
 
     // Extract the paths
     let exclude_paths = calculate_paths(base_dir, excludes.iter());
-    let include_paths = calculate_paths(base_dir, includes.iter());
+    let mut include_paths = calculate_paths(base_dir, includes.iter());
+
+    // Package data files can still be included explicitly
+    let explicit_includes = includes
+      .iter()
+      .filter(|include| !STANDARD_INCLUDES.contains(&include.as_str()));
+    let explicit_include_paths = calculate_paths(base_dir, explicit_includes);
+    let package_excludes: Vec<String> = STANDARD_PACKAGE_EXCLUDES
+      .iter()
+      .map(|exclude| (*exclude).to_string())
+      .collect();
+    for path in calculate_paths(base_dir, package_excludes.iter()) {
+      if !explicit_include_paths.contains(&path) {
+        include_paths.remove(&path);
+      }
+    }
 
     include_paths
       .difference(&exclude_paths)
@@ -540,10 +702,14 @@ mod tests {
       ResolvedConfig::aggregate_includes(&ParsedConfig {
         ..Default::default()
       }),
-      ["../match/**/[!_]*.yml".to_string()]
-        .iter()
-        .cloned()
-        .collect::<HashSet<_>>()
+      [
+        "../match/**/[!_]*.yml".to_string(),
+        "../match/**/[!_]*.json".to_string(),
+        "../match/**/[!_]*.toml".to_string(),
+      ]
+      .iter()
+      .cloned()
+      .collect::<HashSet<_>>()
     );
   }
 
@@ -567,6 +733,8 @@ mod tests {
       }),
       [
         "../match/**/[!_]*.yml".to_string(),
+        "../match/**/[!_]*.json".to_string(),
+        "../match/**/[!_]*.toml".to_string(),
         "custom/*.yml".to_string()
       ]
       .iter()
@@ -584,6 +752,8 @@ mod tests {
       }),
       [
         "../match/**/[!_]*.yml".to_string(),
+        "../match/**/[!_]*.json".to_string(),
+        "../match/**/[!_]*.toml".to_string(),
         "custom/*.yml".to_string()
       ]
       .iter()
@@ -602,6 +772,8 @@ mod tests {
       }),
       [
         "../match/**/[!_]*.yml".to_string(),
+        "../match/**/[!_]*.json".to_string(),
+        "../match/**/[!_]*.toml".to_string(),
         "custom/*.yml".to_string(),
         "sub/*.yml".to_string()
       ]
@@ -834,6 +1006,49 @@ mod tests {
     });
   }
 
+  #[test]
+  fn match_paths_skip_package_data_files() {
+    use_test_directory(|_, match_dir, config_dir| {
+      let package_dir = match_dir.join("packages").join("pkg");
+      create_dir_all(&package_dir).unwrap();
+
+      let base_file = match_dir.join("base.json");
+      std::fs::write(&base_file, "test").unwrap();
+      let package_file = package_dir.join("package.yml");
+      std::fs::write(&package_file, "test").unwrap();
+      let data_file = package_dir.join("data.json");
+      std::fs::write(&data_file, "test").unwrap();
+      let other_data_file = package_dir.join("data.toml");
+      std::fs::write(other_data_file, "test").unwrap();
+
+      let config_file = config_dir.join("default.yml");
+      std::fs::write(&config_file, "").unwrap();
+      let config = ResolvedConfig::load(&config_file, None).unwrap();
+
+      let mut expected = vec![
+        base_file.to_string_lossy().to_string(),
+        package_file.to_string_lossy().to_string(),
+      ];
+      expected.sort();
+      let mut result = config.match_paths().to_vec();
+      result.sort();
+      assert_eq!(result, expected.as_slice());
+
+      std::fs::write(
+        &config_file,
+        "extra_includes: ['../match/packages/pkg/data.json']",
+      )
+      .unwrap();
+      let config = ResolvedConfig::load(&config_file, None).unwrap();
+
+      expected.push(data_file.to_string_lossy().to_string());
+      expected.sort();
+      let mut result = config.match_paths().to_vec();
+      result.sort();
+      assert_eq!(result, expected.as_slice());
+    });
+  }
+
   fn test_filter_is_match(config: &str, app: &AppProperties) -> bool {
     let mut result = false;
     let result_ref = &mut result;
@@ -848,6 +1063,28 @@ mod tests {
     result
   }
 
+  fn test_filter_is_match_at(config: &str, app: &AppProperties, now: &NaiveDateTime) -> bool {
+    let mut result = false;
+    let result_ref = &mut result;
+    use_test_directory(move |_, _, config_dir| {
+      let config_file = config_dir.join("default.yml");
+      std::fs::write(&config_file, config).unwrap();
+
+      let config = ResolvedConfig::load(&config_file, None).unwrap();
+
+      *result_ref = config.is_match_at(app, now);
+    });
+    result
+  }
+
+  // 2021-03-01 was a Monday
+  fn monday_at(hour: u32, minute: u32) -> NaiveDateTime {
+    chrono::NaiveDate::from_ymd_opt(2021, 3, 1)
+      .unwrap()
+      .and_hms_opt(hour, minute, 0)
+      .unwrap()
+  }
+
   #[test]
   fn is_match_no_filters() {
     assert!(!test_filter_is_match(
@@ -1007,4 +1244,151 @@ mod tests {
       },
     ));
   }
+
+  #[test]
+  fn is_match_filter_time() {
+    let app = AppProperties {
+      title: Some("Google"),
+      class: Some("Chrome"),
+      exec: Some("chrome.exe"),
+    };
+
+    assert!(test_filter_is_match_at(
+      "filter_time: 09:00-17:00",
+      &app,
+      &monday_at(10, 30)
+    ));
+
+    assert!(!test_filter_is_match_at(
+      "filter_time: 09:00-17:00",
+      &app,
+      &monday_at(18, 0)
+    ));
+  }
+
+  #[test]
+  fn is_match_filter_weekday() {
+    let app = AppProperties {
+      title: Some("Google"),
+      class: Some("Chrome"),
+      exec: Some("chrome.exe"),
+    };
+
+    assert!(test_filter_is_match_at(
+      "filter_weekday: [mon-fri]",
+      &app,
+      &monday_at(10, 30)
+    ));
+
+    assert!(!test_filter_is_match_at(
+      "filter_weekday: [sat, sun]",
+      &app,
+      &monday_at(10, 30)
+    ));
+  }
+
+  #[test]
+  fn is_match_filter_time_combined_with_app_filters() {
+    assert!(test_filter_is_match_at(
+      r#"
+      filter_exec: chrome.exe
+      filter_time: "09:00-17:00"
+      filter_weekday: ["mon-fri"]
+      "#,
+      &AppProperties {
+        title: Some("Gmail"),
+        class: Some("Chrome"),
+        exec: Some("chrome.exe"),
+      },
+      &monday_at(9, 0)
+    ));
+
+    assert!(!test_filter_is_match_at(
+      r#"
+      filter_exec: firefox.exe
+      filter_time: "09:00-17:00"
+      "#,
+      &AppProperties {
+        title: Some("Gmail"),
+        class: Some("Chrome"),
+        exec: Some("chrome.exe"),
+      },
+      &monday_at(9, 0)
+    ));
+  }
+
+  #[test]
+  fn invalid_filter_time_fails_to_load() {
+    use_test_directory(|_, _, config_dir| {
+      let config_file = config_dir.join("default.yml");
+      std::fs::write(&config_file, "filter_time: 9am-5pm").unwrap();
+
+      assert!(ResolvedConfig::load(&config_file, None).is_err());
+    });
+  }
+
+  #[test]
+  fn is_match_filter_hostname() {
+    let hostname = current_hostname().expect("unable to determine the hostname");
+    let app = AppProperties {
+      title: Some("Google"),
+      class: Some("Chrome"),
+      exec: Some("chrome.exe"),
+    };
+
+    assert!(test_filter_is_match(
+      &format!("filter_hostname: \"^{}$\"", regex::escape(hostname)),
+      &app,
+    ));
+
+    assert!(!test_filter_is_match(
+      "filter_hostname: \"^this-is-not-the-hostname$\"",
+      &app,
+    ));
+  }
+
+  #[test]
+  fn is_match_filter_user() {
+    let user = current_username().expect("unable to determine the username");
+    let app = AppProperties {
+      title: Some("Google"),
+      class: Some("Chrome"),
+      exec: Some("chrome.exe"),
+    };
+
+    assert!(test_filter_is_match(
+      &format!("filter_user: \"^{}$\"", regex::escape(user)),
+      &app,
+    ));
+
+    assert!(!test_filter_is_match(
+      "filter_user: \"^this-is-not-the-user$\"",
+      &app,
+    ));
+  }
+
+  #[test]
+  fn is_match_filter_env() {
+    std::env::set_var("ESPANSO_TEST_RESOLVE_FILTER_ENV", "remote");
+    let app = AppProperties {
+      title: Some("Google"),
+      class: Some("Chrome"),
+      exec: Some("chrome.exe"),
+    };
+
+    assert!(test_filter_is_match(
+      "filter_env: [ESPANSO_TEST_RESOLVE_FILTER_ENV]",
+      &app,
+    ));
+
+    assert!(test_filter_is_match(
+      r#"filter_env: ["ESPANSO_TEST_RESOLVE_FILTER_ENV=^rem", "!ESPANSO_TEST_RESOLVE_MISSING"]"#,
+      &app,
+    ));
+
+    assert!(!test_filter_is_match(
+      r#"filter_env: ["ESPANSO_TEST_RESOLVE_FILTER_ENV", "ESPANSO_TEST_RESOLVE_MISSING"]"#,
+      &app,
+    ));
+  }
 }
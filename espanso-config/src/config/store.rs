@@ -17,9 +17,9 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::error::NonFatalErrorSet;
+use crate::error::{ErrorRecord, NonFatalErrorSet};
 
-use super::{resolve::ResolvedConfig, Config, ConfigStore, ConfigStoreError};
+use super::{parse::ParsedConfig, resolve::ResolvedConfig, Config, ConfigStore, ConfigStoreError};
 use anyhow::{Context, Result};
 use log::{debug, error};
 use std::sync::Arc;
@@ -85,6 +85,7 @@ impl DefaultConfigStore {
     let default = ResolvedConfig::load(&default_file, None)
       .context("failed to load default.yml configuration")?;
     debug!("loaded default config at path: {:?}", default_file);
+    non_fatal_errors.extend(find_unknown_fields(&default_file));
 
     // Then the others
     let mut customs: Vec<Arc<dyn Config>> = Vec::new();
@@ -106,6 +107,7 @@ impl DefaultConfigStore {
           Ok(config) => {
             customs.push(Arc::new(config));
             debug!("loaded config at path: {:?}", config_file);
+            non_fatal_errors.extend(find_unknown_fields(&config_file));
           }
           Err(err) => {
             error!(
@@ -135,6 +137,21 @@ impl DefaultConfigStore {
   }
 }
 
+// Unknown fields don't prevent a config from being loaded, but they are likely typos
+fn find_unknown_fields(config_file: &Path) -> Option<NonFatalErrorSet> {
+  let errors: Vec<ErrorRecord> = ParsedConfig::find_unknown_fields(config_file)
+    .ok()?
+    .into_iter()
+    .map(|error| ErrorRecord::warn(error.into()))
+    .collect();
+
+  if errors.is_empty() {
+    None
+  } else {
+    Some(NonFatalErrorSet::new(config_file, errors))
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -0,0 +1,162 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use chrono::{NaiveTime, Timelike, Weekday};
+use thiserror::Error;
+
+/// A daily time window, expressed as `HH:MM-HH:MM`.
+/// The start is inclusive and the end is exclusive. If the end comes before
+/// the start, the window spans across midnight (for example `22:00-06:00`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TimeRange {
+  start: u32,
+  end: u32,
+}
+
+impl TimeRange {
+  pub fn parse(range: &str) -> Result<Self> {
+    let invalid_range = || TimeFilterError::InvalidRange(range.to_string());
+    let (start, end) = range.split_once('-').ok_or_else(invalid_range)?;
+
+    Ok(Self {
+      start: parse_minutes(start).ok_or_else(invalid_range)?,
+      end: parse_minutes(end).ok_or_else(invalid_range)?,
+    })
+  }
+
+  pub fn contains(self, time: NaiveTime) -> bool {
+    let minutes = time.hour() * 60 + time.minute();
+    if self.start <= self.end {
+      minutes >= self.start && minutes < self.end
+    } else {
+      minutes >= self.start || minutes < self.end
+    }
+  }
+}
+
+fn parse_minutes(time: &str) -> Option<u32> {
+  let time = NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()?;
+  Some(time.hour() * 60 + time.minute())
+}
+
+/// Parse a list of weekdays, such as `["mon", "wed"]` or `["monday-friday"]`.
+/// Ranges are inclusive and can wrap around the end of the week (e.g. `fri-mon`).
+pub(crate) fn parse_weekdays(days: &[String]) -> Result<Vec<Weekday>> {
+  let mut weekdays = Vec::new();
+
+  for day in days {
+    if let Some((start, end)) = day.split_once('-') {
+      let start = parse_weekday(start)?;
+      let end = parse_weekday(end)?;
+
+      let mut current = start;
+      weekdays.push(current);
+      while current != end {
+        current = current.succ();
+        weekdays.push(current);
+      }
+    } else {
+      weekdays.push(parse_weekday(day)?);
+    }
+  }
+
+  Ok(weekdays)
+}
+
+fn parse_weekday(day: &str) -> Result<Weekday> {
+  Ok(
+    day
+      .trim()
+      .parse::<Weekday>()
+      .map_err(|_| TimeFilterError::InvalidWeekday(day.to_string()))?,
+  )
+}
+
+#[derive(Error, Debug)]
+pub enum TimeFilterError {
+  #[error("invalid time range `{0}`, expected format is HH:MM-HH:MM")]
+  InvalidRange(String),
+
+  #[error("invalid weekday `{0}`")]
+  InvalidWeekday(String),
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+  }
+
+  #[test]
+  fn time_range_same_day() {
+    let range = TimeRange::parse("09:00-17:30").unwrap();
+    assert!(range.contains(time(9, 0)));
+    assert!(range.contains(time(12, 15)));
+    assert!(range.contains(time(17, 29)));
+    assert!(!range.contains(time(17, 30)));
+    assert!(!range.contains(time(8, 59)));
+  }
+
+  #[test]
+  fn time_range_across_midnight() {
+    let range = TimeRange::parse("22:00 - 06:00").unwrap();
+    assert!(range.contains(time(23, 0)));
+    assert!(range.contains(time(0, 30)));
+    assert!(!range.contains(time(6, 0)));
+    assert!(!range.contains(time(12, 0)));
+  }
+
+  #[test]
+  fn time_range_invalid() {
+    assert!(TimeRange::parse("09:00").is_err());
+    assert!(TimeRange::parse("9am-5pm").is_err());
+    assert!(TimeRange::parse("25:00-26:00").is_err());
+  }
+
+  #[test]
+  fn weekdays_list_and_ranges() {
+    assert_eq!(
+      parse_weekdays(&["mon".to_string(), "Wednesday".to_string()]).unwrap(),
+      vec![Weekday::Mon, Weekday::Wed]
+    );
+    assert_eq!(
+      parse_weekdays(&["mon-fri".to_string()]).unwrap(),
+      vec![
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri
+      ]
+    );
+    assert_eq!(
+      parse_weekdays(&["sat-mon".to_string()]).unwrap(),
+      vec![Weekday::Sat, Weekday::Sun, Weekday::Mon]
+    );
+  }
+
+  #[test]
+  fn weekdays_invalid() {
+    assert!(parse_weekdays(&["someday".to_string()]).is_err());
+    assert!(parse_weekdays(&["mon-someday".to_string()]).is_err());
+  }
+}
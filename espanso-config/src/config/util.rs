@@ -17,6 +17,10 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
+
 #[macro_export]
 macro_rules! merge {
   ( $t:ident, $child:expr, $parent:expr, $( $x:ident ),* ) => {
@@ -47,10 +51,107 @@ pub fn os_matches(os: &str) -> bool {
   }
 }
 
+lazy_static! {
+  static ref CURRENT_HOSTNAME: Option<String> = whoami::fallible::hostname().ok();
+  static ref CURRENT_USERNAME: Option<String> = whoami::fallible::username().ok();
+}
+
+pub fn current_hostname() -> Option<&'static str> {
+  CURRENT_HOSTNAME.as_deref()
+}
+
+pub fn current_username() -> Option<&'static str> {
+  CURRENT_USERNAME.as_deref()
+}
+
+/// A condition on an environment variable, which can be expressed as:
+/// - `NAME` to require the variable to be defined
+/// - `!NAME` to require the variable to be undefined
+/// - `NAME=regex` to require the variable value to match the given regex
+#[derive(Debug, Clone)]
+pub enum EnvCondition {
+  Defined(String),
+  Undefined(String),
+  Matches(String, Regex),
+}
+
+impl EnvCondition {
+  pub fn parse(condition: &str) -> Result<Self> {
+    let condition = condition.trim();
+    if let Some((name, value_regex)) = condition.split_once('=') {
+      Ok(Self::Matches(
+        name.trim().to_string(),
+        Regex::new(value_regex)?,
+      ))
+    } else if let Some(name) = condition.strip_prefix('!') {
+      Ok(Self::Undefined(name.trim().to_string()))
+    } else {
+      Ok(Self::Defined(condition.to_string()))
+    }
+  }
+
+  pub fn is_satisfied(&self) -> bool {
+    match self {
+      Self::Defined(name) => std::env::var_os(name).is_some(),
+      Self::Undefined(name) => std::env::var_os(name).is_none(),
+      Self::Matches(name, regex) => std::env::var(name).is_ok_and(|value| regex.is_match(&value)),
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  #[test]
+  fn env_condition_defined() {
+    std::env::set_var("ESPANSO_TEST_ENV_CONDITION_DEFINED", "1");
+    assert!(EnvCondition::parse("ESPANSO_TEST_ENV_CONDITION_DEFINED")
+      .unwrap()
+      .is_satisfied());
+    assert!(!EnvCondition::parse("ESPANSO_TEST_ENV_CONDITION_MISSING")
+      .unwrap()
+      .is_satisfied());
+  }
+
+  #[test]
+  fn env_condition_undefined() {
+    std::env::set_var("ESPANSO_TEST_ENV_CONDITION_UNDEFINED", "1");
+    assert!(
+      !EnvCondition::parse("!ESPANSO_TEST_ENV_CONDITION_UNDEFINED")
+        .unwrap()
+        .is_satisfied()
+    );
+    assert!(EnvCondition::parse("!ESPANSO_TEST_ENV_CONDITION_MISSING")
+      .unwrap()
+      .is_satisfied());
+  }
+
+  #[test]
+  fn env_condition_matches() {
+    std::env::set_var("ESPANSO_TEST_ENV_CONDITION_MATCHES", "work-laptop");
+    assert!(
+      EnvCondition::parse("ESPANSO_TEST_ENV_CONDITION_MATCHES=^work")
+        .unwrap()
+        .is_satisfied()
+    );
+    assert!(
+      !EnvCondition::parse("ESPANSO_TEST_ENV_CONDITION_MATCHES=^home")
+        .unwrap()
+        .is_satisfied()
+    );
+    assert!(
+      !EnvCondition::parse("ESPANSO_TEST_ENV_CONDITION_MISSING=.*")
+        .unwrap()
+        .is_satisfied()
+    );
+  }
+
+  #[test]
+  fn env_condition_invalid_regex() {
+    assert!(EnvCondition::parse("NAME=[invalid").is_err());
+  }
+
   #[test]
   #[cfg(target_os = "linux")]
   fn os_matches_linux() {
@@ -19,6 +19,9 @@
 
 use anyhow::Error;
 use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::util::suggest_field;
 
 #[derive(Debug)]
 pub struct NonFatalErrorSet {
@@ -64,8 +67,29 @@ impl ErrorRecord {
   }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ErrorLevel {
   Error,
   Warning,
 }
+
+#[derive(Error, Debug)]
+#[error(
+  "unknown field `{field}` in {location}{}",
+  .suggestion.as_ref().map(|s| format!(", did you mean `{s}`?")).unwrap_or_default()
+)]
+pub struct UnknownFieldError {
+  pub field: String,
+  pub location: String,
+  pub suggestion: Option<String>,
+}
+
+impl UnknownFieldError {
+  pub fn new(field: &str, location: &str, known_fields: &[String]) -> Self {
+    Self {
+      field: field.to_string(),
+      location: location.to_string(),
+      suggestion: suggest_field(field, known_fields).map(String::from),
+    }
+  }
+}
@@ -45,6 +45,8 @@ use std::convert::TryInto;
 mod config;
 mod model;
 
+// Matches are hashed by value, the compiled filter regexes by their pattern
+#[allow(clippy::mutable_key_type)]
 pub fn load(
   base_dir: &Path,
   package_dir: &Path,
@@ -121,6 +123,7 @@ fn split_config(config: LegacyConfig) -> (LegacyInteropConfig, LegacyMatchGroup)
 
 /// Due to the way the legacy configs are loaded (matches are copied multiple times in the various configs)
 /// we need to deduplicate the ids of those matches (and global vars).
+#[allow(clippy::mutable_key_type)]
 fn deduplicate_ids(
   match_group: &mut LegacyMatchGroup,
   match_map: &mut HashMap<Match, StructId>,
@@ -130,6 +133,7 @@ fn deduplicate_ids(
   deduplicate_matches(&mut match_group.matches, match_map, var_map);
 }
 
+#[allow(clippy::mutable_key_type)]
 fn deduplicate_matches(
   matches: &mut [Match],
   match_map: &mut HashMap<Match, StructId>,
@@ -351,6 +355,18 @@ impl Config for LegacyInteropConfig {
     self.config.backspace_limit.try_into().unwrap()
   }
 
+  fn reset_buffer_on_click(&self) -> bool {
+    true
+  }
+
+  fn reset_buffer_on_focus_change(&self) -> bool {
+    false
+  }
+
+  fn reset_buffer_idle_timeout(&self) -> usize {
+    0
+  }
+
   fn apply_patch(&self) -> bool {
     true
   }
@@ -383,6 +399,18 @@ impl Config for LegacyInteropConfig {
     self.config.secure_input_notification
   }
 
+  fn clipboard_history(&self) -> bool {
+    false
+  }
+
+  fn clipboard_history_size(&self) -> usize {
+    crate::config::default::DEFAULT_CLIPBOARD_HISTORY_SIZE
+  }
+
+  fn clipboard_history_interval(&self) -> usize {
+    crate::config::default::DEFAULT_CLIPBOARD_HISTORY_INTERVAL
+  }
+
   fn enable(&self) -> bool {
     self.config.enable_active
   }
@@ -27,6 +27,7 @@ pub mod config;
 mod counter;
 pub mod error;
 mod legacy;
+pub mod lint;
 pub mod matches;
 mod util;
 
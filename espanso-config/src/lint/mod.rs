@@ -0,0 +1,871 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+  collections::{BTreeSet, HashMap, HashSet},
+  path::{Path, PathBuf},
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{
+  error::{ErrorLevel, NonFatalErrorSet, UnknownFieldError},
+  matches::group::{
+    loader::{
+      yaml::validate::{MATCH_FIELDS, MERGE_KEY, VARIABLE_FIELDS},
+      LoadError,
+    },
+    path::ResolveImportError,
+  },
+  ConfigError,
+};
+
+use self::tree::Node;
+
+mod tree;
+
+lazy_static! {
+  // Also matches the template blocks (`{{#if var}}`, `{{#each var}}`) and filters (`{{var|upper}}`)
+  static ref VAR_REGEX: Regex =
+    Regex::new(r"\{\{\s*(?:#(?:if|each)\s+)?(\w+)(\.\w+)?(?:\s*\|\s*\w+)*\s*\}\}").unwrap();
+}
+
+// Names bound by the template blocks rather than by variables
+const TEMPLATE_KEYWORDS: &[&str] = &["this", "else"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+  pub file: PathBuf,
+  pub line: Option<usize>,
+  pub level: ErrorLevel,
+  pub message: String,
+}
+
+/// Load the configuration contained in the given base directory and
+/// check all the match files it references, reporting both the errors
+/// that would be raised by espanso at load time and the problems that
+/// would only surface while expanding a match.
+/// Issues are sorted by file and line.
+/// Errors that prevent the configuration from loading are reported
+/// as a single issue, as no match file can be checked in that case.
+pub fn lint(base_path: &Path) -> Vec<LintIssue> {
+  let (_, match_store, non_fatal_errors) = match crate::load(base_path) {
+    Ok(loaded) => loaded,
+    Err(err) => return vec![convert_fatal_error(base_path, &err)],
+  };
+
+  let mut paths = match_store.loaded_paths();
+  paths.sort();
+  let files: Vec<MatchFile> = paths
+    .iter()
+    .filter_map(|path| MatchFile::parse(Path::new(path)))
+    .collect();
+
+  let mut issues = convert_load_errors(&non_fatal_errors, &files);
+
+  let global_vars: Vec<VarDefinition> = files
+    .iter()
+    .flat_map(|file| file.root.items_at("global_vars"))
+    .filter_map(VarDefinition::parse)
+    .collect();
+
+  for file in &files {
+    file.check_unknown_keys(&mut issues);
+    file.check_variables(&global_vars, &mut issues);
+  }
+  check_duplicate_triggers(&files, &mut issues);
+
+  issues.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+  issues.dedup();
+  issues
+}
+
+struct MatchFile {
+  path: PathBuf,
+  root: Node,
+}
+
+impl MatchFile {
+  fn parse(path: &Path) -> Option<Self> {
+    // JSON is a subset of YAML, so both can be checked in the same way
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    if !["yml", "yaml", "json", "toml"].contains(&extension.as_str()) {
+      return None;
+    }
+
+    // Files that cannot be parsed are already reported by the loader
+    let content = std::fs::read_to_string(path).ok()?;
+    let root = if extension == "toml" {
+      Node::parse_toml(&content)
+    } else {
+      Node::parse(&content)
+    }
+    .ok()??;
+    Some(Self {
+      path: path.to_owned(),
+      root,
+    })
+  }
+
+  fn matches(&self) -> &[Node] {
+    self.root.items_at("matches")
+  }
+
+  fn issue(&self, line: usize, level: ErrorLevel, message: String) -> LintIssue {
+    LintIssue {
+      file: self.path.clone(),
+      line: Some(line),
+      level,
+      message,
+    }
+  }
+
+  fn check_unknown_keys(&self, issues: &mut Vec<LintIssue>) {
+    let global_vars = self.root.items_at("global_vars");
+    let local_vars = self.matches().iter().flat_map(|m| m.items_at("vars"));
+
+    for m in self.matches() {
+      self.check_keys(m, &MATCH_FIELDS, "match definition", issues);
+    }
+    for var in global_vars.iter().chain(local_vars) {
+      self.check_keys(var, &VARIABLE_FIELDS, "variable definition", issues);
+    }
+  }
+
+  fn check_keys(
+    &self,
+    node: &Node,
+    known_fields: &[String],
+    location: &str,
+    issues: &mut Vec<LintIssue>,
+  ) {
+    for (key, _) in node.entries() {
+      if let Some(key_name) = key.as_str() {
+        if key_name != MERGE_KEY && !known_fields.iter().any(|field| field == key_name) {
+          let error = UnknownFieldError::new(key_name, location, known_fields);
+          issues.push(self.issue(key.line, ErrorLevel::Warning, error.to_string()));
+        }
+      }
+    }
+  }
+
+  fn check_variables(&self, global_vars: &[VarDefinition], issues: &mut Vec<LintIssue>) {
+    let globals: HashMap<&str, &VarDefinition> = global_vars
+      .iter()
+      .map(|var| (var.name.as_str(), var))
+      .collect();
+
+    // Global variables can only depend on other global ones
+    let file_globals: Vec<VarDefinition> = self
+      .root
+      .items_at("global_vars")
+      .iter()
+      .filter_map(VarDefinition::parse)
+      .collect();
+    let mut reported_cycles = HashSet::new();
+    for var in &file_globals {
+      for (dependency, line) in &var.dependencies {
+        if !globals.contains_key(dependency.as_str()) {
+          issues.push(self.issue(
+            *line,
+            ErrorLevel::Error,
+            format!("undefined variable `{dependency}`"),
+          ));
+        }
+      }
+
+      let graph = DependencyGraph::new(&globals, &[], Vec::new());
+      if let Some(cycle) = graph.find_cycle(&var.name) {
+        if reported_cycles.insert(cycle.iter().cloned().collect::<BTreeSet<_>>()) {
+          issues.push(self.issue(var.line, ErrorLevel::Error, format_cycle(&cycle)));
+        }
+      }
+    }
+
+    for m in self.matches() {
+      // Form matches generate their own variables
+      if m.get("form").is_some() {
+        continue;
+      }
+
+      let local_vars: Vec<VarDefinition> = m
+        .items_at("vars")
+        .iter()
+        .filter_map(VarDefinition::parse)
+        .collect();
+
+      let captures: HashSet<String> = m
+        .get("regex")
+        .and_then(Node::as_str)
+        .and_then(|regex| Regex::new(regex).ok())
+        .map(|regex| regex.capture_names().flatten().map(String::from).collect())
+        .unwrap_or_default();
+
+      let is_defined = |name: &str| {
+        captures.contains(name)
+          || globals.contains_key(name)
+          || local_vars.iter().any(|var| var.name == name)
+      };
+
+      let mut body_references = Vec::new();
+      for field in ["replace", "markdown", "html"] {
+        if let Some(body) = m.get(field) {
+          body_references.extend(variable_references(body));
+        }
+      }
+
+      let local_references = local_vars.iter().flat_map(|var| var.dependencies.iter());
+      for (name, line) in body_references.iter().chain(local_references) {
+        if !is_defined(name) {
+          issues.push(self.issue(
+            *line,
+            ErrorLevel::Error,
+            format!("undefined variable `{name}`"),
+          ));
+        }
+      }
+
+      let graph = DependencyGraph::new(
+        &globals,
+        &local_vars,
+        body_references.into_iter().map(|(name, _)| name).collect(),
+      );
+      if let Some(cycle) = graph.find_cycle(BODY_NODE) {
+        // Cycles between global variables are reported on their own
+        if let Some(var) = local_vars.iter().find(|var| cycle.contains(&var.name)) {
+          issues.push(self.issue(var.line, ErrorLevel::Error, format_cycle(&cycle)));
+        }
+      }
+    }
+  }
+}
+
+struct VarDefinition {
+  name: String,
+  line: usize,
+  dependencies: Vec<(String, usize)>,
+}
+
+impl VarDefinition {
+  fn parse(node: &Node) -> Option<Self> {
+    let name = node.get("name")?.as_str()?.to_string();
+
+    let mut dependencies = Vec::new();
+    let inject_vars = node.get("inject_vars").and_then(Node::as_str) != Some("false");
+    if inject_vars {
+      if let Some(params) = node.get("params") {
+        dependencies.extend(variable_references(params));
+      }
+    }
+    if let Some(depends_on) = node.get("depends_on") {
+      dependencies.extend(
+        depends_on
+          .items()
+          .iter()
+          .filter_map(|item| item.as_str().map(|name| (name.to_string(), item.line))),
+      );
+    }
+
+    Some(Self {
+      name,
+      line: node.line,
+      dependencies,
+    })
+  }
+}
+
+fn variable_references(node: &Node) -> Vec<(String, usize)> {
+  node
+    .scalars()
+    .into_iter()
+    .flat_map(|scalar| {
+      let line = scalar.line;
+      VAR_REGEX
+        .captures_iter(scalar.as_str().unwrap_or_default())
+        .filter(|caps| !TEMPLATE_KEYWORDS.contains(&&caps[1]))
+        .map(move |caps| (caps[1].to_string(), line))
+        .collect::<Vec<_>>()
+    })
+    .collect()
+}
+
+const BODY_NODE: &str = "__match_body";
+
+/// Mirrors the evaluation order used by the renderer: every local variable
+/// depends on the one defined before it and the body depends on all of them,
+/// while local variables shadow global ones with the same name.
+struct DependencyGraph {
+  edges: HashMap<String, Vec<String>>,
+}
+
+impl DependencyGraph {
+  fn new(
+    globals: &HashMap<&str, &VarDefinition>,
+    locals: &[VarDefinition],
+    body_references: Vec<String>,
+  ) -> Self {
+    let mut edges: HashMap<String, Vec<String>> = globals
+      .iter()
+      .map(|(name, var)| {
+        let dependencies = var
+          .dependencies
+          .iter()
+          .map(|(dep, _)| dep.clone())
+          .collect();
+        ((*name).to_string(), dependencies)
+      })
+      .collect();
+
+    for (index, var) in locals.iter().enumerate() {
+      let mut dependencies: Vec<String> = var
+        .dependencies
+        .iter()
+        .map(|(dep, _)| dep.clone())
+        .collect();
+      if index > 0 {
+        dependencies.push(locals[index - 1].name.clone());
+      }
+      edges.insert(var.name.clone(), dependencies);
+    }
+
+    let mut body_dependencies: Vec<String> = locals.iter().map(|var| var.name.clone()).collect();
+    body_dependencies.extend(body_references);
+    edges.insert(BODY_NODE.to_string(), body_dependencies);
+
+    Self { edges }
+  }
+
+  /// Return the first dependency cycle reachable from the given node, if any
+  fn find_cycle(&self, start: &str) -> Option<Vec<String>> {
+    let mut path = Vec::new();
+    let mut visited = HashSet::new();
+    self.visit(start, &mut path, &mut visited)
+  }
+
+  fn visit<'a>(
+    &'a self,
+    node: &'a str,
+    path: &mut Vec<&'a str>,
+    visited: &mut HashSet<&'a str>,
+  ) -> Option<Vec<String>> {
+    if let Some(position) = path.iter().position(|name| *name == node) {
+      let mut cycle: Vec<String> = path[position..].iter().map(ToString::to_string).collect();
+      cycle.push(node.to_string());
+      return Some(cycle);
+    }
+    if !visited.insert(node) {
+      return None;
+    }
+
+    path.push(node);
+    for dependency in self.edges.get(node).into_iter().flatten() {
+      if let Some(cycle) = self.visit(dependency, path, visited) {
+        return Some(cycle);
+      }
+    }
+    path.pop();
+
+    None
+  }
+}
+
+fn format_cycle(cycle: &[String]) -> String {
+  format!(
+    "circular dependency between variables: {}",
+    cycle.join(" -> ")
+  )
+}
+
+fn check_duplicate_triggers(files: &[MatchFile], issues: &mut Vec<LintIssue>) {
+  let mut definitions: HashMap<&str, (&Path, usize)> = HashMap::new();
+
+  for file in files {
+    for m in file.matches() {
+      let triggers = m.get("trigger").into_iter().chain(m.items_at("triggers"));
+
+      for trigger in triggers {
+        let Some(name) = trigger.as_str() else {
+          continue;
+        };
+
+        if let Some((path, line)) = definitions.get(name) {
+          issues.push(file.issue(
+            trigger.line,
+            ErrorLevel::Warning,
+            format!(
+              "trigger `{name}` is already defined at {}:{line}",
+              path.display()
+            ),
+          ));
+        } else {
+          definitions.insert(name, (&file.path, trigger.line));
+        }
+      }
+    }
+  }
+}
+
+fn convert_load_errors(error_sets: &[NonFatalErrorSet], files: &[MatchFile]) -> Vec<LintIssue> {
+  let mut issues = Vec::new();
+
+  for error_set in error_sets {
+    for record in &error_set.errors {
+      // Unknown fields of match files are reported with their line while checking each file
+      let is_match_file = files.iter().any(|file| file.path == error_set.file);
+      if is_match_file && record.error.downcast_ref::<UnknownFieldError>().is_some() {
+        continue;
+      }
+
+      let line = parsing_error_line(&record.error)
+        .or_else(|| unresolved_import_line(&record.error, &error_set.file, files));
+
+      issues.push(LintIssue {
+        file: error_set.file.clone(),
+        line,
+        level: record.level,
+        message: format!("{:#}", record.error),
+      });
+    }
+  }
+
+  issues
+}
+
+fn convert_fatal_error(base_path: &Path, error: &anyhow::Error) -> LintIssue {
+  let config_dir = base_path.join("config");
+  let file = if matches!(
+    error.downcast_ref::<ConfigError>(),
+    Some(ConfigError::MissingConfigDir())
+  ) {
+    config_dir
+  } else {
+    config_dir.join("default.yml")
+  };
+
+  LintIssue {
+    file,
+    line: parsing_error_line(error),
+    level: ErrorLevel::Error,
+    message: format!("{error:#}"),
+  }
+}
+
+fn parsing_error_line(error: &anyhow::Error) -> Option<usize> {
+  if let Some(LoadError::ParsingError(inner)) = error.downcast_ref::<LoadError>() {
+    return parsing_error_line(inner);
+  }
+
+  error.chain().find_map(|cause| {
+    if let Some(error) = cause.downcast_ref::<serde_yaml::Error>() {
+      error.location().map(|location| location.line())
+    } else if let Some(error) = cause.downcast_ref::<serde_json::Error>() {
+      Some(error.line())
+    } else if let Some(error) = cause.downcast_ref::<toml::de::Error>() {
+      error.line_col().map(|(line, _)| line + 1)
+    } else {
+      None
+    }
+  })
+}
+
+fn unresolved_import_line(
+  error: &anyhow::Error,
+  file: &Path,
+  files: &[MatchFile],
+) -> Option<usize> {
+  let Some(ResolveImportError::Unresolved { import, .. }) =
+    error.downcast_ref::<ResolveImportError>()
+  else {
+    return None;
+  };
+
+  let file = files.iter().find(|candidate| candidate.path == file)?;
+  file
+    .root
+    .get("imports")?
+    .items()
+    .iter()
+    .find(|item| item.as_str() == Some(import))
+    .map(|item| item.line)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::util::tests::use_test_directory;
+
+  fn lint_files(files: &[(&str, &str)]) -> Vec<(String, Option<usize>, ErrorLevel, String)> {
+    let mut result = Vec::new();
+    use_test_directory(|base, match_dir, config_dir| {
+      std::fs::write(config_dir.join("default.yml"), "").unwrap();
+      for (name, content) in files {
+        std::fs::write(match_dir.join(name), content).unwrap();
+      }
+
+      result = lint(base)
+        .into_iter()
+        .map(|issue| {
+          (
+            issue
+              .file
+              .file_name()
+              .unwrap()
+              .to_string_lossy()
+              .to_string(),
+            issue.line,
+            issue.level,
+            issue.message,
+          )
+        })
+        .collect();
+    });
+    result
+  }
+
+  #[test]
+  fn valid_files_have_no_issues() {
+    let issues = lint_files(&[(
+      "base.yml",
+      r#"
+global_vars:
+  - name: name
+    type: echo
+    params:
+      echo: "Jon"
+
+matches:
+  - trigger: ":hi"
+    replace: "Hi {{name}}, it's {{time}}"
+    vars:
+      - name: time
+        type: date
+        params:
+          format: "%H:%M"
+  - regex: "=(?P<amount>\\d+)"
+    replace: "{{amount}} {{name}}"
+"#,
+    )]);
+
+    assert_eq!(issues, vec![]);
+  }
+
+  #[test]
+  fn config_load_errors_are_reported() {
+    use_test_directory(|base, _, config_dir| {
+      std::fs::write(
+        config_dir.join("default.yml"),
+        "backend: Clipboard\nword_separators: 3\n",
+      )
+      .unwrap();
+
+      let issues = lint(base);
+      assert_eq!(issues.len(), 1);
+      assert_eq!(issues[0].file, config_dir.join("default.yml"));
+      assert_eq!(issues[0].line, Some(2));
+      assert_eq!(issues[0].level, ErrorLevel::Error);
+      assert!(issues[0]
+        .message
+        .starts_with("failed to load default.yml configuration"));
+
+      std::fs::remove_file(config_dir.join("default.yml")).unwrap();
+      let issues = lint(base);
+      assert_eq!(issues.len(), 1);
+      assert_eq!(issues[0].message, "missing default.yml config");
+    });
+  }
+
+  #[test]
+  fn yaml_errors_are_reported_with_their_line() {
+    let issues = lint_files(&[(
+      "base.yml",
+      r#"
+matches:
+  - trigger: ":hi"
+    replace: "hello"
+    word: notabool
+"#,
+    )]);
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].0, "base.yml");
+    assert_eq!(issues[0].1, Some(5));
+    assert_eq!(issues[0].2, ErrorLevel::Error);
+  }
+
+  #[test]
+  fn json_and_toml_errors_are_reported_with_their_line() {
+    let issues = lint_files(&[
+      (
+        "a.json",
+        r#"{
+  "matches": [
+    { "trigger": ":hi", "word": "notabool" }
+  ]
+}"#,
+      ),
+      (
+        "b.toml",
+        r#"
+[[matches]]
+trigger = ":hey"
+replace = "hey"
+word = "notabool"
+"#,
+      ),
+    ]);
+
+    assert_eq!(issues.len(), 2);
+    assert_eq!(issues[0].0, "a.json");
+    assert_eq!(issues[0].1, Some(3));
+    assert_eq!(issues[1].0, "b.toml");
+    assert_eq!(issues[1].1, Some(5));
+  }
+
+  #[test]
+  fn unknown_keys_are_reported() {
+    let issues = lint_files(&[(
+      "base.yml",
+      r#"
+matches:
+  - trigger: ":hi"
+    lable: "greeting"
+    replace: "hello"
+    vars:
+      - name: a
+        type: echo
+        parms:
+          echo: "a"
+"#,
+    )]);
+
+    assert_eq!(
+      issues,
+      vec![
+        (
+          "base.yml".to_string(),
+          Some(4),
+          ErrorLevel::Warning,
+          "unknown field `lable` in match definition, did you mean `label`?".to_string()
+        ),
+        (
+          "base.yml".to_string(),
+          Some(9),
+          ErrorLevel::Warning,
+          "unknown field `parms` in variable definition, did you mean `params`?".to_string()
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn toml_files_are_checked() {
+    let issues = lint_files(&[(
+      "base.toml",
+      r#"
+[[matches]]
+trigger = ":hi"
+lable = "greeting"
+replace = "hello {{name}}"
+"#,
+    )]);
+
+    assert_eq!(
+      issues,
+      vec![
+        (
+          "base.toml".to_string(),
+          Some(4),
+          ErrorLevel::Warning,
+          "unknown field `lable` in match definition, did you mean `label`?".to_string()
+        ),
+        (
+          "base.toml".to_string(),
+          Some(5),
+          ErrorLevel::Error,
+          "undefined variable `name`".to_string()
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn duplicate_triggers_across_files_are_reported() {
+    let issues = lint_files(&[
+      (
+        "a.yml",
+        r#"
+matches:
+  - trigger: ":hi"
+    replace: "hello"
+"#,
+      ),
+      (
+        "b.yml",
+        r#"
+matches:
+  - triggers: [":hey", ":hi"]
+    replace: "hey"
+"#,
+      ),
+    ]);
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].0, "b.yml");
+    assert_eq!(issues[0].1, Some(3));
+    assert_eq!(issues[0].2, ErrorLevel::Warning);
+    assert!(issues[0]
+      .3
+      .starts_with("trigger `:hi` is already defined at"));
+    assert!(issues[0].3.ends_with("a.yml:3"));
+  }
+
+  #[test]
+  fn undefined_variables_are_reported() {
+    let issues = lint_files(&[(
+      "base.yml",
+      r#"
+matches:
+  - trigger: ":hi"
+    replace: "{{greeting}} {{missing}}"
+    vars:
+      - name: greeting
+        type: shell
+        params:
+          cmd: "echo {{other}}"
+"#,
+    )]);
+
+    assert_eq!(
+      issues,
+      vec![
+        (
+          "base.yml".to_string(),
+          Some(4),
+          ErrorLevel::Error,
+          "undefined variable `missing`".to_string()
+        ),
+        (
+          "base.yml".to_string(),
+          Some(9),
+          ErrorLevel::Error,
+          "undefined variable `other`".to_string()
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn template_blocks_are_checked() {
+    let issues = lint_files(&[(
+      "base.yml",
+      r#"
+matches:
+  - trigger: ":hi"
+    replace: "{{#if greeting}}{{greeting|upper}}{{else}}{{#each missing}}{{this}}{{/each}}{{/if}}"
+    vars:
+      - name: greeting
+        type: echo
+        params:
+          echo: "hello"
+"#,
+    )]);
+
+    assert_eq!(
+      issues,
+      vec![(
+        "base.yml".to_string(),
+        Some(4),
+        ErrorLevel::Error,
+        "undefined variable `missing`".to_string()
+      )]
+    );
+  }
+
+  #[test]
+  fn circular_dependencies_are_reported() {
+    let issues = lint_files(&[(
+      "base.yml",
+      r#"
+global_vars:
+  - name: first
+    type: echo
+    params:
+      echo: "{{second}}"
+  - name: second
+    type: echo
+    depends_on: ["first"]
+
+matches:
+  - trigger: ":hi"
+    replace: "{{a}}"
+    vars:
+      - name: a
+        type: echo
+        params:
+          echo: "{{b}}"
+      - name: b
+        type: echo
+        params:
+          echo: "b"
+"#,
+    )]);
+
+    assert_eq!(
+      issues,
+      vec![
+        (
+          "base.yml".to_string(),
+          Some(3),
+          ErrorLevel::Error,
+          "circular dependency between variables: first -> second -> first".to_string()
+        ),
+        (
+          "base.yml".to_string(),
+          Some(15),
+          ErrorLevel::Error,
+          "circular dependency between variables: a -> b -> a".to_string()
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn unreachable_imports_are_reported_with_their_line() {
+    let issues = lint_files(&[(
+      "base.yml",
+      r#"
+imports:
+  - "missing.yml"
+
+matches:
+  - trigger: ":hi"
+    replace: "hello"
+"#,
+    )]);
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].0, "base.yml");
+    assert_eq!(issues[0].1, Some(3));
+    assert_eq!(issues[0].2, ErrorLevel::Error);
+    assert!(issues[0]
+      .3
+      .contains("unable to resolve import `missing.yml`"));
+  }
+}
@@ -0,0 +1,385 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{collections::HashMap, fmt};
+
+use anyhow::Result;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use toml::Spanned;
+use yaml_rust::{
+  parser::{Event, MarkedEventReceiver, Parser},
+  scanner::Marker,
+};
+
+/// A minimal YAML (or TOML) document tree that keeps track of the line
+/// in which each node was defined, so that lint issues can
+/// point users to the right place.
+#[derive(Debug, Clone)]
+pub(crate) struct Node {
+  pub line: usize,
+  pub value: Value,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Value {
+  Scalar(String),
+  Sequence(Vec<Node>),
+  Mapping(Vec<(Node, Node)>),
+}
+
+impl Node {
+  /// Parse the first document contained in the given source, returning
+  /// None if the source is empty.
+  pub fn parse(source: &str) -> Result<Option<Node>> {
+    let mut builder = TreeBuilder::default();
+    Parser::new(source.chars()).load(&mut builder, false)?;
+    Ok(builder.root)
+  }
+
+  /// Parse the given TOML source, returning None if it contains no entries.
+  /// Tables defined through a header (such as `[[matches]]`) have no position
+  /// of their own, so they are reported at the line of their first entry.
+  pub fn parse_toml(source: &str) -> Result<Option<Node>> {
+    let root: TomlValue = toml::from_str(source)?;
+    let node = root.into_node(source, None);
+    if node.entries().is_empty() {
+      return Ok(None);
+    }
+    Ok(Some(node))
+  }
+
+  pub fn get(&self, key: &str) -> Option<&Node> {
+    self
+      .entries()
+      .iter()
+      .find(|(k, _)| k.as_str() == Some(key))
+      .map(|(_, v)| v)
+  }
+
+  /// Return the items of the sequence stored at the given key, if any
+  pub fn items_at(&self, key: &str) -> &[Node] {
+    self.get(key).map_or(&[], Node::items)
+  }
+
+  pub fn as_str(&self) -> Option<&str> {
+    match &self.value {
+      Value::Scalar(value) => Some(value),
+      _ => None,
+    }
+  }
+
+  pub fn items(&self) -> &[Node] {
+    match &self.value {
+      Value::Sequence(items) => items,
+      _ => &[],
+    }
+  }
+
+  pub fn entries(&self) -> &[(Node, Node)] {
+    match &self.value {
+      Value::Mapping(entries) => entries,
+      _ => &[],
+    }
+  }
+
+  /// Collect all the scalar values contained in this node, recursively.
+  /// Mapping keys are not included.
+  pub fn scalars(&self) -> Vec<&Node> {
+    match &self.value {
+      Value::Scalar(_) => vec![self],
+      Value::Sequence(items) => items.iter().flat_map(Node::scalars).collect(),
+      Value::Mapping(entries) => entries.iter().flat_map(|(_, v)| v.scalars()).collect(),
+    }
+  }
+}
+
+struct Frame {
+  node: Node,
+  anchor: usize,
+  pending_key: Option<Node>,
+}
+
+#[derive(Default)]
+struct TreeBuilder {
+  root: Option<Node>,
+  stack: Vec<Frame>,
+  anchors: HashMap<usize, Node>,
+}
+
+impl TreeBuilder {
+  fn push_frame(&mut self, value: Value, anchor: usize, mark: Marker) {
+    self.stack.push(Frame {
+      node: Node {
+        line: mark.line(),
+        value,
+      },
+      anchor,
+      pending_key: None,
+    });
+  }
+
+  fn insert(&mut self, node: Node, anchor: usize) {
+    if anchor > 0 {
+      self.anchors.insert(anchor, node.clone());
+    }
+
+    match self.stack.last_mut() {
+      None => {
+        if self.root.is_none() {
+          self.root = Some(node);
+        }
+      }
+      Some(frame) => match &mut frame.node.value {
+        Value::Sequence(items) => items.push(node),
+        Value::Mapping(entries) => {
+          if let Some(key) = frame.pending_key.take() {
+            entries.push((key, node));
+          } else {
+            frame.pending_key = Some(node);
+          }
+        }
+        Value::Scalar(_) => unreachable!("scalars cannot contain other nodes"),
+      },
+    }
+  }
+}
+
+impl MarkedEventReceiver for TreeBuilder {
+  fn on_event(&mut self, event: Event, mark: Marker) {
+    match event {
+      Event::SequenceStart(anchor) => self.push_frame(Value::Sequence(Vec::new()), anchor, mark),
+      Event::MappingStart(anchor) => self.push_frame(Value::Mapping(Vec::new()), anchor, mark),
+      Event::SequenceEnd | Event::MappingEnd => {
+        if let Some(frame) = self.stack.pop() {
+          self.insert(frame.node, frame.anchor);
+        }
+      }
+      Event::Scalar(value, _, anchor, _) => self.insert(
+        Node {
+          line: mark.line(),
+          value: Value::Scalar(value),
+        },
+        anchor,
+      ),
+      Event::Alias(anchor) => {
+        if let Some(node) = self.anchors.get(&anchor).cloned() {
+          // Report issues at the alias location, rather than at the anchor one
+          self.insert(
+            Node {
+              line: mark.line(),
+              ..node
+            },
+            0,
+          );
+        }
+      }
+      _ => {}
+    }
+  }
+}
+
+enum TomlValue {
+  Scalar(String),
+  Sequence(Vec<Spanned<TomlValue>>),
+  Table(Vec<(String, Spanned<TomlValue>)>),
+}
+
+impl TomlValue {
+  fn into_node(self, source: &str, offset: Option<usize>) -> Node {
+    let spanned_node = |value: Spanned<TomlValue>| {
+      // Tables defined through a header are reported at the start of the document
+      let offset = Some(value.start()).filter(|offset| *offset > 0);
+      value.into_inner().into_node(source, offset)
+    };
+
+    let value = match self {
+      TomlValue::Scalar(value) => Value::Scalar(value),
+      TomlValue::Sequence(items) => Value::Sequence(items.into_iter().map(spanned_node).collect()),
+      TomlValue::Table(entries) => Value::Mapping(
+        entries
+          .into_iter()
+          .map(|(key, value)| {
+            let value = spanned_node(value);
+            let key = Node {
+              line: value.line,
+              value: Value::Scalar(key),
+            };
+            (key, value)
+          })
+          .collect(),
+      ),
+    };
+
+    let first_child_line = match &value {
+      Value::Scalar(_) => None,
+      Value::Sequence(items) => items.iter().map(|item| item.line).min(),
+      Value::Mapping(entries) => entries.iter().map(|(key, _)| key.line).min(),
+    };
+    let line = offset
+      .map(|offset| source[..offset].matches('\n').count() + 1)
+      .or(first_child_line)
+      .unwrap_or(1);
+
+    Node { line, value }
+  }
+}
+
+impl<'de> Deserialize<'de> for TomlValue {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    struct TomlValueVisitor;
+
+    impl<'de> Visitor<'de> for TomlValueVisitor {
+      type Value = TomlValue;
+
+      fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a TOML value")
+      }
+
+      fn visit_bool<E>(self, value: bool) -> Result<TomlValue, E> {
+        Ok(TomlValue::Scalar(value.to_string()))
+      }
+
+      fn visit_i64<E>(self, value: i64) -> Result<TomlValue, E> {
+        Ok(TomlValue::Scalar(value.to_string()))
+      }
+
+      fn visit_u64<E>(self, value: u64) -> Result<TomlValue, E> {
+        Ok(TomlValue::Scalar(value.to_string()))
+      }
+
+      fn visit_f64<E>(self, value: f64) -> Result<TomlValue, E> {
+        Ok(TomlValue::Scalar(value.to_string()))
+      }
+
+      fn visit_str<E>(self, value: &str) -> Result<TomlValue, E> {
+        Ok(TomlValue::Scalar(value.to_string()))
+      }
+
+      fn visit_string<E>(self, value: String) -> Result<TomlValue, E> {
+        Ok(TomlValue::Scalar(value))
+      }
+
+      fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<TomlValue, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+          items.push(item);
+        }
+        Ok(TomlValue::Sequence(items))
+      }
+
+      fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<TomlValue, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+          entries.push(entry);
+        }
+        Ok(TomlValue::Table(entries))
+      }
+    }
+
+    deserializer.deserialize_any(TomlValueVisitor)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_keeps_track_of_lines() {
+    let root = Node::parse(
+      r#"
+      matches:
+        - trigger: ":hi"
+          replace: "hello"
+
+        - trigger: ":bye"
+          replace: "bye"
+      "#,
+    )
+    .unwrap()
+    .unwrap();
+
+    let matches = root.get("matches").unwrap().items();
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].get("trigger").unwrap().as_str(), Some(":hi"));
+    assert_eq!(matches[0].get("trigger").unwrap().line, 3);
+    assert_eq!(matches[1].get("replace").unwrap().line, 7);
+  }
+
+  #[test]
+  fn parse_resolves_aliases() {
+    let root = Node::parse(
+      r"
+      base: &base
+        - a
+        - b
+      other: *base
+      ",
+    )
+    .unwrap()
+    .unwrap();
+
+    let other = root.get("other").unwrap();
+    assert_eq!(other.line, 5);
+    assert_eq!(
+      other
+        .scalars()
+        .iter()
+        .filter_map(|node| node.as_str())
+        .collect::<Vec<_>>(),
+      vec!["a", "b"]
+    );
+  }
+
+  #[test]
+  fn parse_toml_keeps_track_of_lines() {
+    let root = Node::parse_toml(
+      r#"
+[[matches]]
+trigger = ":hi"
+replace = "hello"
+
+[[matches]]
+trigger = ":bye"
+replace = "bye"
+vars = [{ name = "a", type = "echo" }]
+"#,
+    )
+    .unwrap()
+    .unwrap();
+
+    let matches = root.get("matches").unwrap().items();
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].line, 3);
+    assert_eq!(matches[0].get("trigger").unwrap().as_str(), Some(":hi"));
+    assert_eq!(matches[1].get("replace").unwrap().line, 8);
+    assert_eq!(matches[1].items_at("vars")[0].get("name").unwrap().line, 9);
+  }
+
+  #[test]
+  fn parse_empty_document() {
+    assert!(Node::parse("").unwrap().is_none());
+    assert!(Node::parse_toml("").unwrap().is_none());
+  }
+
+  #[test]
+  fn parse_invalid_document() {
+    assert!(Node::parse("matches: [").is_err());
+  }
+}
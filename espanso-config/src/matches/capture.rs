@@ -0,0 +1,343 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::BuildHasher;
+
+use chrono::{Datelike, NaiveDate};
+use thiserror::Error;
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A named capture of a regex trigger, whose value is validated against
+/// the declared type before the match is expanded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RegexCapture {
+  pub name: String,
+  pub capture_type: CaptureType,
+
+  // Used when the capture doesn't participate in the match,
+  // for example with optional groups
+  pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CaptureType {
+  String,
+  Int,
+  // Date in the given chrono format
+  Date(String),
+  // One of the given options, compared case-insensitively
+  Enum(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureValue {
+  String(String),
+  Int(i64),
+  Date { raw: String, date: NaiveDate },
+  Enum { value: String, index: usize },
+}
+
+impl CaptureType {
+  /// Parse a type declaration, such as `int`, `date`, `date[%d/%m/%Y]`
+  /// or `enum[kg,lb]`
+  pub fn parse(declaration: &str) -> Result<Self, CaptureError> {
+    let declaration = declaration.trim();
+    let (name, argument) = match declaration.split_once('[') {
+      Some((name, rest)) => match rest.strip_suffix(']') {
+        Some(argument) => (name.trim(), Some(argument)),
+        None => return Err(CaptureError::InvalidType(declaration.to_string())),
+      },
+      None => (declaration, None),
+    };
+
+    match (name.to_lowercase().as_str(), argument) {
+      ("string", None) => Ok(Self::String),
+      ("int", None) => Ok(Self::Int),
+      ("date", None) => Ok(Self::Date(DEFAULT_DATE_FORMAT.to_string())),
+      ("date", Some(format)) if !format.trim().is_empty() => {
+        Ok(Self::Date(format.trim().to_string()))
+      }
+      ("enum", Some(options)) => {
+        let options: Vec<String> = options
+          .split(',')
+          .map(str::trim)
+          .filter(|option| !option.is_empty())
+          .map(String::from)
+          .collect();
+        if options.is_empty() {
+          Err(CaptureError::InvalidType(declaration.to_string()))
+        } else {
+          Ok(Self::Enum(options))
+        }
+      }
+      _ => Err(CaptureError::InvalidType(declaration.to_string())),
+    }
+  }
+
+  pub fn parse_value(&self, raw: &str) -> Result<CaptureValue, CaptureError> {
+    let invalid = || CaptureError::InvalidValue {
+      value: raw.to_string(),
+      expected: self.to_string(),
+    };
+
+    match self {
+      Self::String => Ok(CaptureValue::String(raw.to_string())),
+      Self::Int => raw
+        .trim()
+        .parse()
+        .map(CaptureValue::Int)
+        .map_err(|_| invalid()),
+      Self::Date(format) => NaiveDate::parse_from_str(raw.trim(), format)
+        .map(|date| CaptureValue::Date {
+          raw: raw.to_string(),
+          date,
+        })
+        .map_err(|_| invalid()),
+      Self::Enum(options) => options
+        .iter()
+        .position(|option| option.to_lowercase() == raw.trim().to_lowercase())
+        .map(|index| CaptureValue::Enum {
+          value: options[index].clone(),
+          index,
+        })
+        .ok_or_else(invalid),
+    }
+  }
+}
+
+impl Display for CaptureType {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::String => write!(f, "string"),
+      Self::Int => write!(f, "int"),
+      Self::Date(format) => write!(f, "date[{format}]"),
+      Self::Enum(options) => write!(f, "enum[{}]", options.join(",")),
+    }
+  }
+}
+
+impl CaptureValue {
+  /// The fields exposed by structured values, such as the year of a date.
+  /// The `value` field always holds the value as a string.
+  pub fn fields(&self) -> Vec<(&'static str, String)> {
+    match self {
+      Self::String(_) | Self::Int(_) => Vec::new(),
+      Self::Date { date, .. } => vec![
+        ("value", self.to_string()),
+        ("iso", date.format(DEFAULT_DATE_FORMAT).to_string()),
+        ("year", date.year().to_string()),
+        ("month", date.month().to_string()),
+        ("day", date.day().to_string()),
+        ("weekday", date.format("%A").to_string()),
+      ],
+      Self::Enum { index, .. } => vec![("value", self.to_string()), ("index", index.to_string())],
+    }
+  }
+}
+
+impl Display for CaptureValue {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::String(value) | Self::Enum { value, .. } | Self::Date { raw: value, .. } => {
+        write!(f, "{value}")
+      }
+      Self::Int(value) => write!(f, "{value}"),
+    }
+  }
+}
+
+/// Convert the named captures of a regex match into typed values, using the
+/// defaults for the missing ones. Captures without a declaration are kept as
+/// strings, while a single invalid value makes the whole conversion fail.
+pub fn resolve_captures<S: BuildHasher>(
+  captures: &[RegexCapture],
+  vars: &HashMap<String, String, S>,
+) -> Result<HashMap<String, CaptureValue>, CaptureError> {
+  let mut values: HashMap<String, CaptureValue> = vars
+    .iter()
+    .map(|(name, value)| (name.clone(), CaptureValue::String(value.clone())))
+    .collect();
+
+  for capture in captures {
+    let Some(raw) = vars.get(&capture.name).or(capture.default.as_ref()) else {
+      continue;
+    };
+
+    let value = capture
+      .capture_type
+      .parse_value(raw)
+      .map_err(|err| CaptureError::InvalidCapture(capture.name.clone(), Box::new(err)))?;
+    values.insert(capture.name.clone(), value);
+  }
+
+  Ok(values)
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CaptureError {
+  #[error(
+    "invalid capture type: '{0}', expected string, int, date, date[<format>] or enum[<options>]"
+  )]
+  InvalidType(String),
+
+  #[error("invalid value '{value}', expected {expected}")]
+  InvalidValue { value: String, expected: String },
+
+  #[error("capture '{0}': {1}")]
+  InvalidCapture(String, Box<CaptureError>),
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn capture(name: &str, declaration: &str, default: Option<&str>) -> RegexCapture {
+    RegexCapture {
+      name: name.to_string(),
+      capture_type: CaptureType::parse(declaration).unwrap(),
+      default: default.map(String::from),
+    }
+  }
+
+  fn vars(values: &[(&str, &str)]) -> HashMap<String, String> {
+    values
+      .iter()
+      .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+      .collect()
+  }
+
+  #[test]
+  fn parse_type_declarations() {
+    assert_eq!(CaptureType::parse("int").unwrap(), CaptureType::Int);
+    assert_eq!(CaptureType::parse(" String ").unwrap(), CaptureType::String);
+    assert_eq!(
+      CaptureType::parse("date").unwrap(),
+      CaptureType::Date("%Y-%m-%d".to_string())
+    );
+    assert_eq!(
+      CaptureType::parse("date[%d/%m/%Y]").unwrap(),
+      CaptureType::Date("%d/%m/%Y".to_string())
+    );
+    assert_eq!(
+      CaptureType::parse("enum[kg, lb]").unwrap(),
+      CaptureType::Enum(vec!["kg".to_string(), "lb".to_string()])
+    );
+  }
+
+  #[test]
+  fn parse_invalid_type_declarations() {
+    for declaration in ["float", "int[3]", "enum[]", "enum", "enum[a,b", "date[]"] {
+      assert_eq!(
+        CaptureType::parse(declaration),
+        Err(CaptureError::InvalidType(declaration.to_string()))
+      );
+    }
+  }
+
+  #[test]
+  fn parse_int_values() {
+    assert_eq!(
+      CaptureType::Int.parse_value("42"),
+      Ok(CaptureValue::Int(42))
+    );
+    assert_eq!(
+      CaptureType::Int.parse_value("-007"),
+      Ok(CaptureValue::Int(-7))
+    );
+    assert!(CaptureType::Int.parse_value("4x").is_err());
+  }
+
+  #[test]
+  fn parse_date_values() {
+    let value = CaptureType::parse("date[%d/%m/%Y]")
+      .unwrap()
+      .parse_value("25/12/2026")
+      .unwrap();
+    assert_eq!(value.to_string(), "25/12/2026");
+    assert_eq!(
+      value.fields(),
+      vec![
+        ("value", "25/12/2026".to_string()),
+        ("iso", "2026-12-25".to_string()),
+        ("year", "2026".to_string()),
+        ("month", "12".to_string()),
+        ("day", "25".to_string()),
+        ("weekday", "Friday".to_string()),
+      ]
+    );
+    assert!(CaptureType::parse("date")
+      .unwrap()
+      .parse_value("2026-02-30")
+      .is_err());
+  }
+
+  #[test]
+  fn parse_enum_values() {
+    let capture_type = CaptureType::parse("enum[kg,lb]").unwrap();
+    assert_eq!(
+      capture_type.parse_value("LB"),
+      Ok(CaptureValue::Enum {
+        value: "lb".to_string(),
+        index: 1
+      })
+    );
+    assert_eq!(
+      capture_type.parse_value("oz"),
+      Err(CaptureError::InvalidValue {
+        value: "oz".to_string(),
+        expected: "enum[kg,lb]".to_string(),
+      })
+    );
+  }
+
+  #[test]
+  fn resolve_captures_with_types_and_defaults() {
+    let captures = [
+      capture("amount", "int", None),
+      capture("unit", "enum[kg,lb]", Some("kg")),
+    ];
+    let values = resolve_captures(&captures, &vars(&[("amount", "3"), ("note", "x")])).unwrap();
+    assert_eq!(values.get("amount"), Some(&CaptureValue::Int(3)));
+    assert_eq!(
+      values.get("unit"),
+      Some(&CaptureValue::Enum {
+        value: "kg".to_string(),
+        index: 0
+      })
+    );
+    assert_eq!(
+      values.get("note"),
+      Some(&CaptureValue::String("x".to_string()))
+    );
+  }
+
+  #[test]
+  fn resolve_captures_rejects_invalid_values() {
+    let captures = [capture("amount", "int", None)];
+    assert_eq!(
+      resolve_captures(&captures, &vars(&[("amount", "three")]))
+        .unwrap_err()
+        .to_string(),
+      "capture 'amount': invalid value 'three', expected int"
+    );
+  }
+}
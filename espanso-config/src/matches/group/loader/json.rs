@@ -0,0 +1,177 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::{error::NonFatalErrorSet, matches::group::MatchGroup};
+
+use super::{
+  yaml::{convert_group, parse::YAMLMatchGroup, validate::find_unknown_fields},
+  Importer,
+};
+
+// JSON match groups share the same structure as the YAML ones
+pub(crate) struct JSONImporter {}
+
+impl JSONImporter {
+  pub fn new() -> Self {
+    Self {}
+  }
+}
+
+impl Importer for JSONImporter {
+  fn is_supported(&self, extension: &str) -> bool {
+    extension == "json"
+  }
+
+  fn load_group(&self, path: &Path) -> Result<(MatchGroup, Option<NonFatalErrorSet>)> {
+    let content = std::fs::read_to_string(path).context("failed to read JSON match group")?;
+    let group: YAMLMatchGroup =
+      serde_json::from_str(&content).context("failed to parse JSON match group")?;
+
+    let unknown_fields = serde_json::from_str(&content)
+      .map(|value| find_unknown_fields(&value))
+      .unwrap_or_default();
+
+    convert_group(path, group, unknown_fields)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    error::ErrorLevel,
+    matches::{Match, MatchCause, MatchEffect, Params, TextEffect, TriggerCause, Value, Variable},
+    util::tests::use_test_directory,
+  };
+  use std::fs::create_dir_all;
+
+  #[test]
+  fn importer_is_supported() {
+    let importer = JSONImporter::new();
+    assert!(importer.is_supported("json"));
+    assert!(!importer.is_supported("yml"));
+  }
+
+  #[test]
+  fn importer_works_correctly() {
+    use_test_directory(|_, match_dir, _| {
+      let sub_dir = match_dir.join("sub");
+      create_dir_all(&sub_dir).unwrap();
+
+      let base_file = match_dir.join("base.json");
+      std::fs::write(
+        &base_file,
+        r#"
+      {
+        "imports": ["sub/sub.yml", "invalid/import.json"],
+        "global_vars": [
+          { "name": "var1", "type": "echo", "params": { "echo": "test" } }
+        ],
+        "matches": [
+          { "trigger": "hello", "replace": "world", "word": true }
+        ]
+      }
+      "#,
+      )
+      .unwrap();
+
+      let sub_file = sub_dir.join("sub.yml");
+      std::fs::write(&sub_file, "").unwrap();
+
+      let importer = JSONImporter::new();
+      let (mut group, non_fatal_error_set) = importer.load_group(&base_file).unwrap();
+      // The invalid import path should be reported as error
+      assert_eq!(non_fatal_error_set.unwrap().errors.len(), 1);
+
+      // Reset the ids to compare them correctly
+      group.matches.iter_mut().for_each(|m| m.id = 0);
+      group.global_vars.iter_mut().for_each(|v| v.id = 0);
+
+      let mut params = Params::new();
+      params.insert("echo".to_string(), Value::String("test".to_string()));
+
+      assert_eq!(
+        group,
+        MatchGroup {
+          imports: vec![sub_file.to_string_lossy().to_string()],
+          global_vars: vec![Variable {
+            name: "var1".to_string(),
+            var_type: "echo".to_string(),
+            params,
+            ..Default::default()
+          }],
+          matches: vec![Match {
+            cause: MatchCause::Trigger(TriggerCause {
+              triggers: vec!["hello".to_string()],
+              left_word: true,
+              right_word: true,
+              ..Default::default()
+            }),
+            effect: MatchEffect::Text(TextEffect {
+              replace: "world".to_string(),
+              ..Default::default()
+            }),
+            ..Default::default()
+          }],
+        }
+      );
+    });
+  }
+
+  #[test]
+  fn importer_reports_unknown_fields() {
+    use_test_directory(|_, match_dir, _| {
+      let base_file = match_dir.join("base.json");
+      std::fs::write(
+        &base_file,
+        r#"{ "matches": [{ "trigger": "hello", "repalce": "world" }] }"#,
+      )
+      .unwrap();
+
+      let importer = JSONImporter::new();
+      let (group, non_fatal_error_set) = importer.load_group(&base_file).unwrap();
+      assert!(group.matches.is_empty());
+
+      // Both the typo and the resulting match without effect are reported
+      let errors = non_fatal_error_set.unwrap().errors;
+      assert_eq!(errors.len(), 2);
+      assert_eq!(errors[0].level, ErrorLevel::Warning);
+      assert_eq!(
+        errors[0].error.to_string(),
+        "unknown field `repalce` in match \"hello\", did you mean `replace`?"
+      );
+      assert_eq!(errors[1].level, ErrorLevel::Error);
+    });
+  }
+
+  #[test]
+  fn importer_invalid_syntax() {
+    use_test_directory(|_, match_dir, _| {
+      let base_file = match_dir.join("base.json");
+      std::fs::write(&base_file, r#"{ "matches": [ "#).unwrap();
+
+      let importer = JSONImporter::new();
+      assert!(importer.load_group(&base_file).is_err());
+    });
+  }
+}
@@ -24,10 +24,12 @@ use thiserror::Error;
 
 use crate::error::NonFatalErrorSet;
 
-use self::yaml::YAMLImporter;
+use self::{json::JSONImporter, toml::TOMLImporter, yaml::YAMLImporter};
 
 use super::MatchGroup;
 
+mod json;
+mod toml;
 pub(crate) mod yaml;
 
 trait Importer {
@@ -36,7 +38,11 @@ trait Importer {
 }
 
 lazy_static! {
-  static ref IMPORTERS: Vec<Box<dyn Importer + Sync + Send>> = vec![Box::new(YAMLImporter::new()),];
+  static ref IMPORTERS: Vec<Box<dyn Importer + Sync + Send>> = vec![
+    Box::new(YAMLImporter::new()),
+    Box::new(JSONImporter::new()),
+    Box::new(TOMLImporter::new()),
+  ];
 }
 
 pub(crate) fn load_match_group(path: &Path) -> Result<(MatchGroup, Option<NonFatalErrorSet>)> {
@@ -19,28 +19,35 @@
 
 use crate::{
   counter::next_id,
-  error::{ErrorRecord, NonFatalErrorSet},
+  error::{ErrorRecord, NonFatalErrorSet, UnknownFieldError},
   matches::{
     group::{path::resolve_imports, MatchGroup},
-    ImageEffect, Match, Params, RegexCause, TextFormat, TextInjectMode, UpperCasingStyle, Value,
-    Variable,
+    AppFilter, FilterRegex, HotkeyCause, ImageEffect, Match, Params, RegexCause, TextFormat,
+    TextInjectMode, UpperCasingStyle, Value, Variable,
   },
 };
 use anyhow::{anyhow, bail, Context, Result};
 use lazy_static::lazy_static;
 use parse::YAMLMatchGroup;
 use regex::{Captures, Regex};
+use serde_yaml::Mapping;
+use std::path::Path;
 
 use self::{
   parse::{YAMLMatch, YAMLVariable},
   util::convert_params,
+  validate::find_unknown_fields,
+};
+use crate::matches::{
+  capture::{CaptureType, RegexCapture},
+  MatchCause, MatchEffect, TextEffect, TriggerCause,
 };
-use crate::matches::{MatchCause, MatchEffect, TextEffect, TriggerCause};
 
 use super::Importer;
 
 pub(crate) mod parse;
 mod util;
+pub(crate) mod validate;
 
 lazy_static! {
   static ref VAR_REGEX: Regex = Regex::new("\\{\\{\\s*(\\w+)(\\.\\w+)?\\s*\\}\\}").unwrap();
@@ -51,6 +58,9 @@ lazy_static! {
 // Create an alias to make the meaning more explicit
 type Warning = anyhow::Error;
 
+// Higher distances would make triggers match too much unrelated text
+const MAX_FUZZY_DISTANCE: usize = 2;
+
 pub(crate) struct YAMLImporter {}
 
 impl YAMLImporter {
@@ -64,62 +74,78 @@ impl Importer for YAMLImporter {
     extension == "yaml" || extension == "yml"
   }
 
-  fn load_group(
-    &self,
-    path: &std::path::Path,
-  ) -> anyhow::Result<(crate::matches::group::MatchGroup, Option<NonFatalErrorSet>)> {
+  fn load_group(&self, path: &Path) -> Result<(MatchGroup, Option<NonFatalErrorSet>)> {
+    let content = std::fs::read_to_string(path).context("failed to read YAML match group")?;
     let yaml_group =
-      YAMLMatchGroup::parse_from_file(path).context("failed to parse YAML match group")?;
+      YAMLMatchGroup::parse_from_str(&content).context("failed to parse YAML match group")?;
 
-    let mut non_fatal_errors = Vec::new();
+    // Serde silently ignores unknown fields, so typos must be detected separately
+    let unknown_fields = serde_yaml::from_str(&content)
+      .map(|value| find_unknown_fields(&value))
+      .unwrap_or_default();
 
-    let mut global_vars = Vec::new();
-    for yaml_global_var in yaml_group.global_vars.clone().unwrap_or_default() {
-      match try_convert_into_variable(yaml_global_var, false) {
-        Ok((var, warnings)) => {
-          global_vars.push(var);
-          non_fatal_errors.extend(warnings.into_iter().map(ErrorRecord::warn));
-        }
-        Err(err) => {
-          non_fatal_errors.push(ErrorRecord::error(err));
-        }
+    convert_group(path, yaml_group, unknown_fields)
+  }
+}
+
+/// Convert the given group into a `MatchGroup`, collecting all the non-fatal errors.
+/// This is shared by all the importers, as they deserialize into the same structure.
+pub(crate) fn convert_group(
+  path: &Path,
+  yaml_group: YAMLMatchGroup,
+  unknown_fields: Vec<UnknownFieldError>,
+) -> Result<(MatchGroup, Option<NonFatalErrorSet>)> {
+  let mut non_fatal_errors: Vec<ErrorRecord> = unknown_fields
+    .into_iter()
+    .map(|error| ErrorRecord::warn(error.into()))
+    .collect();
+
+  let mut global_vars = Vec::new();
+  for yaml_global_var in yaml_group.global_vars.unwrap_or_default() {
+    match try_convert_into_variable(yaml_global_var, false) {
+      Ok((var, warnings)) => {
+        global_vars.push(var);
+        non_fatal_errors.extend(warnings.into_iter().map(ErrorRecord::warn));
+      }
+      Err(err) => {
+        non_fatal_errors.push(ErrorRecord::error(err));
       }
     }
+  }
 
-    let mut matches = Vec::new();
-    for yaml_match in yaml_group.matches.clone().unwrap_or_default() {
-      match try_convert_into_match(yaml_match, false) {
-        Ok((m, warnings)) => {
-          matches.push(m);
-          non_fatal_errors.extend(warnings.into_iter().map(ErrorRecord::warn));
-        }
-        Err(err) => {
-          non_fatal_errors.push(ErrorRecord::error(err));
-        }
+  let mut matches = Vec::new();
+  for yaml_match in yaml_group.matches.unwrap_or_default() {
+    match try_convert_into_match(yaml_match, false) {
+      Ok((m, warnings)) => {
+        matches.push(m);
+        non_fatal_errors.extend(warnings.into_iter().map(ErrorRecord::warn));
+      }
+      Err(err) => {
+        non_fatal_errors.push(ErrorRecord::error(err));
       }
     }
+  }
 
-    // Resolve imports
-    let (resolved_imports, import_errors) =
-      resolve_imports(path, &yaml_group.imports.unwrap_or_default())
-        .context("failed to resolve YAML match group imports")?;
-    non_fatal_errors.extend(import_errors);
+  // Resolve imports
+  let (resolved_imports, import_errors) =
+    resolve_imports(path, &yaml_group.imports.unwrap_or_default())
+      .context("failed to resolve match group imports")?;
+  non_fatal_errors.extend(import_errors);
 
-    let non_fatal_error_set = if non_fatal_errors.is_empty() {
-      None
-    } else {
-      Some(NonFatalErrorSet::new(path, non_fatal_errors))
-    };
+  let non_fatal_error_set = if non_fatal_errors.is_empty() {
+    None
+  } else {
+    Some(NonFatalErrorSet::new(path, non_fatal_errors))
+  };
 
-    Ok((
-      MatchGroup {
-        imports: resolved_imports,
-        global_vars,
-        matches,
-      },
-      non_fatal_error_set,
-    ))
-  }
+  Ok((
+    MatchGroup {
+      imports: resolved_imports,
+      global_vars,
+      matches,
+    },
+    non_fatal_error_set,
+  ))
 }
 
 pub fn try_convert_into_match(
@@ -134,12 +160,53 @@ pub fn try_convert_into_match(
     ));
   }
 
+  let fuzzy = match yaml_match.fuzzy {
+    Some(fuzzy) if fuzzy > MAX_FUZZY_DISTANCE => {
+      warnings.push(anyhow!(
+        "the maximum supported 'fuzzy' distance is {}, but {} was specified",
+        MAX_FUZZY_DISTANCE,
+        fuzzy
+      ));
+      MAX_FUZZY_DISTANCE
+    }
+    Some(fuzzy) => fuzzy,
+    None => TriggerCause::default().fuzzy,
+  };
+
+  if yaml_match.hotkey.is_some()
+    && (yaml_match.trigger.is_some() || yaml_match.triggers.is_some() || yaml_match.regex.is_some())
+  {
+    warnings.push(anyhow!(
+      "the 'hotkey' option is ignored when a 'trigger', 'triggers' or 'regex' is also specified"
+    ));
+  }
+
   let triggers = if let Some(trigger) = yaml_match.trigger {
     Some(vec![trigger])
   } else {
     yaml_match.triggers
   };
 
+  if yaml_match.fuzzy.is_some() && triggers.is_none() {
+    warnings.push(anyhow!(
+      "the 'fuzzy' option only has effect on matches with a 'trigger' or 'triggers'"
+    ));
+  }
+
+  if (yaml_match.max_typing_interval_ms.is_some() || yaml_match.min_typing_interval_ms.is_some())
+    && triggers.is_none()
+  {
+    warnings.push(anyhow!(
+      "the 'max_typing_interval_ms' and 'min_typing_interval_ms' options only have effect on matches with a 'trigger' or 'triggers'"
+    ));
+  }
+
+  if yaml_match.captures.is_some() && (triggers.is_some() || yaml_match.regex.is_none()) {
+    warnings.push(anyhow!(
+      "the 'captures' option only has effect on matches with a 'regex'"
+    ));
+  }
+
   let uppercase_style = match yaml_match
     .uppercase_style
     .map(|s| s.to_lowercase())
@@ -173,10 +240,19 @@ pub fn try_convert_into_match(
         .propagate_case
         .unwrap_or(TriggerCause::default().propagate_case),
       uppercase_style,
+      fuzzy,
+      max_typing_interval_ms: yaml_match.max_typing_interval_ms,
+      min_typing_interval_ms: yaml_match.min_typing_interval_ms,
     })
   } else if let Some(regex) = yaml_match.regex {
     // TODO: add test case
-    MatchCause::Regex(RegexCause { regex })
+    let captures = yaml_match
+      .captures
+      .map(|captures| convert_captures(&regex, captures, &mut warnings))
+      .unwrap_or_default();
+    MatchCause::Regex(RegexCause { regex, captures })
+  } else if let Some(hotkey) = yaml_match.hotkey {
+    MatchCause::Hotkey(HotkeyCause { hotkey })
   } else {
     MatchCause::None
   };
@@ -295,10 +371,39 @@ pub fn try_convert_into_match(
     );
   }
 
+  // The filters are compiled once here, as they are evaluated on every keystroke
+  let compile_filter = |filter: Option<String>| -> Result<Option<FilterRegex>> {
+    filter
+      .map(|filter| {
+        FilterRegex::new(&filter).with_context(|| format!("invalid match filter regex: {filter:?}"))
+      })
+      .transpose()
+  };
+  let title_filter = compile_filter(yaml_match.filter_title)?;
+  let class_filter = compile_filter(yaml_match.filter_class)?;
+  let exec_filter = compile_filter(yaml_match.filter_exec)?;
+
+  if let Some(os) = yaml_match.filter_os.as_deref() {
+    if !matches!(os, "linux" | "macos" | "windows") {
+      warnings.push(anyhow!(
+        "unrecognized filter_os: {:?}, the match will never be active",
+        os
+      ));
+    }
+  }
+
+  let app_filter = AppFilter {
+    title: title_filter,
+    class: class_filter,
+    exec: exec_filter,
+    os: yaml_match.filter_os,
+  };
+
   Ok((
     Match {
       cause,
       effect,
+      app_filter,
       label: yaml_match.label,
       id: next_id(),
       search_terms: yaml_match.search_terms.unwrap_or_default(),
@@ -307,6 +412,100 @@ pub fn try_convert_into_match(
   ))
 }
 
+// Convert the type declarations of the named captures of a regex. Invalid
+// declarations are reported as warnings and skipped, so those captures are
+// handled as plain strings.
+fn convert_captures(
+  regex: &str,
+  captures: Mapping,
+  warnings: &mut Vec<Warning>,
+) -> Vec<RegexCapture> {
+  let group_names: Option<Vec<String>> = Regex::new(regex)
+    .ok()
+    .map(|regex| regex.capture_names().flatten().map(String::from).collect());
+
+  let mut converted = Vec::new();
+  for (name, declaration) in captures {
+    let Some(name) = name.as_str().map(String::from) else {
+      warnings.push(anyhow!("invalid capture name: {:?}", name));
+      continue;
+    };
+
+    if let Some(group_names) = &group_names {
+      if !group_names.contains(&name) {
+        warnings.push(anyhow!(
+          "capture '{}' is not a named group of regex: '{}'",
+          name,
+          regex
+        ));
+        continue;
+      }
+    }
+
+    let (type_declaration, default) = match declaration {
+      serde_yaml::Value::String(declaration) => (Some(declaration), None),
+      serde_yaml::Value::Mapping(options) => {
+        for key in options.iter().filter_map(|(key, _)| key.as_str()) {
+          if key != "type" && key != "default" {
+            warnings.push(anyhow!(
+              "unknown option '{}' of capture '{}', expected 'type' or 'default'",
+              key,
+              name
+            ));
+          }
+        }
+        let option = |key: &str| {
+          options
+            .get(&serde_yaml::Value::String(key.to_string()))
+            .and_then(scalar_to_string)
+        };
+        (option("type"), option("default"))
+      }
+      _ => {
+        warnings.push(anyhow!(
+          "capture '{}' must be declared as a type or as a mapping with 'type' and 'default'",
+          name
+        ));
+        continue;
+      }
+    };
+
+    let capture_type = match type_declaration.as_deref().map(CaptureType::parse) {
+      Some(Ok(capture_type)) => capture_type,
+      Some(Err(err)) => {
+        warnings.push(anyhow!("capture '{}': {}", name, err));
+        continue;
+      }
+      None => CaptureType::String,
+    };
+
+    let default = default.filter(|default| match capture_type.parse_value(default) {
+      Ok(_) => true,
+      Err(err) => {
+        warnings.push(anyhow!("ignoring default of capture '{}': {}", name, err));
+        false
+      }
+    });
+
+    converted.push(RegexCapture {
+      name,
+      capture_type,
+      default,
+    });
+  }
+
+  converted
+}
+
+fn scalar_to_string(value: &serde_yaml::Value) -> Option<String> {
+  match value {
+    serde_yaml::Value::String(value) => Some(value.clone()),
+    serde_yaml::Value::Number(value) => Some(value.to_string()),
+    serde_yaml::Value::Bool(value) => Some(value.to_string()),
+    _ => None,
+  }
+}
+
 pub fn try_convert_into_variable(
   yaml_var: YAMLVariable,
   use_compatibility_mode: bool,
@@ -328,6 +527,7 @@ pub fn try_convert_into_variable(
 mod tests {
   use super::*;
   use crate::{
+    error::ErrorLevel,
     matches::{Match, Params, Value},
     util::tests::use_test_directory,
   };
@@ -511,6 +711,157 @@ mod tests {
     );
   }
 
+  #[test]
+  fn fuzzy_maps_correctly() {
+    assert_eq!(
+      create_match(
+        r#"
+        trigger: "receive"
+        replace: "receive"
+        fuzzy: 1
+        "#
+      )
+      .unwrap(),
+      Match {
+        cause: MatchCause::Trigger(TriggerCause {
+          triggers: vec!["receive".to_string()],
+          fuzzy: 1,
+          ..Default::default()
+        }),
+        effect: MatchEffect::Text(TextEffect {
+          replace: "receive".to_string(),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn fuzzy_distance_is_capped() {
+    let (m, warnings) = create_match_with_warnings(
+      r#"
+      trigger: "receive"
+      replace: "receive"
+      fuzzy: 5
+      "#,
+      false,
+    )
+    .unwrap();
+    let MatchCause::Trigger(cause) = m.cause else {
+      panic!("expected a trigger cause");
+    };
+    assert_eq!(cause.fuzzy, MAX_FUZZY_DISTANCE);
+    assert_eq!(warnings.len(), 1);
+  }
+
+  #[test]
+  fn typing_intervals_map_correctly() {
+    assert_eq!(
+      create_match(
+        r#"
+        trigger: ":date"
+        replace: "today"
+        max_typing_interval_ms: 200
+        min_typing_interval_ms: 500
+        "#
+      )
+      .unwrap(),
+      Match {
+        cause: MatchCause::Trigger(TriggerCause {
+          triggers: vec![":date".to_string()],
+          max_typing_interval_ms: Some(200),
+          min_typing_interval_ms: Some(500),
+          ..Default::default()
+        }),
+        effect: MatchEffect::Text(TextEffect {
+          replace: "today".to_string(),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn typing_intervals_without_triggers_warn() {
+    let (_, warnings) = create_match_with_warnings(
+      r#"
+      regex: "hello"
+      replace: "world"
+      max_typing_interval_ms: 200
+      "#,
+      false,
+    )
+    .unwrap();
+    assert_eq!(warnings.len(), 1);
+  }
+
+  #[test]
+  fn regex_captures_map_correctly() {
+    assert_eq!(
+      create_match(
+        r#"
+        regex: ":add\\((?P<amount>\\d+)(?P<unit>kg|lb)?\\)"
+        replace: "{{amount}}"
+        captures:
+          amount: int
+          unit:
+            type: enum[kg,lb]
+            default: kg
+        "#
+      )
+      .unwrap()
+      .cause,
+      MatchCause::Regex(RegexCause {
+        regex: ":add\\((?P<amount>\\d+)(?P<unit>kg|lb)?\\)".to_string(),
+        captures: vec![
+          RegexCapture {
+            name: "amount".to_string(),
+            capture_type: CaptureType::Int,
+            default: None,
+          },
+          RegexCapture {
+            name: "unit".to_string(),
+            capture_type: CaptureType::Enum(vec!["kg".to_string(), "lb".to_string()]),
+            default: Some("kg".to_string()),
+          },
+        ],
+      })
+    );
+  }
+
+  #[test]
+  fn regex_captures_invalid_declarations_are_reported() {
+    let (m, warnings) = create_match_with_warnings(
+      r#"
+      regex: ":add\\((?P<amount>\\d+),(?P<count>\\d+)\\)"
+      replace: "{{amount}}"
+      captures:
+        amount: float
+        count:
+          type: int
+          default: many
+        missing: int
+      "#,
+      false,
+    )
+    .unwrap();
+
+    assert_eq!(
+      m.cause,
+      MatchCause::Regex(RegexCause {
+        regex: ":add\\((?P<amount>\\d+),(?P<count>\\d+)\\)".to_string(),
+        captures: vec![RegexCapture {
+          name: "count".to_string(),
+          capture_type: CaptureType::Int,
+          default: None,
+        }],
+      })
+    );
+    assert_eq!(warnings.len(), 3);
+  }
+
   #[test]
   fn uppercase_style_maps_correctly() {
     assert_eq!(
@@ -598,6 +949,95 @@ mod tests {
     assert_eq!(warnings.len(), 1);
   }
 
+  #[test]
+  fn hotkey_maps_correctly() {
+    assert_eq!(
+      create_match(
+        r#"
+        hotkey: "ALT+SHIFT+H"
+        replace: "world"
+        "#
+      )
+      .unwrap(),
+      Match {
+        cause: MatchCause::Hotkey(HotkeyCause {
+          hotkey: "ALT+SHIFT+H".to_string(),
+        }),
+        effect: MatchEffect::Text(TextEffect {
+          replace: "world".to_string(),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn hotkey_with_trigger_is_ignored() {
+    let (m, warnings) = create_match_with_warnings(
+      r#"
+        trigger: "Hello"
+        hotkey: "ALT+SHIFT+H"
+        replace: "world"
+        "#,
+      false,
+    )
+    .unwrap();
+    assert!(m.cause.into_trigger().is_ok());
+    assert_eq!(warnings.len(), 1);
+  }
+
+  #[test]
+  fn filters_map_correctly() {
+    assert_eq!(
+      create_match(
+        r#"
+        trigger: "Hello"
+        replace: "world"
+        filter_title: "Chrome"
+        filter_exec: "chrome\\.exe"
+        filter_class: "Chromium"
+        filter_os: "linux"
+        "#
+      )
+      .unwrap()
+      .app_filter,
+      AppFilter {
+        title: Some(FilterRegex::new("Chrome").unwrap()),
+        class: Some(FilterRegex::new("Chromium").unwrap()),
+        exec: Some(FilterRegex::new("chrome\\.exe").unwrap()),
+        os: Some("linux".to_string()),
+      }
+    );
+  }
+
+  #[test]
+  fn invalid_filter_regex_is_rejected() {
+    assert!(create_match(
+      r#"
+        trigger: "Hello"
+        replace: "world"
+        filter_title: "[invalid"
+        "#
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn invalid_filter_os_produces_warning() {
+    let (m, warnings) = create_match_with_warnings(
+      r#"
+        trigger: "Hello"
+        replace: "world"
+        filter_os: "plan9"
+        "#,
+      false,
+    )
+    .unwrap();
+    assert_eq!(m.app_filter.os.as_deref(), Some("plan9"));
+    assert_eq!(warnings.len(), 1);
+  }
+
   #[test]
   fn form_maps_correctly() {
     let mut params = Params::new();
@@ -920,4 +1360,33 @@ mod tests {
       assert!(importer.load_group(&base_file).is_err());
     });
   }
+
+  #[test]
+  fn importer_reports_unknown_fields() {
+    use_test_directory(|_, match_dir, _| {
+      let base_file = match_dir.join("base.yml");
+      std::fs::write(
+        &base_file,
+        r#"
+      matches:
+        - trigger: "hello"
+          replace: "world"
+          wrod: true
+      "#,
+      )
+      .unwrap();
+
+      let importer = YAMLImporter::new();
+      let (group, non_fatal_error_set) = importer.load_group(&base_file).unwrap();
+      assert_eq!(group.matches.len(), 1);
+
+      let errors = non_fatal_error_set.unwrap().errors;
+      assert_eq!(errors.len(), 1);
+      assert_eq!(errors[0].level, ErrorLevel::Warning);
+      assert_eq!(
+        errors[0].error.to_string(),
+        "unknown field `wrod` in match \"hello\", did you mean `word`?"
+      );
+    });
+  }
 }
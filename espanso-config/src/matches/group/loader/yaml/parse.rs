@@ -17,13 +17,11 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::path::Path;
-
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Mapping;
 
-use crate::util::is_yaml_empty;
+use crate::util::{field_names, is_yaml_empty};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct YAMLMatchGroup {
@@ -48,15 +46,9 @@ impl YAMLMatchGroup {
 
     Ok(serde_yaml::from_str(yaml)?)
   }
-
-  // TODO: test
-  pub fn parse_from_file(path: &Path) -> Result<Self> {
-    let content = std::fs::read_to_string(path)?;
-    Self::parse_from_str(&content)
-  }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct YAMLMatch {
   #[serde(default)]
   pub label: Option<String>,
@@ -70,6 +62,12 @@ pub struct YAMLMatch {
   #[serde(default)]
   pub regex: Option<String>,
 
+  #[serde(default)]
+  pub captures: Option<Mapping>,
+
+  #[serde(default)]
+  pub hotkey: Option<String>,
+
   #[serde(default)]
   pub replace: Option<String>,
 
@@ -100,6 +98,15 @@ pub struct YAMLMatch {
   #[serde(default)]
   pub uppercase_style: Option<String>,
 
+  #[serde(default)]
+  pub fuzzy: Option<usize>,
+
+  #[serde(default)]
+  pub max_typing_interval_ms: Option<usize>,
+
+  #[serde(default)]
+  pub min_typing_interval_ms: Option<usize>,
+
   #[serde(default)]
   pub force_clipboard: Option<bool>,
 
@@ -117,6 +124,18 @@ pub struct YAMLMatch {
 
   #[serde(default)]
   pub search_terms: Option<Vec<String>>,
+
+  #[serde(default)]
+  pub filter_title: Option<String>,
+
+  #[serde(default)]
+  pub filter_class: Option<String>,
+
+  #[serde(default)]
+  pub filter_exec: Option<String>,
+
+  #[serde(default)]
+  pub filter_os: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -139,3 +158,23 @@ pub struct YAMLVariable {
 fn default_params() -> Mapping {
   Mapping::new()
 }
+
+impl YAMLMatch {
+  /// Return the names of all the keys accepted inside a match definition
+  pub fn field_names() -> Vec<String> {
+    field_names(&YAMLMatch::default())
+  }
+}
+
+impl YAMLVariable {
+  /// Return the names of all the keys accepted inside a variable definition
+  pub fn field_names() -> Vec<String> {
+    field_names(&YAMLVariable {
+      name: String::new(),
+      var_type: String::new(),
+      params: default_params(),
+      inject_vars: None,
+      depends_on: Vec::new(),
+    })
+  }
+}
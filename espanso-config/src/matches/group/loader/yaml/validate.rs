@@ -0,0 +1,202 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use lazy_static::lazy_static;
+use serde_yaml::{Mapping, Value};
+
+use super::parse::{YAMLMatch, YAMLVariable};
+use crate::error::UnknownFieldError;
+
+lazy_static! {
+  pub(crate) static ref MATCH_FIELDS: Vec<String> = YAMLMatch::field_names();
+  pub(crate) static ref VARIABLE_FIELDS: Vec<String> = YAMLVariable::field_names();
+}
+
+// Merge keys are resolved by the YAML parser, so they are always accepted
+pub(crate) const MERGE_KEY: &str = "<<";
+
+/// Find the fields of matches and variables that would be silently ignored
+/// by serde when deserializing the given match group
+pub(crate) fn find_unknown_fields(group: &Value) -> Vec<UnknownFieldError> {
+  let Value::Mapping(root) = group else {
+    return Vec::new();
+  };
+
+  let mut errors = Vec::new();
+
+  for var in sequence(root, "global_vars") {
+    let location = format!("global variable {}", describe_variable(var));
+    check_fields(var, &VARIABLE_FIELDS, &location, &mut errors);
+  }
+
+  for (index, m) in sequence(root, "matches").iter().enumerate() {
+    let match_location = format!("match {}", describe_match(m, index));
+    check_fields(m, &MATCH_FIELDS, &match_location, &mut errors);
+
+    if let Value::Mapping(mapping) = m {
+      for var in sequence(mapping, "vars") {
+        let location = format!("variable {} of {match_location}", describe_variable(var));
+        check_fields(var, &VARIABLE_FIELDS, &location, &mut errors);
+      }
+    }
+  }
+
+  errors
+}
+
+fn sequence<'a>(mapping: &'a Mapping, key: &str) -> &'a [Value] {
+  match mapping.get(&Value::String(key.to_string())) {
+    Some(Value::Sequence(items)) => items,
+    _ => &[],
+  }
+}
+
+fn check_fields(
+  value: &Value,
+  known_fields: &[String],
+  location: &str,
+  errors: &mut Vec<UnknownFieldError>,
+) {
+  let Value::Mapping(mapping) = value else {
+    return;
+  };
+
+  for key in mapping.iter().filter_map(|(key, _)| key.as_str()) {
+    if key != MERGE_KEY && !known_fields.iter().any(|field| field == key) {
+      errors.push(UnknownFieldError::new(key, location, known_fields));
+    }
+  }
+}
+
+fn describe_match(m: &Value, index: usize) -> String {
+  let cause = ["trigger", "regex", "hotkey", "label"]
+    .into_iter()
+    .find_map(|field| m.get(field).and_then(Value::as_str))
+    .or_else(|| {
+      m.get("triggers")
+        .and_then(Value::as_sequence)
+        .and_then(|triggers| triggers.first())
+        .and_then(Value::as_str)
+    });
+
+  match cause {
+    Some(cause) => format!("{cause:?}"),
+    None => format!("#{}", index + 1),
+  }
+}
+
+fn describe_variable(var: &Value) -> String {
+  match var.get("name").and_then(Value::as_str) {
+    Some(name) => format!("{name:?}"),
+    None => "without name".to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn messages(yaml: &str) -> Vec<String> {
+    let group = serde_yaml::from_str(yaml).unwrap_or(Value::Null);
+    find_unknown_fields(&group)
+      .iter()
+      .map(ToString::to_string)
+      .collect()
+  }
+
+  #[test]
+  fn valid_group_has_no_unknown_fields() {
+    assert!(messages(
+      r#"
+      global_vars:
+        - name: name
+          type: echo
+          params:
+            echo: "Jon"
+
+      matches:
+        - trigger: ":hi"
+          replace: "Hi {{name}}"
+          word: true
+          vars:
+            - name: time
+              type: date
+              params:
+                format: "%H:%M"
+      "#
+    )
+    .is_empty());
+  }
+
+  #[test]
+  fn typos_in_matches_are_reported() {
+    assert_eq!(
+      messages(
+        r#"
+        matches:
+          - tirgger: ":hi"
+            replace: "Hi"
+          - trigger: ":bye"
+            repalce: "Bye"
+          - trigger: ":other"
+            replace: "other"
+            something: true
+        "#
+      ),
+      vec![
+        "unknown field `tirgger` in match #1, did you mean `trigger`?",
+        "unknown field `repalce` in match \":bye\", did you mean `replace`?",
+        "unknown field `something` in match \":other\"",
+      ]
+    );
+  }
+
+  #[test]
+  fn typos_in_variables_are_reported() {
+    assert_eq!(
+      messages(
+        r#"
+        global_vars:
+          - name: first
+            type: echo
+            parms:
+              echo: "a"
+
+        matches:
+          - trigger: ":hi"
+            replace: "{{second}}"
+            vars:
+              - name: second
+                type: echo
+                dependson: ["first"]
+        "#
+      ),
+      vec![
+        "unknown field `parms` in global variable \"first\", did you mean `params`?",
+        "unknown field `dependson` in variable \"second\" of match \":hi\", did you mean `depends_on`?",
+      ]
+    );
+  }
+
+  #[test]
+  fn invalid_yaml_is_ignored() {
+    assert!(messages("matches: [").is_empty());
+    assert!(messages("").is_empty());
+  }
+}
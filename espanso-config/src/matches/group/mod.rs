@@ -25,7 +25,7 @@ use crate::error::NonFatalErrorSet;
 use super::{Match, Variable};
 
 pub(crate) mod loader;
-mod path;
+pub(crate) mod path;
 
 #[derive(Debug, Clone, PartialEq, Default)]
 pub(crate) struct MatchGroup {
@@ -57,17 +57,20 @@ pub fn resolve_imports(
       import_path
     };
 
-    match dunce::canonicalize(&full_path)
-      .with_context(|| format!("unable to canonicalize import path: {full_path:?}"))
-    {
+    // Best effort imports
+    match dunce::canonicalize(&full_path).with_context(|| ResolveImportError::Unresolved {
+      import: import.clone(),
+      path: full_path.clone(),
+    }) {
       Ok(canonical_path) => {
         if canonical_path.exists() && canonical_path.is_file() {
           paths.push(canonical_path);
         } else {
-          // Best effort imports
           non_fatal_errors.push(ErrorRecord::error(anyhow!(
-            "unable to resolve import at path: {:?}",
-            canonical_path
+            ResolveImportError::Unresolved {
+              import: import.clone(),
+              path: canonical_path,
+            }
           )));
         }
       }
@@ -87,6 +90,9 @@ pub fn resolve_imports(
 pub enum ResolveImportError {
   #[error("resolve import failed: `{0}`")]
   Failed(String),
+
+  #[error("unable to resolve import `{import}` at path: {path:?}")]
+  Unresolved { import: String, path: PathBuf },
 }
 
 #[cfg(test)]
@@ -133,6 +139,10 @@ pub mod tests {
 
       // The "sub/invalid.yml" should generate an error
       assert_eq!(errors.len(), 1);
+      assert!(matches!(
+        errors[0].error.downcast_ref::<ResolveImportError>(),
+        Some(ResolveImportError::Unresolved { import, .. }) if import == "sub/invalid.yml"
+      ));
     });
   }
 
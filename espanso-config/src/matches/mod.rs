@@ -19,10 +19,15 @@
 
 use enum_as_inner::EnumAsInner;
 use ordered_float::OrderedFloat;
+use regex::Regex;
 use std::collections::BTreeMap;
 
-use crate::counter::StructId;
+use crate::{
+  config::{util::os_matches, AppProperties},
+  counter::StructId,
+};
 
+pub mod capture;
 pub(crate) mod group;
 pub mod store;
 
@@ -32,6 +37,7 @@ pub struct Match {
 
   pub cause: MatchCause,
   pub effect: MatchEffect,
+  pub app_filter: AppFilter,
 
   // Metadata
   pub label: Option<String>,
@@ -43,6 +49,7 @@ impl Default for Match {
     Self {
       cause: MatchCause::None,
       effect: MatchEffect::None,
+      app_filter: AppFilter::default(),
       label: None,
       id: 0,
       search_terms: vec![],
@@ -79,6 +86,79 @@ impl Match {
   }
 }
 
+// Filters
+
+/// Restricts a match to the applications satisfying all the specified filters.
+/// The title, class and exec filters are regular expressions, compiled when
+/// the match is loaded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct AppFilter {
+  pub title: Option<FilterRegex>,
+  pub class: Option<FilterRegex>,
+  pub exec: Option<FilterRegex>,
+  pub os: Option<String>,
+}
+
+/// A compiled filter regex, compared and hashed by its pattern
+#[derive(Debug, Clone)]
+pub struct FilterRegex(Regex);
+
+impl FilterRegex {
+  pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+    Regex::new(pattern).map(Self)
+  }
+
+  pub fn as_str(&self) -> &str {
+    self.0.as_str()
+  }
+
+  pub fn is_match(&self, text: &str) -> bool {
+    self.0.is_match(text)
+  }
+}
+
+impl PartialEq for FilterRegex {
+  fn eq(&self, other: &Self) -> bool {
+    self.as_str() == other.as_str()
+  }
+}
+
+impl Eq for FilterRegex {}
+
+impl std::hash::Hash for FilterRegex {
+  fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    self.as_str().hash(state);
+  }
+}
+
+impl AppFilter {
+  pub fn is_empty(&self) -> bool {
+    self.title.is_none() && self.class.is_none() && self.exec.is_none() && self.os.is_none()
+  }
+
+  pub fn is_match(&self, app: &AppProperties) -> bool {
+    let is_os_match = if let Some(os) = self.os.as_deref() {
+      os_matches(os)
+    } else {
+      true
+    };
+
+    // All the filters that have been specified must be true to define a match
+    is_os_match
+      && is_property_match(self.title.as_ref(), app.title)
+      && is_property_match(self.exec.as_ref(), app.exec)
+      && is_property_match(self.class.as_ref(), app.class)
+  }
+}
+
+fn is_property_match(filter: Option<&FilterRegex>, property: Option<&str>) -> bool {
+  match (filter, property) {
+    (None, _) => true,
+    (Some(filter), Some(property)) => filter.is_match(property),
+    (Some(_), None) => false,
+  }
+}
+
 // Causes
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, EnumAsInner)]
@@ -86,7 +166,7 @@ pub enum MatchCause {
   None,
   Trigger(TriggerCause),
   Regex(RegexCause),
-  // TODO: shortcut
+  Hotkey(HotkeyCause),
 }
 
 impl MatchCause {
@@ -94,18 +174,18 @@ impl MatchCause {
     match &self {
       MatchCause::Trigger(trigger_cause) => trigger_cause.triggers.first().map(String::as_str),
       MatchCause::Regex(trigger_cause) => Some(trigger_cause.regex.as_str()),
+      MatchCause::Hotkey(hotkey_cause) => Some(hotkey_cause.hotkey.as_str()),
       MatchCause::None => None,
     }
-    // TODO: insert rendering for hotkey/shortcut
   }
 
   pub fn long_description(&self) -> String {
     match &self {
       MatchCause::Trigger(trigger_cause) => format!("triggers: {:?}", trigger_cause.triggers),
       MatchCause::Regex(trigger_cause) => format!("regex: {:?}", trigger_cause.regex),
+      MatchCause::Hotkey(hotkey_cause) => format!("hotkey: {:?}", hotkey_cause.hotkey),
       MatchCause::None => "No description available".to_owned(),
     }
-    // TODO: insert rendering for hotkey/shortcut
   }
 
   pub fn search_terms(&self) -> Vec<&str> {
@@ -126,6 +206,15 @@ pub struct TriggerCause {
 
   pub propagate_case: bool,
   pub uppercase_style: UpperCasingStyle,
+
+  // Maximum number of typos (transposed or missing characters)
+  // tolerated when typing the triggers, 0 for exact matching
+  pub fuzzy: usize,
+
+  // Maximum number of milliseconds between two keystrokes of the trigger
+  pub max_typing_interval_ms: Option<usize>,
+  // Minimum number of milliseconds without typing before the trigger
+  pub min_typing_interval_ms: Option<usize>,
 }
 
 impl Default for TriggerCause {
@@ -136,6 +225,9 @@ impl Default for TriggerCause {
       right_word: false,
       propagate_case: false,
       uppercase_style: UpperCasingStyle::Uppercase,
+      fuzzy: 0,
+      max_typing_interval_ms: None,
+      min_typing_interval_ms: None,
     }
   }
 }
@@ -150,6 +242,14 @@ pub enum UpperCasingStyle {
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct RegexCause {
   pub regex: String,
+
+  // Type declarations of the named captures, the undeclared ones are strings
+  pub captures: Vec<capture::RegexCapture>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct HotkeyCause {
+  pub hotkey: String,
 }
 
 // Effects
@@ -253,6 +353,7 @@ mod tests {
   fn regex_cause() -> RegexCause {
     RegexCause {
       regex: ":greet\\d".to_string(),
+      ..RegexCause::default()
     }
   }
 
@@ -269,6 +370,86 @@ mod tests {
     assert_eq!(MatchCause::Regex(regex).description(), Some(":greet\\d"));
   }
 
+  fn hotkey_cause() -> HotkeyCause {
+    HotkeyCause {
+      hotkey: "CTRL+SHIFT+G".to_string(),
+    }
+  }
+
+  #[test]
+  fn match_cause_hotkey_description() {
+    let hotkey = hotkey_cause();
+    assert_eq!(
+      MatchCause::Hotkey(hotkey).description(),
+      Some("CTRL+SHIFT+G")
+    );
+  }
+
+  #[test]
+  fn app_filter_empty_matches_everything() {
+    let filter = AppFilter::default();
+    assert!(filter.is_empty());
+    assert!(filter.is_match(&AppProperties {
+      title: Some("Google Chrome"),
+      class: None,
+      exec: None,
+    }));
+  }
+
+  #[test]
+  fn app_filter_all_filters_must_match() {
+    let filter = AppFilter {
+      title: Some(FilterRegex::new("Chrome").unwrap()),
+      exec: Some(FilterRegex::new("chrome\\.exe").unwrap()),
+      ..Default::default()
+    };
+    assert!(!filter.is_empty());
+    assert!(filter.is_match(&AppProperties {
+      title: Some("Google Chrome"),
+      class: None,
+      exec: Some("C:\\Programs\\chrome.exe"),
+    }));
+    assert!(!filter.is_match(&AppProperties {
+      title: Some("Google Chrome"),
+      class: None,
+      exec: Some("C:\\Programs\\firefox.exe"),
+    }));
+    assert!(!filter.is_match(&AppProperties {
+      title: Some("Google Chrome"),
+      class: None,
+      exec: None,
+    }));
+  }
+
+  #[test]
+  fn app_filter_os() {
+    let (current, another) = if cfg!(target_os = "windows") {
+      ("windows", "macos")
+    } else if cfg!(target_os = "macos") {
+      ("macos", "windows")
+    } else if cfg!(target_os = "linux") {
+      ("linux", "macos")
+    } else {
+      ("invalid", "invalid")
+    };
+
+    let app = AppProperties {
+      title: None,
+      class: None,
+      exec: None,
+    };
+    assert!(AppFilter {
+      os: Some(current.to_string()),
+      ..Default::default()
+    }
+    .is_match(&app));
+    assert!(!AppFilter {
+      os: Some(another.to_string()),
+      ..Default::default()
+    }
+    .is_match(&app));
+  }
+
   #[test]
   fn match_cause_trigger_long_description() {
     let trigger = trigger_cause();
@@ -288,4 +469,14 @@ mod tests {
       r#"regex: ":greet\\d""#
     );
   }
+
+  #[test]
+  fn match_cause_hotkey_long_description() {
+    let hotkey = hotkey_cause();
+
+    assert_eq!(
+      MatchCause::Hotkey(hotkey).long_description(),
+      r#"hotkey: "CTRL+SHIFT+G""#
+    );
+  }
 }
@@ -0,0 +1,241 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+
+use super::{
+  default::{DefaultMatchStore, QueryPlan},
+  MatchSet, MatchStore,
+};
+use crate::error::NonFatalErrorSet;
+
+/// A `MatchStore` that memoizes the query plans computed by the
+/// underlying `DefaultMatchStore`, so that repeated queries for the
+/// same paths (for example, each time the active app changes) don't
+/// have to traverse the whole import tree again.
+pub(crate) struct CachedMatchStore {
+  store: DefaultMatchStore,
+  cache: Mutex<HashMap<Vec<String>, Arc<QueryPlan>>>,
+}
+
+impl CachedMatchStore {
+  pub fn new(store: DefaultMatchStore) -> Self {
+    Self {
+      store,
+      cache: Mutex::new(HashMap::new()),
+    }
+  }
+
+  fn plan(&self, paths: &[String]) -> Arc<QueryPlan> {
+    let mut cache = self.cache.lock().expect("unable to lock match store cache");
+    if let Some(plan) = cache.get(paths) {
+      return Arc::clone(plan);
+    }
+
+    let plan = Arc::new(self.store.plan(paths));
+    cache.insert(paths.to_vec(), Arc::clone(&plan));
+    plan
+  }
+}
+
+impl MatchStore for CachedMatchStore {
+  fn query(&self, paths: &[String]) -> MatchSet<'_> {
+    let plan = self.plan(paths);
+    self.store.materialize(&plan)
+  }
+
+  fn loaded_paths(&self) -> Vec<String> {
+    self.store.loaded_paths()
+  }
+
+  fn reload(
+    &self,
+    changed_paths: &[String],
+  ) -> Option<(Box<dyn MatchStore>, Vec<NonFatalErrorSet>)> {
+    let (store, non_fatal_error_sets) = self.store.reload(changed_paths);
+    Some((Box::new(CachedMatchStore::new(store)), non_fatal_error_sets))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::util::tests::use_test_directory;
+
+  #[test]
+  fn cached_store_returns_same_results_as_default() {
+    use_test_directory(|_, match_dir, _| {
+      let base_file = match_dir.join("base.yml");
+      std::fs::write(
+        &base_file,
+        r#"
+      imports:
+        - "_another.yml"
+
+      global_vars:
+        - name: var1
+          type: test
+
+      matches:
+        - trigger: "hello"
+          replace: "world"
+      "#,
+      )
+      .unwrap();
+
+      let another_file = match_dir.join("_another.yml");
+      std::fs::write(
+        &another_file,
+        r#"
+      matches:
+        - trigger: "foo"
+          replace: "bar"
+      "#,
+      )
+      .unwrap();
+
+      let paths = vec![base_file.to_string_lossy().to_string()];
+      let (default_store, _) = DefaultMatchStore::load(&paths);
+      let expected: Vec<_> = default_store
+        .query(&paths)
+        .matches
+        .into_iter()
+        .map(|m| m.id)
+        .collect();
+
+      let cached_store = CachedMatchStore::new(default_store);
+      let first = cached_store.query(&paths);
+      let second = cached_store.query(&paths);
+
+      assert_eq!(first, second);
+      assert_eq!(
+        first.matches.iter().map(|m| m.id).collect::<Vec<_>>(),
+        expected
+      );
+      assert_eq!(first.global_vars.len(), 1);
+      assert_eq!(cached_store.cache.lock().unwrap().len(), 1);
+    });
+  }
+
+  #[test]
+  fn cached_store_distinguishes_queries() {
+    use_test_directory(|_, match_dir, _| {
+      let base_file = match_dir.join("base.yml");
+      std::fs::write(
+        &base_file,
+        r#"
+      matches:
+        - trigger: "hello"
+          replace: "world"
+      "#,
+      )
+      .unwrap();
+
+      let another_file = match_dir.join("another.yml");
+      std::fs::write(
+        &another_file,
+        r#"
+      matches:
+        - trigger: "foo"
+          replace: "bar"
+      "#,
+      )
+      .unwrap();
+
+      let base_path = base_file.to_string_lossy().to_string();
+      let another_path = another_file.to_string_lossy().to_string();
+      let (default_store, _) = DefaultMatchStore::load(&[base_path.clone(), another_path.clone()]);
+      let cached_store = CachedMatchStore::new(default_store);
+
+      assert_eq!(
+        cached_store
+          .query(std::slice::from_ref(&base_path))
+          .matches
+          .len(),
+        1
+      );
+      assert_eq!(
+        cached_store
+          .query(std::slice::from_ref(&another_path))
+          .matches
+          .len(),
+        1
+      );
+      assert_eq!(
+        cached_store.query(&[base_path, another_path]).matches.len(),
+        2
+      );
+      assert_eq!(cached_store.cache.lock().unwrap().len(), 3);
+    });
+  }
+
+  #[test]
+  fn cached_store_reload_returns_updated_store() {
+    use_test_directory(|_, match_dir, _| {
+      let base_file = match_dir.join("base.yml");
+      std::fs::write(
+        &base_file,
+        r#"
+      matches:
+        - trigger: "hello"
+          replace: "world"
+      "#,
+      )
+      .unwrap();
+
+      let base_path = base_file.to_string_lossy().to_string();
+      let (default_store, _) = DefaultMatchStore::load(std::slice::from_ref(&base_path));
+      let cached_store = CachedMatchStore::new(default_store);
+      assert_eq!(
+        cached_store
+          .query(std::slice::from_ref(&base_path))
+          .matches
+          .len(),
+        1
+      );
+
+      std::fs::write(
+        &base_file,
+        r#"
+      matches:
+        - trigger: "hello"
+          replace: "world"
+        - trigger: "foo"
+          replace: "bar"
+      "#,
+      )
+      .unwrap();
+
+      let (reloaded_store, non_fatal_error_sets) = cached_store
+        .reload(std::slice::from_ref(&base_path))
+        .unwrap();
+      assert!(non_fatal_error_sets.is_empty());
+      assert_eq!(
+        reloaded_store
+          .query(std::slice::from_ref(&base_path))
+          .matches
+          .len(),
+        2
+      );
+    });
+  }
+}
@@ -29,6 +29,24 @@ use std::{
   path::PathBuf,
 };
 
+/// The result of a query, expressed as the positions of the selected matches
+/// and global variables inside each group. Computing a plan is the expensive part
+/// of a query, while turning it into a `MatchSet` only requires indexing, which
+/// makes plans well suited for caching.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct QueryPlan {
+  segments: Vec<QuerySegment>,
+  match_count: usize,
+  global_var_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QuerySegment {
+  path: String,
+  matches: Vec<usize>,
+  global_vars: Vec<usize>,
+}
+
 pub(crate) struct DefaultMatchStore {
   pub groups: HashMap<String, MatchGroup>,
 }
@@ -45,31 +63,85 @@ impl DefaultMatchStore {
 
     (Self { groups }, non_fatal_error_sets)
   }
-}
 
-impl MatchStore for DefaultMatchStore {
-  fn query(&self, paths: &[String]) -> MatchSet {
-    let mut matches: Vec<&Match> = Vec::new();
-    let mut global_vars: Vec<&Variable> = Vec::new();
+  /// Create a new store by parsing the given files again, while reusing
+  /// the already loaded version of all the other ones.
+  /// If a changed file can't be parsed, its previous version is kept
+  /// and the error is reported as a non-fatal one.
+  pub fn reload(&self, changed_paths: &[String]) -> (Self, Vec<NonFatalErrorSet>) {
+    let mut groups = self.groups.clone();
+    let mut non_fatal_error_sets = Vec::new();
+
+    for path in changed_paths {
+      let group_path = PathBuf::from(path);
+      match MatchGroup::load(&group_path)
+        .with_context(|| format!("unable to reload match group {}", group_path.display()))
+      {
+        Ok((group, non_fatal_error_set)) => {
+          let imports = group.imports.clone();
+          groups.insert(path.clone(), group);
+
+          if let Some(non_fatal_error_set) = non_fatal_error_set {
+            non_fatal_error_sets.push(non_fatal_error_set);
+          }
+
+          // The changed file might now import files that were not loaded before
+          load_match_groups_recursively(&mut groups, &imports, &mut non_fatal_error_sets);
+        }
+        Err(err) => {
+          non_fatal_error_sets.push(NonFatalErrorSet::single_error(&group_path, err));
+        }
+      }
+    }
+
+    (Self { groups }, non_fatal_error_sets)
+  }
+
+  pub fn plan(&self, paths: &[String]) -> QueryPlan {
+    let mut plan = QueryPlan::default();
     let mut visited_paths = HashSet::new();
     let mut visited_matches = HashSet::new();
     let mut visited_global_vars = HashSet::new();
 
-    query_matches_for_paths(
+    plan_query_for_paths(
       &self.groups,
       &mut visited_paths,
       &mut visited_matches,
       &mut visited_global_vars,
-      &mut matches,
-      &mut global_vars,
+      &mut plan,
       paths,
     );
 
+    plan
+  }
+
+  pub fn materialize(&self, plan: &QueryPlan) -> MatchSet<'_> {
+    let mut matches: Vec<&Match> = Vec::with_capacity(plan.match_count);
+    let mut global_vars: Vec<&Variable> = Vec::with_capacity(plan.global_var_count);
+
+    for segment in &plan.segments {
+      if let Some(group) = self.groups.get(&segment.path) {
+        matches.extend(segment.matches.iter().map(|index| &group.matches[*index]));
+        global_vars.extend(
+          segment
+            .global_vars
+            .iter()
+            .map(|index| &group.global_vars[*index]),
+        );
+      }
+    }
+
     MatchSet {
       matches,
       global_vars,
     }
   }
+}
+
+impl MatchStore for DefaultMatchStore {
+  fn query(&self, paths: &[String]) -> MatchSet {
+    self.materialize(&self.plan(paths))
+  }
 
   fn loaded_paths(&self) -> Vec<String> {
     self.groups.keys().cloned().collect()
@@ -105,13 +177,12 @@ fn load_match_groups_recursively(
   }
 }
 
-fn query_matches_for_paths<'a>(
-  groups: &'a HashMap<String, MatchGroup>,
+fn plan_query_for_paths(
+  groups: &HashMap<String, MatchGroup>,
   visited_paths: &mut HashSet<String>,
   visited_matches: &mut HashSet<StructId>,
   visited_global_vars: &mut HashSet<StructId>,
-  matches: &mut Vec<&'a Match>,
-  global_vars: &mut Vec<&'a Variable>,
+  plan: &mut QueryPlan,
   paths: &[String],
 ) {
   for path in paths {
@@ -119,29 +190,38 @@ fn query_matches_for_paths<'a>(
       visited_paths.insert(path.clone());
 
       if let Some(group) = groups.get(path) {
-        query_matches_for_paths(
+        plan_query_for_paths(
           groups,
           visited_paths,
           visited_matches,
           visited_global_vars,
-          matches,
-          global_vars,
+          plan,
           &group.imports,
         );
 
-        for m in &group.matches {
+        let mut segment = QuerySegment {
+          path: path.clone(),
+          matches: Vec::new(),
+          global_vars: Vec::new(),
+        };
+
+        for (index, m) in group.matches.iter().enumerate() {
           if !visited_matches.contains(&m.id) {
-            matches.push(m);
+            segment.matches.push(index);
             visited_matches.insert(m.id);
           }
         }
 
-        for var in &group.global_vars {
+        for (index, var) in group.global_vars.iter().enumerate() {
           if !visited_global_vars.contains(&var.id) {
-            global_vars.push(var);
+            segment.global_vars.push(index);
             visited_global_vars.insert(var.id);
           }
         }
+
+        plan.match_count += segment.matches.len();
+        plan.global_var_count += segment.global_vars.len();
+        plan.segments.push(segment);
       }
     }
   }
@@ -734,5 +814,153 @@ mod tests {
     });
   }
 
+  #[test]
+  fn match_store_reload_only_parses_changed_files() {
+    use_test_directory(|_, match_dir, _| {
+      let base_file = match_dir.join("base.yml");
+      std::fs::write(
+        &base_file,
+        r#"
+      imports:
+        - "_another.yml"
+
+      matches:
+        - trigger: "hello"
+          replace: "world"
+      "#,
+      )
+      .unwrap();
+
+      let another_file = match_dir.join("_another.yml");
+      std::fs::write(
+        &another_file,
+        r#"
+      matches:
+        - trigger: "foo"
+          replace: "bar"
+      "#,
+      )
+      .unwrap();
+
+      let base_path = base_file.to_string_lossy().to_string();
+      let another_path = another_file.to_string_lossy().to_string();
+      let (match_store, _) = DefaultMatchStore::load(std::slice::from_ref(&base_path));
+
+      std::fs::write(
+        &another_file,
+        r#"
+      matches:
+        - trigger: "foo"
+          replace: "baz"
+      "#,
+      )
+      .unwrap();
+
+      let (reloaded_store, non_fatal_error_sets) =
+        match_store.reload(std::slice::from_ref(&another_path));
+      assert_eq!(non_fatal_error_sets.len(), 0);
+
+      // The unchanged file is reused as it is
+      assert_eq!(
+        reloaded_store.groups.get(&base_path),
+        match_store.groups.get(&base_path)
+      );
+
+      let match_set = reloaded_store.query(&[base_path]);
+      assert_eq!(
+        match_set
+          .matches
+          .into_iter()
+          .cloned()
+          .map(|mut m| {
+            m.id = 0;
+            m
+          })
+          .collect::<Vec<Match>>(),
+        create_matches(&[("foo", "baz"), ("hello", "world")])
+      );
+    });
+  }
+
+  #[test]
+  fn match_store_reload_keeps_previous_version_of_invalid_files() {
+    use_test_directory(|_, match_dir, _| {
+      let base_file = match_dir.join("base.yml");
+      std::fs::write(
+        &base_file,
+        r#"
+      matches:
+        - trigger: "hello"
+          replace: "world"
+      "#,
+      )
+      .unwrap();
+
+      let base_path = base_file.to_string_lossy().to_string();
+      let (match_store, _) = DefaultMatchStore::load(std::slice::from_ref(&base_path));
+
+      std::fs::write(&base_file, "matches: [ - invalid").unwrap();
+
+      let (reloaded_store, non_fatal_error_sets) =
+        match_store.reload(std::slice::from_ref(&base_path));
+      assert_eq!(non_fatal_error_sets.len(), 1);
+      assert_eq!(non_fatal_error_sets[0].file, base_file);
+      assert_eq!(
+        reloaded_store.query(std::slice::from_ref(&base_path)),
+        match_store.query(std::slice::from_ref(&base_path))
+      );
+    });
+  }
+
+  #[test]
+  fn match_store_reload_loads_new_imports() {
+    use_test_directory(|_, match_dir, _| {
+      let base_file = match_dir.join("base.yml");
+      std::fs::write(
+        &base_file,
+        r#"
+      matches:
+        - trigger: "hello"
+          replace: "world"
+      "#,
+      )
+      .unwrap();
+
+      let another_file = match_dir.join("_another.yml");
+      std::fs::write(
+        &another_file,
+        r#"
+      matches:
+        - trigger: "foo"
+          replace: "bar"
+      "#,
+      )
+      .unwrap();
+
+      let base_path = base_file.to_string_lossy().to_string();
+      let (match_store, _) = DefaultMatchStore::load(std::slice::from_ref(&base_path));
+      assert_eq!(match_store.loaded_paths().len(), 1);
+
+      std::fs::write(
+        &base_file,
+        r#"
+      imports:
+        - "_another.yml"
+
+      matches:
+        - trigger: "hello"
+          replace: "world"
+      "#,
+      )
+      .unwrap();
+
+      let (reloaded_store, non_fatal_error_sets) =
+        match_store.reload(std::slice::from_ref(&base_path));
+      assert_eq!(non_fatal_error_sets.len(), 0);
+      assert_eq!(reloaded_store.loaded_paths().len(), 2);
+      assert_eq!(reloaded_store.query(&[base_path]).matches.len(), 2);
+    });
+  }
+
   // TODO: add fatal and non-fatal error cases
 }
@@ -21,11 +21,22 @@ use crate::error::NonFatalErrorSet;
 
 use super::{Match, Variable};
 
+mod cached;
 mod default;
 
 pub trait MatchStore: Send {
   fn query(&self, paths: &[String]) -> MatchSet;
   fn loaded_paths(&self) -> Vec<String>;
+
+  /// Create a new store in which only the given files are parsed again,
+  /// reusing the current version of all the others.
+  /// Returns None if the store doesn't support partial reloads.
+  fn reload(
+    &self,
+    _changed_paths: &[String],
+  ) -> Option<(Box<dyn MatchStore>, Vec<NonFatalErrorSet>)> {
+    None
+  }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -35,7 +46,12 @@ pub struct MatchSet<'a> {
 }
 
 pub fn load(paths: &[String]) -> (impl MatchStore, Vec<NonFatalErrorSet>) {
-  // TODO: here we can replace the DefaultMatchStore with a caching wrapper
-  // that returns the same response for the given "paths" query
+  let (store, non_fatal_error_sets) = default::DefaultMatchStore::load(paths);
+  (cached::CachedMatchStore::new(store), non_fatal_error_sets)
+}
+
+/// Load a store that computes every query from scratch, without caching the
+/// query plans. Mostly useful as a baseline to measure the cached store against.
+pub fn load_uncached(paths: &[String]) -> (impl MatchStore, Vec<NonFatalErrorSet>) {
   default::DefaultMatchStore::load(paths)
 }
@@ -17,6 +17,8 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use serde::Serialize;
+
 /// Check if the given string represents an empty YAML.
 /// In other words, it checks if the document is only composed
 /// of spaces and/or comments
@@ -31,6 +33,52 @@ pub fn is_yaml_empty(yaml: &str) -> bool {
   true
 }
 
+/// Return the names of the fields of the given structure, as seen by serde.
+/// Fields are only included when serialized, so the structure should be
+/// a "full" instance (for example, with all the optional fields set to None).
+pub fn field_names<T: Serialize>(value: &T) -> Vec<String> {
+  match serde_yaml::to_value(value) {
+    Ok(serde_yaml::Value::Mapping(mapping)) => mapping
+      .into_iter()
+      .filter_map(|(key, _)| key.as_str().map(String::from))
+      .collect(),
+    _ => Vec::new(),
+  }
+}
+
+// Typos farther than this from a known field are not worth a suggestion
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Return the known field most similar to the given one, if close enough
+pub fn suggest_field<'a>(field: &str, known_fields: &'a [String]) -> Option<&'a str> {
+  known_fields
+    .iter()
+    .map(|known| (edit_distance(field, known), known))
+    .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+    .min_by_key(|(distance, _)| *distance)
+    .map(|(_, known)| known.as_str())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+  for (i, a_char) in a.chars().enumerate() {
+    let mut current = vec![i + 1];
+    for (j, b_char) in b.iter().enumerate() {
+      let cost = usize::from(a_char != *b_char);
+      current.push(
+        (previous[j] + cost)
+          .min(previous[j + 1] + 1)
+          .min(current[j] + 1),
+      );
+    }
+    previous = current;
+  }
+
+  previous[b.len()]
+}
+
 #[cfg(test)]
 pub mod tests {
   use super::*;
@@ -71,4 +119,20 @@ pub mod tests {
   fn is_yaml_empty_document_with_content() {
     assert!(!is_yaml_empty("\nfield: true\n"));
   }
+
+  #[test]
+  fn edit_distance_works_correctly() {
+    assert_eq!(edit_distance("replace", "replace"), 0);
+    assert_eq!(edit_distance("repalce", "replace"), 2);
+    assert_eq!(edit_distance("word", "words"), 1);
+    assert_eq!(edit_distance("", "abc"), 3);
+  }
+
+  #[test]
+  fn suggest_field_works_correctly() {
+    let fields = vec!["trigger".to_string(), "replace".to_string()];
+    assert_eq!(suggest_field("tirgger", &fields), Some("trigger"));
+    assert_eq!(suggest_field("replac", &fields), Some("replace"));
+    assert_eq!(suggest_field("something", &fields), None);
+  }
 }
@@ -24,3 +24,8 @@ pub struct MatchExecRequestEvent {
   pub trigger: Option<String>,
   pub args: HashMap<String, String>,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchesReloadRequestEvent {
+  pub paths: Vec<String>,
+}
@@ -32,6 +32,14 @@ pub struct DetectedMatch {
   pub left_separator: Option<String>,
   pub right_separator: Option<String>,
   pub args: HashMap<String, String>,
+
+  // Number of typos in the detected trigger, 0 for exact matches
+  pub distance: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchesReloadedEvent {
+  pub has_errors: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
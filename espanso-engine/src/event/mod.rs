@@ -59,14 +59,17 @@ pub enum EventType {
   HotKey(input::HotKeyEvent),
   TrayIconClicked,
   ContextMenuClicked(input::ContextMenuClickedEvent),
+  FocusChanged,
 
   // External requests
   MatchExecRequest(external::MatchExecRequestEvent),
+  MatchesReloadRequest(external::MatchesReloadRequestEvent),
 
   // Internal
   MatchesDetected(internal::MatchesDetectedEvent),
   MatchSelected(internal::MatchSelectedEvent),
   CauseCompensatedMatch(internal::CauseCompensatedMatchEvent),
+  MatchesReloaded(internal::MatchesReloadedEvent),
 
   RenderingRequested(internal::RenderingRequestedEvent),
   ImageRequested(internal::ImageRequestedEvent),
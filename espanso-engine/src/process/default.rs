@@ -33,10 +33,11 @@ use super::{
     multiplex::MultiplexMiddleware,
     open_config::ConfigMiddleware,
     open_config::ConfigPathProvider,
+    reload::MatchReloadMiddleware,
     render::RenderMiddleware,
   },
   AltCodeSynthEnabledProvider, DisableOptions, EnabledStatusProvider, MatchFilter,
-  MatchInfoProvider, MatchProvider, MatchResolver, MatchSelector, Matcher,
+  MatchInfoProvider, MatchProvider, MatchReloader, MatchResolver, MatchSelector, Matcher,
   MatcherMiddlewareConfigProvider, Middleware, ModifierStateProvider, Multiplexer,
   NotificationManager, PathProvider, Processor, Renderer, UndoEnabledProvider,
 };
@@ -79,6 +80,7 @@ impl<'a> DefaultProcessor<'a> {
     match_resolver: &'a dyn MatchResolver,
     notification_manager: &'a dyn NotificationManager,
     alt_code_synth_enabled_provider: &'a dyn AltCodeSynthEnabledProvider,
+    match_reloader: &'a dyn MatchReloader,
   ) -> DefaultProcessor<'a> {
     Self {
       event_queue: VecDeque::new(),
@@ -95,6 +97,7 @@ impl<'a> DefaultProcessor<'a> {
           modifier_state_provider,
         )),
         Box::new(MatchExecRequestMiddleware::new(match_resolver)),
+        Box::new(MatchReloadMiddleware::new(match_reloader)),
         Box::new(SuppressMiddleware::new(enabled_status_provider)),
         Box::new(ContextMenuMiddleware::new()),
         Box::new(HotKeyMiddleware::new()),
@@ -68,6 +68,21 @@ impl<'a> Middleware for MatchSelectMiddleware<'a> {
       // Find the matches that are actually valid in the current context
       let valid_ids = self.match_filter.filter_active(&matches_ids);
 
+      // Fuzzy matches are only considered when there are no better ones,
+      // so that typos never take precedence over an exact trigger
+      let distance_of = |id: &i32| {
+        m_event
+          .matches
+          .iter()
+          .find(|m| m.id == *id)
+          .map_or(0, |m| m.distance)
+      };
+      let min_distance = valid_ids.iter().map(distance_of).min().unwrap_or(0);
+      let valid_ids: Vec<i32> = valid_ids
+        .into_iter()
+        .filter(|id| distance_of(id) == min_distance)
+        .collect();
+
       return match valid_ids.len() {
         0 => Event::caused_by(event.source_id, EventType::NOOP), // No valid matches, consume the event
         1 => {
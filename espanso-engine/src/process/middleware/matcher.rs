@@ -19,8 +19,9 @@
 
 use log::trace;
 use std::{
-  cell::RefCell,
+  cell::{Cell, RefCell},
   collections::{HashMap, VecDeque},
+  time::{Duration, Instant},
 };
 
 use super::super::Middleware;
@@ -51,10 +52,33 @@ pub struct MatchResult {
   pub left_separator: Option<String>,
   pub right_separator: Option<String>,
   pub args: HashMap<String, String>,
+  pub distance: usize,
 }
 
 pub trait MatcherMiddlewareConfigProvider {
   fn max_history_size(&self) -> usize;
+  fn buffer_reset_policy(&self) -> BufferResetPolicy;
+}
+
+/// Determines which events cause the typed characters to be forgotten.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferResetPolicy {
+  /// If false, mouse clicks are ignored, so that a trigger can be completed
+  /// after clicking inside the same window.
+  pub reset_on_mouse_click: bool,
+  pub reset_on_focus_change: bool,
+  /// The buffer is cleared when no event is received for longer than this.
+  pub idle_timeout: Option<Duration>,
+}
+
+impl Default for BufferResetPolicy {
+  fn default() -> Self {
+    Self {
+      reset_on_mouse_click: true,
+      reset_on_focus_change: false,
+      idle_timeout: None,
+    }
+  }
 }
 
 pub trait ModifierStateProvider {
@@ -74,6 +98,8 @@ pub struct MatcherMiddleware<'a, State> {
   matcher_states: RefCell<VecDeque<Vec<State>>>,
 
   max_history_size: usize,
+  reset_policy: BufferResetPolicy,
+  last_event_time: Cell<Option<Instant>>,
 
   modifier_status_provider: &'a dyn ModifierStateProvider,
 }
@@ -85,11 +111,14 @@ impl<'a, State> MatcherMiddleware<'a, State> {
     modifier_status_provider: &'a dyn ModifierStateProvider,
   ) -> Self {
     let max_history_size = options_provider.max_history_size();
+    let reset_policy = options_provider.buffer_reset_policy();
 
     Self {
       matchers,
       matcher_states: RefCell::new(VecDeque::new()),
       max_history_size,
+      reset_policy,
+      last_event_time: Cell::new(None),
       modifier_status_provider,
     }
   }
@@ -101,8 +130,34 @@ impl<'a, State> Middleware for MatcherMiddleware<'a, State> {
   }
 
   fn next(&self, event: Event, _: &mut dyn FnMut(Event)) -> Event {
+    if let EventType::FocusChanged = &event.etype {
+      if self.reset_policy.reset_on_focus_change {
+        trace!("focus change detected, clearing matching state");
+        self.matcher_states.borrow_mut().clear();
+      }
+      return event;
+    }
+
+    if let EventType::Mouse(_) = &event.etype {
+      if !self.reset_policy.reset_on_mouse_click {
+        return event;
+      }
+    }
+
     if is_event_of_interest(&event.etype) {
       let mut matcher_states = self.matcher_states.borrow_mut();
+
+      let now = Instant::now();
+      if let (Some(idle_timeout), Some(last_event_time)) =
+        (self.reset_policy.idle_timeout, self.last_event_time.get())
+      {
+        if now.duration_since(last_event_time) > idle_timeout {
+          trace!("idle timeout exceeded, clearing matching state");
+          matcher_states.clear();
+        }
+      }
+      self.last_event_time.set(Some(now));
+
       let prev_states = if matcher_states.is_empty() {
         None
       } else {
@@ -167,6 +222,7 @@ impl<'a, State> Middleware for MatcherMiddleware<'a, State> {
                   right_separator: result.right_separator,
                   left_separator: result.left_separator,
                   args: result.args,
+                  distance: result.distance,
                 })
                 .collect(),
               is_search: false,
@@ -254,4 +310,166 @@ fn should_skip_key_event_due_to_modifier_press(modifier_state: &ModifierState) -
   }
 }
 
-// TODO: test
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::event::input::{KeyboardEvent, MouseButton, MouseEvent};
+
+  struct MockMatcher;
+
+  // Detects the "ab" trigger, a separator resets the progress
+  impl<'a> Matcher<'a, String> for MockMatcher {
+    fn process(
+      &'a self,
+      prev_state: Option<&String>,
+      event: &MatcherEvent,
+    ) -> (String, Vec<MatchResult>) {
+      let mut buffer = prev_state.cloned().unwrap_or_default();
+      match event {
+        MatcherEvent::Key {
+          chars: Some(chars), ..
+        } => buffer.push_str(chars),
+        _ => buffer.clear(),
+      }
+
+      let results = if buffer.ends_with("ab") {
+        vec![MatchResult {
+          id: 1,
+          trigger: "ab".to_string(),
+          left_separator: None,
+          right_separator: None,
+          args: HashMap::new(),
+          distance: 0,
+        }]
+      } else {
+        Vec::new()
+      };
+
+      (buffer, results)
+    }
+  }
+
+  struct MockConfigProvider(BufferResetPolicy);
+
+  impl MatcherMiddlewareConfigProvider for MockConfigProvider {
+    fn max_history_size(&self) -> usize {
+      10
+    }
+
+    fn buffer_reset_policy(&self) -> BufferResetPolicy {
+      self.0.clone()
+    }
+  }
+
+  struct MockModifierStateProvider;
+
+  impl ModifierStateProvider for MockModifierStateProvider {
+    fn get_modifier_state(&self) -> ModifierState {
+      ModifierState {
+        is_ctrl_down: false,
+        is_alt_down: false,
+        is_meta_down: false,
+      }
+    }
+  }
+
+  fn key(c: &str) -> Event {
+    Event::caused_by(
+      0,
+      EventType::Keyboard(KeyboardEvent {
+        key: Key::Other(0),
+        value: Some(c.to_string()),
+        status: Status::Pressed,
+        variant: None,
+      }),
+    )
+  }
+
+  fn click() -> Event {
+    Event::caused_by(
+      0,
+      EventType::Mouse(MouseEvent {
+        button: MouseButton::Left,
+        status: Status::Pressed,
+      }),
+    )
+  }
+
+  fn focus_changed() -> Event {
+    Event::caused_by(0, EventType::FocusChanged)
+  }
+
+  fn run(policy: BufferResetPolicy, events: Vec<Event>) -> bool {
+    let matcher = MockMatcher;
+    let matchers: Vec<&dyn Matcher<String>> = vec![&matcher];
+    let config = MockConfigProvider(policy);
+    let modifiers = MockModifierStateProvider;
+    let middleware = MatcherMiddleware::new(&matchers, &config, &modifiers);
+
+    let mut detected = false;
+    for event in events {
+      let result = middleware.next(event, &mut |_| {});
+      if let EventType::MatchesDetected(_) = result.etype {
+        detected = true;
+      }
+    }
+    detected
+  }
+
+  #[test]
+  fn click_resets_buffer_by_default() {
+    assert!(run(BufferResetPolicy::default(), vec![key("a"), key("b")]));
+    assert!(!run(
+      BufferResetPolicy::default(),
+      vec![key("a"), click(), key("b")]
+    ));
+  }
+
+  #[test]
+  fn click_keeps_buffer_when_disabled() {
+    let policy = BufferResetPolicy {
+      reset_on_mouse_click: false,
+      ..Default::default()
+    };
+    assert!(run(policy, vec![key("a"), click(), key("b")]));
+  }
+
+  #[test]
+  fn focus_change_resets_buffer_when_enabled() {
+    assert!(run(
+      BufferResetPolicy::default(),
+      vec![key("a"), focus_changed(), key("b")]
+    ));
+
+    let policy = BufferResetPolicy {
+      reset_on_mouse_click: false,
+      reset_on_focus_change: true,
+      ..Default::default()
+    };
+    assert!(!run(
+      policy.clone(),
+      vec![key("a"), click(), focus_changed(), key("b")]
+    ));
+    assert!(run(policy, vec![key("a"), click(), key("b")]));
+  }
+
+  #[test]
+  fn idle_timeout_resets_buffer() {
+    let policy = BufferResetPolicy {
+      idle_timeout: Some(Duration::from_millis(50)),
+      ..Default::default()
+    };
+    assert!(run(policy.clone(), vec![key("a"), key("b")]));
+
+    let matcher = MockMatcher;
+    let matchers: Vec<&dyn Matcher<String>> = vec![&matcher];
+    let config = MockConfigProvider(policy);
+    let modifiers = MockModifierStateProvider;
+    let middleware = MatcherMiddleware::new(&matchers, &config, &modifiers);
+
+    middleware.next(key("a"), &mut |_| {});
+    std::thread::sleep(Duration::from_millis(100));
+    let result = middleware.next(key("b"), &mut |_| {});
+    assert!(!matches!(result.etype, EventType::MatchesDetected(_)));
+  }
+}
@@ -36,6 +36,7 @@ pub mod matcher;
 pub mod multiplex;
 pub mod notification;
 pub mod open_config;
+pub mod reload;
 pub mod render;
 pub mod search;
 pub mod suppress;
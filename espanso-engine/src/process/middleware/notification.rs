@@ -23,6 +23,7 @@ use crate::event::{Event, EventType};
 pub trait NotificationManager {
   fn notify_status_change(&self, enabled: bool);
   fn notify_rendering_error(&self);
+  fn notify_matches_reloaded(&self, has_errors: bool);
 }
 
 pub struct NotificationMiddleware<'a> {
@@ -47,6 +48,9 @@ impl<'a> Middleware for NotificationMiddleware<'a> {
       EventType::Enabled => self.notification_manager.notify_status_change(true),
       EventType::Disabled => self.notification_manager.notify_status_change(false),
       EventType::RenderingError => self.notification_manager.notify_rendering_error(),
+      EventType::MatchesReloaded(m_event) => self
+        .notification_manager
+        .notify_matches_reloaded(m_event.has_errors),
       _ => {}
     }
 
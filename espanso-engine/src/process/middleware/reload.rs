@@ -0,0 +1,163 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use log::{info, warn};
+
+use super::super::Middleware;
+use crate::event::{internal::MatchesReloadedEvent, Event, EventType, ExitMode};
+
+pub enum MatchReloadResult {
+  Reloaded { has_errors: bool },
+
+  // The changes can't be applied in place, so the worker has to be restarted
+  RestartRequired,
+}
+
+pub trait MatchReloader {
+  fn reload_matches(&self, paths: &[String]) -> MatchReloadResult;
+}
+
+pub struct MatchReloadMiddleware<'a> {
+  match_reloader: &'a dyn MatchReloader,
+}
+
+impl<'a> MatchReloadMiddleware<'a> {
+  pub fn new(match_reloader: &'a dyn MatchReloader) -> Self {
+    Self { match_reloader }
+  }
+}
+
+impl<'a> Middleware for MatchReloadMiddleware<'a> {
+  fn name(&self) -> &'static str {
+    "match_reload"
+  }
+
+  fn next(&self, event: Event, _: &mut dyn FnMut(Event)) -> Event {
+    if let EventType::MatchesReloadRequest(m_event) = &event.etype {
+      return match self.match_reloader.reload_matches(&m_event.paths) {
+        MatchReloadResult::Reloaded { has_errors } => {
+          info!(
+            "reloaded matches from {} changed file(s)",
+            m_event.paths.len()
+          );
+          Event::caused_by(
+            event.source_id,
+            EventType::MatchesReloaded(MatchesReloadedEvent { has_errors }),
+          )
+        }
+        MatchReloadResult::RestartRequired => {
+          warn!("unable to reload the changed matches in place, restarting worker");
+          Event::caused_by(
+            event.source_id,
+            EventType::ExitRequested(ExitMode::RestartWorker),
+          )
+        }
+      };
+    }
+
+    event
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cell::RefCell;
+
+  use super::*;
+  use crate::event::external::MatchesReloadRequestEvent;
+
+  struct MockMatchReloader {
+    restart_required: bool,
+    reloaded_paths: RefCell<Vec<String>>,
+  }
+
+  impl MockMatchReloader {
+    fn new(restart_required: bool) -> Self {
+      Self {
+        restart_required,
+        reloaded_paths: RefCell::new(Vec::new()),
+      }
+    }
+  }
+
+  impl MatchReloader for MockMatchReloader {
+    fn reload_matches(&self, paths: &[String]) -> MatchReloadResult {
+      self.reloaded_paths.borrow_mut().extend_from_slice(paths);
+      if self.restart_required {
+        MatchReloadResult::RestartRequired
+      } else {
+        MatchReloadResult::Reloaded {
+          has_errors: paths.iter().any(|path| path.contains("invalid")),
+        }
+      }
+    }
+  }
+
+  fn reload_request(paths: &[&str]) -> Event {
+    Event::caused_by(
+      42,
+      EventType::MatchesReloadRequest(MatchesReloadRequestEvent {
+        paths: paths.iter().map(ToString::to_string).collect(),
+      }),
+    )
+  }
+
+  #[test]
+  fn matches_are_reloaded_in_place() {
+    let reloader = MockMatchReloader::new(false);
+    let middleware = MatchReloadMiddleware::new(&reloader);
+
+    let event = middleware.next(reload_request(&["base.yml"]), &mut |_| {});
+    assert_eq!(event.source_id, 42);
+    assert!(matches!(
+      event.etype,
+      EventType::MatchesReloaded(MatchesReloadedEvent { has_errors: false })
+    ));
+    assert_eq!(*reloader.reloaded_paths.borrow(), vec!["base.yml"]);
+
+    let event = middleware.next(reload_request(&["invalid.yml"]), &mut |_| {});
+    assert!(matches!(
+      event.etype,
+      EventType::MatchesReloaded(MatchesReloadedEvent { has_errors: true })
+    ));
+  }
+
+  #[test]
+  fn worker_is_restarted_when_matches_cannot_be_reloaded_in_place() {
+    let reloader = MockMatchReloader::new(true);
+    let middleware = MatchReloadMiddleware::new(&reloader);
+
+    let event = middleware.next(reload_request(&["base.yml"]), &mut |_| {});
+    assert_eq!(event.source_id, 42);
+    assert!(matches!(
+      event.etype,
+      EventType::ExitRequested(ExitMode::RestartWorker)
+    ));
+  }
+
+  #[test]
+  fn other_events_are_ignored() {
+    let reloader = MockMatchReloader::new(false);
+    let middleware = MatchReloadMiddleware::new(&reloader);
+
+    let event = middleware.next(Event::caused_by(1, EventType::NOOP), &mut |_| {});
+    assert!(matches!(event.etype, EventType::NOOP));
+    assert!(reloader.reloaded_paths.borrow().is_empty());
+  }
+}
@@ -59,6 +59,7 @@ impl<'a> Middleware for SearchMiddleware<'a> {
               left_separator: None,
               right_separator: None,
               args: HashMap::new(),
+              distance: 0,
             })
             .collect(),
           is_search: true,
@@ -41,12 +41,13 @@ pub use middleware::image_resolve::PathProvider;
 pub use middleware::match_exec::MatchResolver;
 pub use middleware::match_select::{MatchFilter, MatchSelector};
 pub use middleware::matcher::{
-  MatchResult, Matcher, MatcherEvent, MatcherMiddlewareConfigProvider, ModifierState,
-  ModifierStateProvider,
+  BufferResetPolicy, MatchResult, Matcher, MatcherEvent, MatcherMiddlewareConfigProvider,
+  ModifierState, ModifierStateProvider,
 };
 pub use middleware::multiplex::Multiplexer;
 pub use middleware::notification::NotificationManager;
 pub use middleware::open_config::ConfigPathProvider;
+pub use middleware::reload::{MatchReloadResult, MatchReloader};
 pub use middleware::render::{Renderer, RendererError};
 pub use middleware::search::MatchProvider;
 pub use middleware::suppress::EnabledStatusProvider;
@@ -73,6 +74,7 @@ pub fn default<'a, MatcherState>(
   match_resolver: &'a dyn MatchResolver,
   notification_manager: &'a dyn NotificationManager,
   alt_code_synth_enabled_provider: &'a dyn AltCodeSynthEnabledProvider,
+  match_reloader: &'a dyn MatchReloader,
 ) -> impl Processor + 'a {
   default::DefaultProcessor::new(
     matchers,
@@ -94,5 +96,6 @@ pub fn default<'a, MatcherState>(
     match_resolver,
     notification_manager,
     alt_code_synth_enabled_provider,
+    match_reloader,
   )
 }
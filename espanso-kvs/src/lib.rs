@@ -29,6 +29,17 @@ pub trait KVS: Send + Sync + Clone {
   fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>>;
   fn set<T: Serialize>(&self, key: &str, value: T) -> Result<()>;
   fn delete(&self, key: &str) -> Result<()>;
+
+  /// Atomically replace the value of the given key with the one returned by
+  /// `update`, which receives the current value (if any). Returns the new value.
+  fn update<T: Serialize + DeserializeOwned>(
+    &self,
+    key: &str,
+    update: impl FnOnce(Option<T>) -> T,
+  ) -> Result<T>;
+
+  /// Return the sorted list of the stored keys
+  fn list(&self) -> Result<Vec<String>>;
 }
 
 pub fn get_persistent(base_dir: &Path) -> Result<impl KVS> {
@@ -82,6 +93,87 @@ mod tests {
     });
   }
 
+  #[test]
+  fn test_update() {
+    use_test_directory(|base_dir| {
+      let kvs = get_persistent(base_dir).unwrap();
+
+      let value = kvs
+        .update("counter", |value: Option<i32>| value.unwrap_or(0) + 1)
+        .unwrap();
+      assert_eq!(value, 1);
+      let value = kvs
+        .update("counter", |value: Option<i32>| value.unwrap_or(0) + 1)
+        .unwrap();
+      assert_eq!(value, 2);
+
+      // Values must survive a restart
+      let kvs = get_persistent(base_dir).unwrap();
+      assert_eq!(kvs.get::<i32>("counter").unwrap().unwrap(), 2);
+    });
+  }
+
+  #[test]
+  fn test_concurrent_updates() {
+    use_test_directory(|base_dir| {
+      let kvs = get_persistent(base_dir).unwrap();
+
+      let handles: Vec<_> = (0..8)
+        .map(|_| {
+          let kvs = kvs.clone();
+          std::thread::spawn(move || {
+            for _ in 0..10 {
+              kvs
+                .update("counter", |value: Option<i32>| value.unwrap_or(0) + 1)
+                .unwrap();
+            }
+          })
+        })
+        .collect();
+      for handle in handles {
+        handle.join().unwrap();
+      }
+
+      assert_eq!(kvs.get::<i32>("counter").unwrap().unwrap(), 80);
+    });
+  }
+
+  #[test]
+  fn test_list() {
+    use_test_directory(|base_dir| {
+      let kvs = get_persistent(base_dir).unwrap();
+
+      assert!(kvs.list().unwrap().is_empty());
+
+      kvs.set("second", 2).unwrap();
+      kvs.set("first", 1).unwrap();
+      kvs.update("third", |_: Option<i32>| 3).unwrap();
+      assert_eq!(kvs.list().unwrap(), vec!["first", "second", "third"]);
+
+      kvs.delete("second").unwrap();
+      assert_eq!(kvs.list().unwrap(), vec!["first", "third"]);
+    });
+  }
+
+  #[test]
+  fn test_changes_from_other_instances_are_visible() {
+    use_test_directory(|base_dir| {
+      let kvs = get_persistent(base_dir).unwrap();
+      let other_kvs = get_persistent(base_dir).unwrap();
+
+      kvs.set("my_key", "first".to_string()).unwrap();
+      assert_eq!(kvs.get::<String>("my_key").unwrap().unwrap(), "first");
+
+      // Make sure the modification time changes even on coarse-grained file systems
+      std::thread::sleep(std::time::Duration::from_millis(1100));
+      other_kvs.set("my_key", "second".to_string()).unwrap();
+      assert_eq!(kvs.get::<String>("my_key").unwrap().unwrap(), "second");
+
+      other_kvs.delete("my_key").unwrap();
+      assert!(kvs.get::<String>("my_key").unwrap().is_none());
+    });
+  }
+
   #[test]
   fn test_delete_non_existing_key() {
     use_test_directory(|base_dir| {
@@ -23,17 +23,23 @@ use std::{
   collections::HashMap,
   path::{Path, PathBuf},
   sync::{Arc, Mutex},
+  time::SystemTime,
 };
 use thiserror::Error;
 
 use super::KVS;
 
 const DEFAULT_KVS_DIR_NAME: &str = "kvs";
+const TEMP_FILE_EXTENSION: &str = "tmp";
+
+// Values are cached along with the modification time of their file, so that
+// changes made by other processes (such as the CLI) are picked up
+type Cache = HashMap<String, (Value, Option<SystemTime>)>;
 
 #[derive(Clone)]
 pub struct PersistentJsonKVS {
   kvs_dir: PathBuf,
-  store: Arc<Mutex<HashMap<String, Value>>>,
+  store: Arc<Mutex<Cache>>,
 }
 
 impl PersistentJsonKVS {
@@ -48,6 +54,44 @@ impl PersistentJsonKVS {
       store: Arc::new(Mutex::new(HashMap::new())),
     })
   }
+
+  fn read_value(&self, store: &mut Cache, key: &str) -> Result<Option<Value>> {
+    let target_file = self.kvs_dir.join(key);
+    if !target_file.is_file() {
+      store.remove(key);
+      return Ok(None);
+    }
+
+    let modified = get_modified_time(&target_file);
+    if let Some((cached_value, cached_modified)) = store.get(key) {
+      if modified.is_some() && *cached_modified == modified {
+        return Ok(Some(cached_value.clone()));
+      }
+    }
+
+    // Not found in the cache or changed since the last read, read from the file
+    let content = std::fs::read_to_string(&target_file)?;
+    let deserialized_value: Value = serde_json::from_str(&content)?;
+
+    store.insert(key.to_string(), (deserialized_value.clone(), modified));
+
+    Ok(Some(deserialized_value))
+  }
+
+  fn write_value(&self, store: &mut Cache, key: &str, value: Value) -> Result<()> {
+    let serialized_string = serde_json::to_string(&value)?;
+
+    // Write to a temporary file first and then rename it, so that
+    // the value is never left half-written
+    let target_file = self.kvs_dir.join(key);
+    let temp_file = target_file.with_extension(TEMP_FILE_EXTENSION);
+    std::fs::write(&temp_file, serialized_string)?;
+    std::fs::rename(&temp_file, &target_file)?;
+
+    store.insert(key.to_string(), (value, get_modified_time(&target_file)));
+
+    Ok(())
+  }
 }
 
 impl KVS for PersistentJsonKVS {
@@ -58,24 +102,10 @@ impl KVS for PersistentJsonKVS {
 
     let mut lock = self.store.lock().expect("unable to obtain KVS read lock");
 
-    if let Some(cached_value) = lock.get(key) {
-      let converted_value = serde_json::from_value(cached_value.clone())?;
-      return Ok(Some(converted_value));
-    }
-
-    // Not found in the cache, read from the file
-    let target_file = self.kvs_dir.join(key);
-    if target_file.is_file() {
-      let content = std::fs::read_to_string(&target_file)?;
-      let deserialized_value: Value = serde_json::from_str(&content)?;
-      let converted_value = serde_json::from_value(deserialized_value.clone())?;
-
-      lock.insert(key.to_string(), deserialized_value);
-
-      return Ok(Some(converted_value));
+    match self.read_value(&mut lock, key)? {
+      Some(value) => Ok(Some(serde_json::from_value(value)?)),
+      None => Ok(None),
     }
-
-    Ok(None)
   }
 
   fn set<T: serde::Serialize>(&self, key: &str, value: T) -> Result<()> {
@@ -86,14 +116,7 @@ impl KVS for PersistentJsonKVS {
     let mut lock = self.store.lock().expect("unable to obtain KVS write lock");
 
     let serialized_value = serde_json::to_value(value)?;
-    let serialized_string = serde_json::to_string(&serialized_value)?;
-
-    lock.insert(key.to_string(), serialized_value);
-
-    let target_file = self.kvs_dir.join(key);
-    std::fs::write(target_file, serialized_string)?;
-
-    Ok(())
+    self.write_value(&mut lock, key, serialized_value)
   }
 
   fn delete(&self, key: &str) -> Result<()> {
@@ -112,6 +135,56 @@ impl KVS for PersistentJsonKVS {
 
     Ok(())
   }
+
+  fn update<T: serde::Serialize + serde::de::DeserializeOwned>(
+    &self,
+    key: &str,
+    update: impl FnOnce(Option<T>) -> T,
+  ) -> Result<T> {
+    if !is_valid_key_name(key) {
+      return Err(PersistentJsonKVSError::InvalidKey(key.to_string()).into());
+    }
+
+    // The lock is held for the whole read-update-write cycle
+    let mut lock = self.store.lock().expect("unable to obtain KVS update lock");
+
+    let current_value = match self.read_value(&mut lock, key)? {
+      Some(value) => Some(serde_json::from_value(value)?),
+      None => None,
+    };
+
+    let new_value = update(current_value);
+    self.write_value(&mut lock, key, serde_json::to_value(&new_value)?)?;
+
+    Ok(new_value)
+  }
+
+  fn list(&self) -> Result<Vec<String>> {
+    let mut keys = Vec::new();
+
+    for entry in std::fs::read_dir(&self.kvs_dir)? {
+      let entry = entry?;
+      if !entry.file_type()?.is_file() {
+        continue;
+      }
+
+      // Temporary files are skipped, as their extension is not a valid key name
+      if let Some(key) = entry.file_name().to_str() {
+        if is_valid_key_name(key) {
+          keys.push(key.to_string());
+        }
+      }
+    }
+
+    keys.sort();
+    Ok(keys)
+  }
+}
+
+fn get_modified_time(path: &Path) -> Option<SystemTime> {
+  std::fs::metadata(path)
+    .and_then(|metadata| metadata.modified())
+    .ok()
 }
 
 fn is_valid_key_name(key: &str) -> bool {
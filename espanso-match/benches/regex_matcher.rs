@@ -0,0 +1,100 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use espanso_match::{
+  event::{Event, Key},
+  regex::{RegexMatch, RegexMatcher, RegexMatcherOptions},
+  Matcher,
+};
+
+const MATCH_COUNTS: [usize; 3] = [100, 1000, 5000];
+
+fn create_matches(count: usize) -> Vec<RegexMatch<usize>> {
+  (0..count)
+    .map(|i| match i % 3 {
+      0 => RegexMatch::new(i, &format!(":greet{i}\\((?P<name>.*?)\\)")),
+      1 => RegexMatch::new(i, &format!(":num{i}_(?P<value>\\d+)\\.")),
+      _ => RegexMatch::new(i, &format!(":word{i}\\((?P<word>\\w+)\\)")),
+    })
+    .collect()
+}
+
+fn type_string(matcher: &RegexMatcher<usize>, string: &str) -> usize {
+  let mut state = None;
+  let mut found = 0;
+
+  for c in string.chars() {
+    let (next_state, matches) = matcher.process(
+      state.as_ref(),
+      Event::Key {
+        key: Key::Other,
+        chars: Some(c.to_string()),
+      },
+    );
+    state = Some(next_state);
+    found += matches.len();
+  }
+
+  found
+}
+
+fn bench_build(c: &mut Criterion) {
+  let mut group = c.benchmark_group("regex_matcher_build");
+  group.sample_size(10);
+
+  for count in MATCH_COUNTS {
+    let matches = create_matches(count);
+    group.bench_with_input(
+      BenchmarkId::from_parameter(count),
+      &matches,
+      |b, matches| {
+        b.iter(|| RegexMatcher::new(matches, RegexMatcherOptions::default()));
+      },
+    );
+  }
+
+  group.finish();
+}
+
+fn bench_process(c: &mut Criterion) {
+  let mut group = c.benchmark_group("regex_matcher_process");
+
+  for count in MATCH_COUNTS {
+    let matcher = RegexMatcher::new(&create_matches(count), RegexMatcherOptions::default());
+
+    group.bench_with_input(
+      BenchmarkId::new("no_match", count),
+      &matcher,
+      |b, matcher| {
+        b.iter(|| type_string(matcher, "the quick brown fox jumps over the lazy dog"));
+      },
+    );
+
+    let trigger = format!(":greet{}(jane)", count - 1 - (count - 1) % 3);
+    group.bench_with_input(BenchmarkId::new("match", count), &matcher, |b, matcher| {
+      b.iter(|| assert_eq!(type_string(matcher, &trigger), 1));
+    });
+  }
+
+  group.finish();
+}
+
+criterion_group!(benches, bench_build, bench_process);
+criterion_main!(benches);
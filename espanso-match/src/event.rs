@@ -23,7 +23,7 @@ pub enum Event {
   VirtualSeparator,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Key {
   // Modifiers
   Alt,
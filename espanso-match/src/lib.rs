@@ -33,6 +33,10 @@ pub struct MatchResult<Id> {
   pub left_separator: Option<String>,
   pub right_separator: Option<String>,
   pub vars: HashMap<String, String>,
+
+  /// Number of edits between the typed trigger and the original one,
+  /// 0 for exact matches
+  pub distance: usize,
 }
 
 impl<Id: Default> Default for MatchResult<Id> {
@@ -43,6 +47,7 @@ impl<Id: Default> Default for MatchResult<Id> {
       left_separator: None,
       right_separator: None,
       vars: HashMap::new(),
+      distance: 0,
     }
   }
 }
@@ -18,9 +18,10 @@
  */
 
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
 use log::error;
-use regex::{Regex, RegexSet};
+use regex::{Regex, RegexSet, RegexSetBuilder};
 
 use crate::Matcher;
 use crate::{event::Event, MatchResult};
@@ -58,16 +59,32 @@ impl Default for RegexMatcherOptions {
 }
 
 pub struct RegexMatcher<Id> {
-  ids: Vec<Id>,
-  // The RegexSet is used to efficiently determine which regexes match
-  regex_set: RegexSet,
+  entries: Vec<RegexEntry<Id>>,
 
-  // The single regexes are then used to find the captures
-  regexes: Vec<Regex>,
+  // All the regexes are combined into RegexSets, which efficiently determine
+  // which ones match with a single scan of the buffer. Usually there's just
+  // one set, unless the regexes are too many to fit the compiled size limit
+  shards: Vec<RegexShard>,
 
   max_buffer_size: usize,
 }
 
+struct RegexEntry<Id> {
+  id: Id,
+  pattern: String,
+
+  // The single regexes are only needed to find the captures of the matching
+  // ones, so they are compiled lazily to keep the startup time low
+  regex: OnceLock<Option<Regex>>,
+}
+
+struct RegexShard {
+  set: RegexSet,
+
+  // Maps the indexes of the set patterns to the matcher entries
+  entries: Vec<usize>,
+}
+
 impl<'a, Id> Matcher<'a, RegexMatcherState, Id> for RegexMatcher<Id>
 where
   Id: Clone,
@@ -77,6 +94,24 @@ where
     prev_state: Option<&RegexMatcherState>,
     event: Event,
   ) -> (RegexMatcherState, Vec<MatchResult<Id>>) {
+    self.process_filtered(prev_state, event, Some)
+  }
+}
+
+impl<Id: Clone> RegexMatcher<Id> {
+  /// Process the event as `Matcher::process`, but pass each match through
+  /// the given filter, which can transform or reject it (for example when
+  /// its captures are not valid). When all the matches are rejected, the
+  /// buffer is kept as if the event produced none.
+  pub fn process_filtered<F>(
+    &self,
+    prev_state: Option<&RegexMatcherState>,
+    event: Event,
+    filter: F,
+  ) -> (RegexMatcherState, Vec<MatchResult<Id>>)
+  where
+    F: FnMut(MatchResult<Id>) -> Option<MatchResult<Id>>,
+  {
     let mut buffer = if let Some(prev_state) = prev_state {
       prev_state.buffer.clone()
     } else {
@@ -97,31 +132,17 @@ where
     }
 
     // Find matches
-    if self.regex_set.is_match(&buffer) {
-      let mut matches = Vec::new();
-
-      for index in self.regex_set.matches(&buffer) {
-        if let (Some(id), Some(regex)) = (self.ids.get(index), self.regexes.get(index)) {
-          if let Some(captures) = regex.captures(&buffer) {
-            let full_match = captures.get(0).map_or("", |m| m.as_str());
-            if !full_match.is_empty() {
-              // Now extract the captured names as variables
-              let variables: HashMap<String, String> = regex
-                .capture_names()
-                .flatten()
-                .filter_map(|n| Some((n.to_string(), captures.name(n)?.as_str().to_string())))
-                .collect();
-
-              let result = MatchResult {
-                id: (*id).clone(),
-                trigger: full_match.to_string(),
-                left_separator: None,
-                right_separator: None,
-                vars: variables,
-              };
-
-              matches.push(result);
-            }
+    let mut matches = Vec::new();
+    for shard in &self.shards {
+      // Checking for any match first is faster, and most events don't produce one
+      if !shard.set.is_match(&buffer) {
+        continue;
+      }
+
+      for index in shard.set.matches(&buffer) {
+        if let Some(entry) = shard.entries.get(index).and_then(|i| self.entries.get(*i)) {
+          if let Some(result) = entry.find_match(&buffer) {
+            matches.push(result);
           }
         } else {
           error!(
@@ -130,10 +151,11 @@ where
           );
         }
       }
+    }
 
-      if !matches.is_empty() {
-        return (RegexMatcherState::default(), matches);
-      }
+    let matches: Vec<MatchResult<Id>> = matches.into_iter().filter_map(filter).collect();
+    if !matches.is_empty() {
+      return (RegexMatcherState::default(), matches);
     }
 
     let current_state = RegexMatcherState { buffer };
@@ -141,32 +163,113 @@ where
   }
 }
 
+// Same as the default limit of the regex crate
+const DEFAULT_SET_SIZE_LIMIT: usize = 10 * (1 << 20);
+
 impl<Id: Clone> RegexMatcher<Id> {
   pub fn new(matches: &[RegexMatch<Id>], opt: RegexMatcherOptions) -> Self {
-    let mut ids = Vec::new();
-    let mut regexes = Vec::new();
-    let mut good_regexes = Vec::new();
-
-    for m in matches {
-      match Regex::new(&m.regex) {
-        Ok(regex) => {
-          ids.push(m.id.clone());
-          good_regexes.push(&m.regex);
-          regexes.push(regex);
-        }
+    Self::with_set_size_limit(matches, opt, DEFAULT_SET_SIZE_LIMIT)
+  }
+
+  fn with_set_size_limit(
+    matches: &[RegexMatch<Id>],
+    opt: RegexMatcherOptions,
+    set_size_limit: usize,
+  ) -> Self {
+    let entries: Vec<RegexEntry<Id>> = matches
+      .iter()
+      .map(|m| RegexEntry {
+        id: m.id.clone(),
+        pattern: m.regex.clone(),
+        regex: OnceLock::new(),
+      })
+      .collect();
+
+    // Invalid regexes are isolated and logged while splitting the sets
+    let indexes: Vec<usize> = (0..entries.len()).collect();
+    let mut shards = Vec::new();
+    build_shards(&entries, &indexes, set_size_limit, &mut shards);
+
+    Self {
+      entries,
+      shards,
+      max_buffer_size: opt.max_buffer_size,
+    }
+  }
+}
+
+impl<Id: Clone> RegexEntry<Id> {
+  fn find_match(&self, buffer: &str) -> Option<MatchResult<Id>> {
+    let regex = self
+      .regex
+      .get_or_init(|| match Regex::new(&self.pattern) {
+        Ok(regex) => Some(regex),
         Err(err) => {
-          error!("unable to compile regex: '{}', error: {:?}", m.regex, err);
+          error!(
+            "unable to compile regex: '{}', error: {:?}",
+            self.pattern, err
+          );
+          None
         }
-      }
+      })
+      .as_ref()?;
+
+    let captures = regex.captures(buffer)?;
+    let full_match = captures.get(0).map_or("", |m| m.as_str());
+    if full_match.is_empty() {
+      return None;
     }
 
-    let regex_set = RegexSet::new(&good_regexes).expect("unable to build regex set");
+    // Now extract the captured names as variables
+    let variables: HashMap<String, String> = regex
+      .capture_names()
+      .flatten()
+      .filter_map(|n| Some((n.to_string(), captures.name(n)?.as_str().to_string())))
+      .collect();
 
-    Self {
-      ids,
-      regex_set,
-      regexes,
-      max_buffer_size: opt.max_buffer_size,
+    Some(MatchResult {
+      id: self.id.clone(),
+      trigger: full_match.to_string(),
+      left_separator: None,
+      right_separator: None,
+      vars: variables,
+      distance: 0,
+    })
+  }
+}
+
+// Combine the given entries into as few sets as possible, splitting them
+// in halves whenever the compiled set would exceed the size limit or
+// contains an invalid regex
+fn build_shards<Id>(
+  entries: &[RegexEntry<Id>],
+  indexes: &[usize],
+  size_limit: usize,
+  shards: &mut Vec<RegexShard>,
+) {
+  if indexes.is_empty() {
+    return;
+  }
+
+  let patterns = indexes.iter().map(|i| &entries[*i].pattern);
+  match RegexSetBuilder::new(patterns)
+    .size_limit(size_limit)
+    .build()
+  {
+    Ok(set) => shards.push(RegexShard {
+      set,
+      entries: indexes.to_vec(),
+    }),
+    Err(err) if indexes.len() == 1 => {
+      error!(
+        "unable to compile regex: '{}', error: {:?}",
+        entries[indexes[0]].pattern, err
+      );
+    }
+    Err(_) => {
+      let (left, right) = indexes.split_at(indexes.len() / 2);
+      build_shards(entries, left, size_limit, shards);
+      build_shards(entries, right, size_limit, shards);
     }
   }
 }
@@ -174,7 +277,7 @@ impl<Id: Clone> RegexMatcher<Id> {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::util::tests::get_matches_after_str;
+  use crate::{event::Key, util::tests::get_matches_after_str};
 
   fn match_result<Id: Default>(id: Id, trigger: &str, vars: &[(&str, &str)]) -> MatchResult<Id> {
     let vars: HashMap<String, String> = vars
@@ -188,6 +291,7 @@ mod tests {
       left_separator: None,
       right_separator: None,
       vars,
+      distance: 0,
     }
   }
 
@@ -245,6 +349,30 @@ mod tests {
     );
   }
 
+  #[test]
+  fn matcher_keeps_buffer_when_matches_are_rejected() {
+    let matcher = RegexMatcher::new(
+      &[RegexMatch::new(1, "abc"), RegexMatch::new(2, "abcd")],
+      RegexMatcherOptions::default(),
+    );
+
+    let mut state = None;
+    let mut matches = Vec::new();
+    for c in ["a", "b", "c", "d"] {
+      let event = Event::Key {
+        key: Key::Other,
+        chars: Some(c.to_string()),
+      };
+      let (next_state, results) = matcher.process_filtered(state.as_ref(), event, |result| {
+        (result.id != 1).then_some(result)
+      });
+      state = Some(next_state);
+      matches = results;
+    }
+
+    assert_eq!(matches, vec![match_result(2, "abcd", &[])]);
+  }
+
   #[test]
   fn matcher_max_buffer_size() {
     let matcher = RegexMatcher::new(
@@ -265,4 +393,42 @@ mod tests {
       vec![]
     );
   }
+
+  #[test]
+  fn matcher_skips_invalid_regexes() {
+    let matcher = RegexMatcher::new(
+      &[
+        RegexMatch::new(1, "hello"),
+        RegexMatch::new(2, "invalid("),
+        RegexMatch::new(3, "world"),
+      ],
+      RegexMatcherOptions::default(),
+    );
+    assert_eq!(
+      get_matches_after_str("hello", &matcher),
+      vec![match_result(1, "hello", &[])]
+    );
+    assert_eq!(
+      get_matches_after_str("world", &matcher),
+      vec![match_result(3, "world", &[])]
+    );
+  }
+
+  #[test]
+  fn matcher_splits_regexes_exceeding_size_limit() {
+    let matches: Vec<RegexMatch<usize>> = (0..150)
+      .map(|i| RegexMatch::new(i, &format!("cmd{i}\\((?P<arg>[a-z]+)\\)")))
+      .collect();
+    let matcher =
+      RegexMatcher::with_set_size_limit(&matches, RegexMatcherOptions::default(), 20_000);
+    assert!(matcher.shards.len() > 1);
+    assert_eq!(
+      get_matches_after_str("cmd0(test)", &matcher),
+      vec![match_result(0, "cmd0(test)", &[("arg", "test")])]
+    );
+    assert_eq!(
+      get_matches_after_str("cmd149(test)", &matcher),
+      vec![match_result(149, "cmd149(test)", &[("arg", "test")])]
+    );
+  }
 }
@@ -0,0 +1,191 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashSet;
+
+use super::RollingItem;
+
+/// Maximum edit distance supported by the fuzzy matching, as the number of
+/// variants grows quickly with each additional edit
+pub const MAX_FUZZY_DISTANCE: usize = 2;
+
+/// Variants with a missing character must be at least this long, otherwise
+/// they would be triggered too easily while typing regular text
+const MIN_DELETION_VARIANT_LENGTH: usize = 4;
+
+/// Generate the typo variants of the given items, reachable with at most
+/// `max_distance` edits, along with the number of edits they required.
+/// Supported edits are the transposition of two adjacent characters and
+/// the removal of a character. Only alphanumeric characters are edited, so
+/// that trigger prefixes (such as ':') and word separators are always required.
+/// The first alphanumeric character is never edited either, otherwise the
+/// tail of longer words would match (typing "deceive" would expand "receive").
+pub(crate) fn generate_variants(
+  items: &[RollingItem],
+  max_distance: usize,
+) -> Vec<(Vec<RollingItem>, usize)> {
+  let mut seen = HashSet::new();
+  seen.insert(items.to_vec());
+  let mut variants = Vec::new();
+  let mut frontier = vec![items.to_vec()];
+
+  for distance in 1..=max_distance.min(MAX_FUZZY_DISTANCE) {
+    let mut next_frontier = Vec::new();
+
+    for current in &frontier {
+      for variant in single_edit_variants(current) {
+        // Variants that are a prefix of the original trigger would be
+        // expanded before the user finishes typing it
+        if items.starts_with(&variant) || !seen.insert(variant.clone()) {
+          continue;
+        }
+
+        variants.push((variant.clone(), distance));
+        next_frontier.push(variant);
+      }
+    }
+
+    frontier = next_frontier;
+  }
+
+  variants
+}
+
+fn single_edit_variants(items: &[RollingItem]) -> Vec<Vec<RollingItem>> {
+  let mut variants = Vec::new();
+  let first_editable_index = items.iter().position(is_editable);
+
+  for i in 1..items.len() {
+    if is_editable(&items[i - 1])
+      && is_editable(&items[i])
+      && items[i - 1] != items[i]
+      && Some(i - 1) != first_editable_index
+    {
+      let mut variant = items.to_vec();
+      variant.swap(i - 1, i);
+      variants.push(variant);
+    }
+  }
+
+  let char_count = items.iter().filter(|item| is_char(item)).count();
+  if char_count > MIN_DELETION_VARIANT_LENGTH {
+    // The last character is never removed, as the resulting variant
+    // would be expanded before the trigger is completely typed
+    let last_char_index = items.iter().rposition(is_char);
+    for (i, item) in items.iter().enumerate() {
+      if is_editable(item) && Some(i) != last_char_index && Some(i) != first_editable_index {
+        let mut variant = items.to_vec();
+        variant.remove(i);
+        variants.push(variant);
+      }
+    }
+  }
+
+  variants
+}
+
+fn is_char(item: &RollingItem) -> bool {
+  matches!(item, RollingItem::Char(_) | RollingItem::CharInsensitive(_))
+}
+
+fn is_editable(item: &RollingItem) -> bool {
+  match item {
+    RollingItem::Char(c) | RollingItem::CharInsensitive(c) => c.chars().all(char::is_alphanumeric),
+    RollingItem::WordSeparator | RollingItem::Key(_) => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::rolling::{RollingMatch, StringMatchOptions};
+
+  fn variants_of(trigger: &str, max_distance: usize) -> Vec<(String, usize)> {
+    let items = RollingMatch::from_string(0, trigger, &StringMatchOptions::default()).items;
+    generate_variants(&items, max_distance)
+      .into_iter()
+      .map(|(variant, distance)| {
+        (
+          crate::rolling::util::extract_string_from_items(&variant),
+          distance,
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn zero_distance_has_no_variants() {
+    assert!(variants_of("hello", 0).is_empty());
+  }
+
+  #[test]
+  fn transpositions_and_deletions() {
+    let variants = variants_of("hello", 1);
+    assert!(variants.contains(&("hlelo".to_string(), 1)));
+    assert!(variants.contains(&("helol".to_string(), 1)));
+    assert!(variants.contains(&("hllo".to_string(), 1)));
+    assert!(variants.contains(&("helo".to_string(), 1)));
+    // Swapping identical characters produces the original trigger
+    assert!(!variants.iter().any(|(variant, _)| variant == "hello"));
+    // The last character is never removed
+    assert!(!variants.iter().any(|(variant, _)| variant == "hell"));
+  }
+
+  #[test]
+  fn short_triggers_only_allow_transpositions() {
+    assert_eq!(variants_of("teh", 1), vec![("the".to_string(), 1)]);
+  }
+
+  #[test]
+  fn first_character_is_never_edited() {
+    let variants = variants_of("receive", 2);
+    assert!(!variants.is_empty());
+    assert!(variants.iter().all(|(variant, _)| variant.starts_with('r')));
+    assert!(variants_of(":receive", 2)
+      .iter()
+      .all(|(variant, _)| variant.starts_with(":r")));
+  }
+
+  #[test]
+  fn prefixes_are_never_edited() {
+    let variants = variants_of(":hello", 1);
+    assert!(variants.iter().all(|(variant, _)| variant.starts_with(':')));
+  }
+
+  #[test]
+  fn variants_track_minimal_distance() {
+    let variants = variants_of("abcdef", 2);
+    assert!(variants.contains(&("acbdef".to_string(), 1)));
+    assert!(variants.contains(&("acbedf".to_string(), 2)));
+    assert!(variants.contains(&("acdf".to_string(), 2)));
+    assert_eq!(
+      variants
+        .iter()
+        .filter(|(variant, _)| variant == "acbdef")
+        .count(),
+      1
+    );
+  }
+
+  #[test]
+  fn distance_is_capped() {
+    let capped = variants_of("abcdef", MAX_FUZZY_DISTANCE);
+    assert_eq!(variants_of("abcdef", 10), capped);
+  }
+}
@@ -17,12 +17,17 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::collections::HashMap;
+use std::{
+  collections::HashMap,
+  hash::Hash,
+  time::{Duration, Instant},
+};
 
 use super::{
+  fuzzy::generate_variants,
   tree::{MatcherTreeNode, MatcherTreeRef},
   util::extract_string_from_events,
-  RollingMatch,
+  RollingMatch, TriggerConflict, TypingInterval,
 };
 use crate::Matcher;
 use crate::{
@@ -36,11 +41,15 @@ pub(crate) type IsWordSeparator = bool;
 #[derive(Clone)]
 pub struct RollingMatcherState<'a, Id> {
   paths: Vec<RollingMatcherStatePath<'a, Id>>,
+  last_event_time: Option<Instant>,
 }
 
 impl<'a, Id> Default for RollingMatcherState<'a, Id> {
   fn default() -> Self {
-    Self { paths: Vec::new() }
+    Self {
+      paths: Vec::new(),
+      last_event_time: None,
+    }
   }
 }
 
@@ -48,6 +57,37 @@ impl<'a, Id> Default for RollingMatcherState<'a, Id> {
 struct RollingMatcherStatePath<'a, Id> {
   node: &'a MatcherTreeNode<Id>,
   events: Vec<(Event, IsWordSeparator)>,
+  distance: usize,
+  timing: PathTiming,
+}
+
+#[derive(Clone, Copy)]
+struct PathTiming {
+  // Time elapsed between the previous event and the first one of the path,
+  // if there was a previous event
+  pause: Option<Duration>,
+  longest_interval: Duration,
+  last_event_time: Instant,
+}
+
+impl PathTiming {
+  fn start(prev_event_time: Option<Instant>, now: Instant) -> Self {
+    Self {
+      pause: prev_event_time.map(|time| now.saturating_duration_since(time)),
+      longest_interval: Duration::ZERO,
+      last_event_time: now,
+    }
+  }
+
+  fn advance(&self, now: Instant) -> Self {
+    Self {
+      pause: self.pause,
+      longest_interval: self
+        .longest_interval
+        .max(now.saturating_duration_since(self.last_event_time)),
+      last_event_time: now,
+    }
+  }
 }
 
 #[derive(Default)]
@@ -61,16 +101,45 @@ pub struct RollingMatcher<Id> {
   key_word_separators: Vec<Key>,
 
   root: MatcherTreeNode<Id>,
+
+  // Trees containing the typo variants of fuzzy matches, where
+  // the one at index i holds the variants at distance i + 1
+  fuzzy_roots: Vec<MatcherTreeNode<Id>>,
+
+  // Only the matches with timing constraints are listed here
+  typing_intervals: HashMap<Id, TypingInterval>,
+
+  conflicts: Vec<TriggerConflict<Id>>,
 }
 
 impl<'a, Id> Matcher<'a, RollingMatcherState<'a, Id>, Id> for RollingMatcher<Id>
 where
-  Id: Clone,
+  Id: Clone + Eq + Hash,
 {
+  // The typing intervals are measured between the times at which the events
+  // are processed, not the ones at which the keys were pressed. If the events
+  // pile up (for example, while an expansion is being injected), they are
+  // processed in a burst and look faster than they were typed.
   fn process(
     &'a self,
     prev_state: Option<&RollingMatcherState<'a, Id>>,
     event: Event,
+  ) -> (RollingMatcherState<'a, Id>, Vec<MatchResult<Id>>) {
+    self.process_at(prev_state, event, Instant::now())
+  }
+}
+
+impl<Id> RollingMatcher<Id>
+where
+  Id: Clone + Eq + Hash,
+{
+  /// Same as `process`, but with an explicit time for the event, which
+  /// is used to check the typing intervals of the matches
+  pub fn process_at<'a>(
+    &'a self,
+    prev_state: Option<&RollingMatcherState<'a, Id>>,
+    event: Event,
+    now: Instant,
   ) -> (RollingMatcherState<'a, Id>, Vec<MatchResult<Id>>) {
     let mut next_refs = Vec::new();
 
@@ -84,65 +153,154 @@ where
             .map(|(node_ref, is_word_separator)| {
               let mut new_events = node_path.events.clone();
               new_events.push((event.clone(), is_word_separator));
-              (node_ref, new_events)
+              (
+                node_ref,
+                new_events,
+                node_path.distance,
+                node_path.timing.advance(now),
+              )
             }),
         );
       }
     }
 
     // Calculate new ones
-    let root_refs = self.find_refs(&self.root, &event, prev_state.is_some());
-    next_refs.extend(
-      root_refs
-        .into_iter()
-        .map(|(node_ref, is_word_separator)| (node_ref, vec![(event.clone(), is_word_separator)])),
-    );
+    let prev_event_time = prev_state.and_then(|state| state.last_event_time);
+    let roots = std::iter::once(&self.root).chain(self.fuzzy_roots.iter());
+    for (distance, root) in roots.enumerate() {
+      let root_refs = self.find_refs(root, &event, prev_state.is_some());
+      next_refs.extend(root_refs.into_iter().map(|(node_ref, is_word_separator)| {
+        (
+          node_ref,
+          vec![(event.clone(), is_word_separator)],
+          distance,
+          PathTiming::start(prev_event_time, now),
+        )
+      }));
+    }
 
     let mut next_paths = Vec::new();
+    let mut results: Vec<MatchResult<Id>> = Vec::new();
+    let mut matched_distances = Vec::new();
 
-    for (node_ref, events) in next_refs {
+    for (node_ref, events, distance, timing) in next_refs {
       match node_ref {
         MatcherTreeRef::Matches(matches) => {
-          let (trigger, left_separator, right_separator) = extract_string_from_events(&events);
-          let results = matches
+          // Only the first path reaching a match is considered for each distance
+          if matched_distances.contains(&distance) {
+            continue;
+          }
+
+          let matches: Vec<&Id> = matches
             .iter()
-            .map(|id| MatchResult {
+            .filter(|id| self.is_typing_interval_satisfied(id, &timing))
+            .collect();
+          if matches.is_empty() {
+            continue;
+          }
+          matched_distances.push(distance);
+
+          let (trigger, left_separator, right_separator) = extract_string_from_events(&events);
+          for id in matches {
+            if results.iter().any(|result| result.id == *id) {
+              continue;
+            }
+
+            results.push(MatchResult {
               id: id.clone(),
               trigger: trigger.clone(),
               left_separator: left_separator.clone(),
               right_separator: right_separator.clone(),
               vars: HashMap::new(),
-            })
-            .collect();
-
-          // Reset the state and return the matches
-          return (RollingMatcherState::default(), results);
+              distance,
+            });
+          }
         }
         MatcherTreeRef::Node(node) => {
           next_paths.push(RollingMatcherStatePath {
             node: node.as_ref(),
             events,
+            distance,
+            timing,
           });
         }
       }
     }
 
-    let current_state = RollingMatcherState { paths: next_paths };
+    if !results.is_empty() {
+      // Exact matches come first, followed by the fuzzy ones
+      results.sort_by_key(|result| result.distance);
+
+      // Reset the state and return the matches
+      let state = RollingMatcherState {
+        paths: Vec::new(),
+        last_event_time: Some(now),
+      };
+      return (state, results);
+    }
+
+    let current_state = RollingMatcherState {
+      paths: next_paths,
+      last_event_time: Some(now),
+    };
 
     (current_state, Vec::new())
   }
+
+  fn is_typing_interval_satisfied(&self, id: &Id, timing: &PathTiming) -> bool {
+    self
+      .typing_intervals
+      .get(id)
+      .is_none_or(|interval| interval.is_satisfied_by(timing.pause, timing.longest_interval))
+  }
 }
 
-impl<Id: Clone> RollingMatcher<Id> {
+impl<Id: Clone + Eq + Hash> RollingMatcher<Id> {
   pub fn new(matches: &[RollingMatch<Id>], opt: RollingMatcherOptions) -> Self {
     let root = MatcherTreeNode::from_matches(matches);
+    let conflicts = root.find_conflicts(matches);
+
+    let mut fuzzy_matches: Vec<Vec<RollingMatch<Id>>> = Vec::new();
+    for m in matches {
+      for (items, distance) in generate_variants(&m.items, m.fuzzy) {
+        if fuzzy_matches.len() < distance {
+          fuzzy_matches.resize_with(distance, Vec::new);
+        }
+        fuzzy_matches[distance - 1].push(RollingMatch::from_items(m.id.clone(), &items));
+      }
+    }
+    let fuzzy_roots = fuzzy_matches
+      .iter()
+      .map(|variants| MatcherTreeNode::from_matches(variants))
+      .collect();
+
+    let mut typing_intervals = HashMap::new();
+    for m in matches {
+      if m.typing_interval.is_constrained() {
+        typing_intervals
+          .entry(m.id.clone())
+          .or_insert(m.typing_interval);
+      }
+    }
+
     Self {
       root,
+      fuzzy_roots,
+      typing_intervals,
+      conflicts,
       char_word_separators: opt.char_word_separators,
       key_word_separators: opt.key_word_separators,
     }
   }
 
+  /// Return the triggers that interfere with each other, detected
+  /// while building the matcher
+  pub fn conflicts(&self) -> &[TriggerConflict<Id>] {
+    &self.conflicts
+  }
+}
+
+impl<Id: Clone> RollingMatcher<Id> {
   fn find_refs<'a>(
     &'a self,
     node: &'a MatcherTreeNode<Id>,
@@ -353,4 +511,194 @@ mod tests {
       vec![match_result(3, "ARTY")]
     );
   }
+
+  #[test]
+  fn matcher_process_fuzzy_matches() {
+    let matcher = RollingMatcher::new(
+      &[
+        RollingMatch::from_string(
+          1,
+          "receive",
+          &StringMatchOptions {
+            fuzzy: 1,
+            ..Default::default()
+          },
+        ),
+        RollingMatch::from_string(2, "hello", &StringMatchOptions::default()),
+      ],
+      RollingMatcherOptions::default(),
+    );
+
+    assert_eq!(
+      get_matches_after_str("receive", &matcher),
+      vec![match_result(1, "receive")]
+    );
+    assert_eq!(
+      get_matches_after_str("recieve", &matcher),
+      vec![MatchResult {
+        distance: 1,
+        ..match_result(1, "recieve")
+      }]
+    );
+    assert_eq!(
+      get_matches_after_str("recive", &matcher),
+      vec![MatchResult {
+        distance: 1,
+        ..match_result(1, "recive")
+      }]
+    );
+    // Only one typo is tolerated
+    assert_eq!(get_matches_after_str("rcieve", &matcher), vec![]);
+    // Longer words ending with a variant are not matched
+    assert_eq!(get_matches_after_str("deceive", &matcher), vec![]);
+    // Matches are exact unless fuzzy is enabled
+    assert_eq!(get_matches_after_str("hlelo", &matcher), vec![]);
+  }
+
+  #[test]
+  fn matcher_process_fuzzy_ranks_exact_matches_first() {
+    let matcher = RollingMatcher::new(
+      &[
+        RollingMatch::from_string(
+          1,
+          "form",
+          &StringMatchOptions {
+            fuzzy: 1,
+            ..Default::default()
+          },
+        ),
+        RollingMatch::from_string(2, "from", &StringMatchOptions::default()),
+      ],
+      RollingMatcherOptions::default(),
+    );
+
+    assert_eq!(
+      get_matches_after_str("from", &matcher),
+      vec![
+        match_result(2, "from"),
+        MatchResult {
+          distance: 1,
+          ..match_result(1, "from")
+        }
+      ]
+    );
+  }
+
+  // Type each (char, milliseconds since the previous one) pair
+  fn get_matches_after_timed_str(
+    keys: &[(&str, u64)],
+    matcher: &RollingMatcher<i32>,
+  ) -> Vec<MatchResult<i32>> {
+    let mut now = Instant::now();
+    let mut prev_state = None;
+    let mut matches = Vec::new();
+
+    for (c, delay) in keys {
+      now += Duration::from_millis(*delay);
+      let (state, vec_matches) = matcher.process_at(
+        prev_state.as_ref(),
+        Event::Key {
+          key: Key::Other,
+          chars: Some((*c).to_string()),
+        },
+        now,
+      );
+
+      prev_state = Some(state);
+      matches = vec_matches;
+    }
+
+    matches
+  }
+
+  #[test]
+  fn matcher_process_max_typing_interval() {
+    let matcher = RollingMatcher::new(
+      &[RollingMatch::from_string(
+        1,
+        "ty",
+        &StringMatchOptions {
+          typing_interval: TypingInterval {
+            max: Some(Duration::from_millis(100)),
+            ..Default::default()
+          },
+          ..Default::default()
+        },
+      )],
+      RollingMatcherOptions::default(),
+    );
+
+    assert_eq!(
+      get_matches_after_timed_str(&[("t", 0), ("y", 50)], &matcher),
+      vec![match_result(1, "ty")]
+    );
+    assert_eq!(
+      get_matches_after_timed_str(&[("t", 0), ("y", 500)], &matcher),
+      vec![]
+    );
+  }
+
+  #[test]
+  fn matcher_process_min_typing_interval() {
+    let matcher = RollingMatcher::new(
+      &[RollingMatch::from_string(
+        1,
+        "ty",
+        &StringMatchOptions {
+          typing_interval: TypingInterval {
+            min: Some(Duration::from_millis(300)),
+            ..Default::default()
+          },
+          ..Default::default()
+        },
+      )],
+      RollingMatcherOptions::default(),
+    );
+
+    // Part of a word typed quickly
+    assert_eq!(
+      get_matches_after_timed_str(&[("p", 0), ("t", 50), ("y", 50)], &matcher),
+      vec![]
+    );
+    // Typed after a pause
+    assert_eq!(
+      get_matches_after_timed_str(&[("p", 0), ("t", 500), ("y", 50)], &matcher),
+      vec![match_result(1, "ty")]
+    );
+    // Nothing typed before
+    assert_eq!(
+      get_matches_after_timed_str(&[("t", 0), ("y", 50)], &matcher),
+      vec![match_result(1, "ty")]
+    );
+  }
+
+  #[test]
+  fn matcher_process_typing_interval_only_affects_its_match() {
+    let matcher = RollingMatcher::new(
+      &[
+        RollingMatch::from_string(
+          1,
+          "ty",
+          &StringMatchOptions {
+            typing_interval: TypingInterval {
+              max: Some(Duration::from_millis(100)),
+              ..Default::default()
+            },
+            ..Default::default()
+          },
+        ),
+        RollingMatch::from_string(2, "ty", &StringMatchOptions::default()),
+      ],
+      RollingMatcherOptions::default(),
+    );
+
+    assert_eq!(
+      get_matches_after_timed_str(&[("t", 0), ("y", 50)], &matcher),
+      vec![match_result(1, "ty"), match_result(2, "ty")]
+    );
+    assert_eq!(
+      get_matches_after_timed_str(&[("t", 0), ("y", 500)], &matcher),
+      vec![match_result(2, "ty")]
+    );
+  }
 }
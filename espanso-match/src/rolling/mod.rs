@@ -17,13 +17,16 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::time::Duration;
+
 use crate::event::Key;
 
+mod fuzzy;
 pub mod matcher;
 mod tree;
 mod util;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum RollingItem {
   WordSeparator,
   Key(Key),
@@ -35,11 +38,52 @@ pub enum RollingItem {
 pub struct RollingMatch<Id> {
   pub id: Id,
   pub items: Vec<RollingItem>,
+
+  /// Maximum number of typos (transposed or missing characters)
+  /// tolerated when matching the items, 0 to only match them exactly
+  pub fuzzy: usize,
+
+  pub typing_interval: TypingInterval,
+}
+
+/// Timing constraints on the keystrokes, used to tell apart a trigger typed
+/// on purpose from one appearing by accident while typing other words.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TypingInterval {
+  /// Minimum pause between the previous keystroke and the first one of the trigger
+  pub min: Option<Duration>,
+  /// Maximum time between two consecutive keystrokes of the trigger
+  pub max: Option<Duration>,
+}
+
+impl TypingInterval {
+  pub fn is_constrained(&self) -> bool {
+    self.min.is_some() || self.max.is_some()
+  }
+
+  /// Check the constraints against the pause before the trigger, if known,
+  /// and the longest time between two of its keystrokes
+  pub fn is_satisfied_by(&self, pause: Option<Duration>, longest_interval: Duration) -> bool {
+    if self.max.is_some_and(|max| longest_interval > max) {
+      return false;
+    }
+
+    // Without a previous keystroke, there's no evidence of a fast typing
+    match (self.min, pause) {
+      (Some(min), Some(pause)) => pause >= min,
+      _ => true,
+    }
+  }
 }
 
 impl<Id> RollingMatch<Id> {
   pub fn new(id: Id, items: Vec<RollingItem>) -> Self {
-    Self { id, items }
+    Self {
+      id,
+      items,
+      fuzzy: 0,
+      typing_interval: TypingInterval::default(),
+    }
   }
 
   pub fn from_string(id: Id, string: &str, opt: &StringMatchOptions) -> Self {
@@ -61,13 +105,67 @@ impl<Id> RollingMatch<Id> {
       items.push(RollingItem::WordSeparator);
     }
 
-    Self { id, items }
+    Self {
+      id,
+      items,
+      fuzzy: opt.fuzzy,
+      typing_interval: opt.typing_interval,
+    }
   }
 
   pub fn from_items(id: Id, items: &[RollingItem]) -> Self {
     Self {
       id,
       items: items.to_vec(),
+      fuzzy: 0,
+      typing_interval: TypingInterval::default(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictKind {
+  /// Both matches share the same trigger
+  Duplicate,
+  /// The shadowed trigger starts with the other one, so it's never expanded
+  Prefix,
+  /// The shadowed trigger contains the other one, so it's never expanded
+  Infix,
+  /// The other trigger ends with the shadowed one, which is not expanded
+  /// while typing it
+  Suffix,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TriggerConflict<Id> {
+  pub kind: ConflictKind,
+
+  // The shadowed match
+  pub id: Id,
+  pub trigger: String,
+
+  // The match taking precedence
+  pub other_id: Id,
+  pub other_trigger: String,
+}
+
+impl<Id> std::fmt::Display for TriggerConflict<Id> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let (trigger, other) = (&self.trigger, &self.other_trigger);
+    match self.kind {
+      ConflictKind::Duplicate => write!(f, "trigger '{trigger}' is defined by multiple matches"),
+      ConflictKind::Prefix => write!(
+        f,
+        "trigger '{trigger}' is never expanded, because it starts with '{other}'"
+      ),
+      ConflictKind::Infix => write!(
+        f,
+        "trigger '{trigger}' is never expanded, because it contains '{other}'"
+      ),
+      ConflictKind::Suffix => write!(
+        f,
+        "trigger '{trigger}' is not expanded while typing '{other}', which ends with it"
+      ),
     }
   }
 }
@@ -77,6 +175,8 @@ pub struct StringMatchOptions {
   pub case_insensitive: bool,
   pub left_word: bool,
   pub right_word: bool,
+  pub fuzzy: usize,
+  pub typing_interval: TypingInterval,
 }
 
 #[cfg(test)]
@@ -94,7 +194,9 @@ mod tests {
           RollingItem::Char("e".to_string()),
           RollingItem::Char("s".to_string()),
           RollingItem::Char("t".to_string()),
-        ]
+        ],
+        fuzzy: 0,
+        typing_interval: TypingInterval::default(),
       }
     );
   }
@@ -118,7 +220,9 @@ mod tests {
           RollingItem::Char("e".to_string()),
           RollingItem::Char("s".to_string()),
           RollingItem::Char("t".to_string()),
-        ]
+        ],
+        fuzzy: 0,
+        typing_interval: TypingInterval::default(),
       }
     );
   }
@@ -142,7 +246,9 @@ mod tests {
           RollingItem::Char("s".to_string()),
           RollingItem::Char("t".to_string()),
           RollingItem::WordSeparator,
-        ]
+        ],
+        fuzzy: 0,
+        typing_interval: TypingInterval::default(),
       }
     );
   }
@@ -165,7 +271,9 @@ mod tests {
           RollingItem::CharInsensitive("e".to_string()),
           RollingItem::CharInsensitive("s".to_string()),
           RollingItem::CharInsensitive("t".to_string()),
-        ]
+        ],
+        fuzzy: 0,
+        typing_interval: TypingInterval::default(),
       }
     );
   }
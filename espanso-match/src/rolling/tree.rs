@@ -17,11 +17,15 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::{collections::HashSet, hash::Hash};
+
 use unicase::UniCase;
 
 use crate::event::Key;
 
-use super::{RollingItem, RollingMatch};
+use super::{
+  util::extract_string_from_items, ConflictKind, RollingItem, RollingMatch, TriggerConflict,
+};
 
 #[derive(Debug, PartialEq)]
 pub(crate) enum MatcherTreeRef<Id> {
@@ -61,6 +65,138 @@ where
   }
 }
 
+impl<Id> MatcherTreeNode<Id>
+where
+  Id: Clone + Eq + Hash,
+{
+  /// Detect the triggers interfering with each other once inserted in the tree,
+  /// by typing each trigger (starting from all its positions) and collecting the
+  /// matches that would be expanded along the way.
+  pub fn find_conflicts(&self, matches: &[RollingMatch<Id>]) -> Vec<TriggerConflict<Id>> {
+    let mut conflicts = Vec::new();
+    let mut reported = HashSet::new();
+    let mut reported_duplicates = HashSet::new();
+
+    for m in matches {
+      let last = m.items.len().saturating_sub(1);
+      for start in 0..m.items.len() {
+        let mut found = Vec::new();
+        self.walk(&m.items[start..], &mut found);
+
+        for (end, ids) in found {
+          let end = start + end;
+          for id in ids.iter().filter(|id| **id != m.id) {
+            let trigger = extract_string_from_items(&m.items);
+            let other_trigger = extract_string_from_items(&m.items[start..=end]);
+
+            let conflict = if start == 0 && end == last {
+              // Report duplicates only once per pair
+              if reported_duplicates.contains(&(id.clone(), m.id.clone())) {
+                continue;
+              }
+              reported_duplicates.insert((m.id.clone(), id.clone()));
+              TriggerConflict {
+                kind: ConflictKind::Duplicate,
+                id: m.id.clone(),
+                trigger,
+                other_id: id.clone(),
+                other_trigger,
+              }
+            } else if end < last {
+              TriggerConflict {
+                kind: if start == 0 {
+                  ConflictKind::Prefix
+                } else {
+                  ConflictKind::Infix
+                },
+                id: m.id.clone(),
+                trigger,
+                other_id: id.clone(),
+                other_trigger,
+              }
+            } else {
+              TriggerConflict {
+                kind: ConflictKind::Suffix,
+                id: id.clone(),
+                trigger: other_trigger,
+                other_id: m.id.clone(),
+                other_trigger: trigger,
+              }
+            };
+
+            if reported.insert(conflict.clone()) {
+              conflicts.push(conflict);
+            }
+          }
+        }
+      }
+    }
+
+    conflicts
+  }
+
+  // Follow the given items down the tree, collecting the matches reached
+  // along with the index of the item that completed them
+  fn walk<'a>(&'a self, items: &[RollingItem], found: &mut Vec<(usize, &'a [Id])>) {
+    self.walk_recursively(items, 0, found);
+  }
+
+  fn walk_recursively<'a>(
+    &'a self,
+    items: &[RollingItem],
+    depth: usize,
+    found: &mut Vec<(usize, &'a [Id])>,
+  ) {
+    let Some(item) = items.get(depth) else {
+      return;
+    };
+
+    for node_ref in self.find_item_refs(item) {
+      match node_ref {
+        MatcherTreeRef::Matches(ids) => found.push((depth, ids)),
+        MatcherTreeRef::Node(node) => node.walk_recursively(items, depth + 1, found),
+      }
+    }
+  }
+
+  fn find_item_refs(&self, item: &RollingItem) -> Vec<&MatcherTreeRef<Id>> {
+    let mut refs = Vec::new();
+
+    match item {
+      RollingItem::WordSeparator => refs.extend(self.word_separators.as_ref()),
+      RollingItem::Key(key) => refs.extend(
+        self
+          .keys
+          .iter()
+          .filter(|(k, _)| k == key)
+          .map(|(_, node_ref)| node_ref),
+      ),
+      RollingItem::Char(c) | RollingItem::CharInsensitive(c) => {
+        let is_insensitive = matches!(item, RollingItem::CharInsensitive(_));
+        let uni_char = UniCase::new(c.clone());
+        refs.extend(
+          self
+            .chars
+            .iter()
+            .filter(|(char, _)| {
+              char == c || (is_insensitive && UniCase::new(char.as_str()) == UniCase::new(c))
+            })
+            .map(|(_, node_ref)| node_ref),
+        );
+        refs.extend(
+          self
+            .chars_insensitive
+            .iter()
+            .filter(|(char, _)| *char == uni_char)
+            .map(|(_, node_ref)| node_ref),
+        );
+      }
+    }
+
+    refs
+  }
+}
+
 fn insert_items_recursively<Id>(id: Id, node: &mut MatcherTreeNode<Id>, items: &[RollingItem]) {
   if items.is_empty() {
     return;
@@ -353,4 +489,107 @@ mod tests {
       }
     );
   }
+
+  fn conflict(
+    kind: ConflictKind,
+    id: i32,
+    trigger: &str,
+    other_id: i32,
+    other_trigger: &str,
+  ) -> TriggerConflict<i32> {
+    TriggerConflict {
+      kind,
+      id,
+      trigger: trigger.to_string(),
+      other_id,
+      other_trigger: other_trigger.to_string(),
+    }
+  }
+
+  fn find_conflicts(matches: &[RollingMatch<i32>]) -> Vec<TriggerConflict<i32>> {
+    MatcherTreeNode::from_matches(matches).find_conflicts(matches)
+  }
+
+  #[test]
+  fn find_conflicts_no_conflicts() {
+    let conflicts = find_conflicts(&[
+      RollingMatch::from_string(1, "hi", &StringMatchOptions::default()),
+      RollingMatch::from_string(2, "hey", &StringMatchOptions::default()),
+      RollingMatch::from_string(1, "hi", &StringMatchOptions::default()), // Same match
+    ]);
+
+    assert_eq!(conflicts, vec![]);
+  }
+
+  #[test]
+  fn find_conflicts_duplicates() {
+    let conflicts = find_conflicts(&[
+      RollingMatch::from_string(1, "hi", &StringMatchOptions::default()),
+      RollingMatch::from_string(2, "hi", &StringMatchOptions::default()),
+      RollingMatch::from_string(
+        3,
+        "HI",
+        &StringMatchOptions {
+          case_insensitive: true,
+          ..Default::default()
+        },
+      ),
+    ]);
+
+    assert_eq!(
+      conflicts,
+      vec![
+        conflict(ConflictKind::Duplicate, 1, "hi", 2, "hi"),
+        conflict(ConflictKind::Duplicate, 1, "hi", 3, "hi"),
+        conflict(ConflictKind::Duplicate, 2, "hi", 3, "hi"),
+      ]
+    );
+  }
+
+  #[test]
+  fn find_conflicts_prefix_and_infix() {
+    let conflicts = find_conflicts(&[
+      RollingMatch::from_string(1, "my", &StringMatchOptions::default()),
+      RollingMatch::from_string(2, "myself", &StringMatchOptions::default()),
+      RollingMatch::from_string(3, "sel", &StringMatchOptions::default()),
+    ]);
+
+    assert_eq!(
+      conflicts,
+      vec![
+        conflict(ConflictKind::Prefix, 2, "myself", 1, "my"),
+        conflict(ConflictKind::Infix, 2, "myself", 3, "sel"),
+      ]
+    );
+  }
+
+  #[test]
+  fn find_conflicts_suffix() {
+    let conflicts = find_conflicts(&[
+      RollingMatch::from_string(1, "ab", &StringMatchOptions::default()),
+      RollingMatch::from_string(2, "b", &StringMatchOptions::default()),
+    ]);
+
+    assert_eq!(
+      conflicts,
+      vec![conflict(ConflictKind::Suffix, 2, "b", 1, "ab")]
+    );
+  }
+
+  #[test]
+  fn find_conflicts_word_separators() {
+    let conflicts = find_conflicts(&[
+      RollingMatch::from_string(
+        1,
+        "my",
+        &StringMatchOptions {
+          right_word: true,
+          ..Default::default()
+        },
+      ),
+      RollingMatch::from_string(2, "myself", &StringMatchOptions::default()),
+    ]);
+
+    assert_eq!(conflicts, vec![]);
+  }
 }
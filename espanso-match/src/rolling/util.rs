@@ -17,9 +17,11 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::fmt::Write;
+
 use crate::Event;
 
-use super::matcher::IsWordSeparator;
+use super::{matcher::IsWordSeparator, RollingItem};
 
 pub(crate) fn extract_string_from_events(
   events: &[(Event, IsWordSeparator)],
@@ -50,6 +52,22 @@ pub(crate) fn extract_string_from_events(
   (string, left_separator, right_separator)
 }
 
+pub(crate) fn extract_string_from_items(items: &[RollingItem]) -> String {
+  let mut string = String::new();
+
+  for item in items {
+    match item {
+      RollingItem::Char(c) | RollingItem::CharInsensitive(c) => string.push_str(c),
+      RollingItem::Key(key) => {
+        let _ = write!(string, "[{key:?}]");
+      }
+      RollingItem::WordSeparator => {}
+    }
+  }
+
+  string
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -220,4 +238,18 @@ mod tests {
       ("hello".to_string(), None, None)
     );
   }
+
+  #[test]
+  fn extract_string_from_items_mixed() {
+    assert_eq!(
+      extract_string_from_items(&[
+        RollingItem::WordSeparator,
+        RollingItem::Char("h".to_string()),
+        RollingItem::CharInsensitive("i".to_string()),
+        RollingItem::Key(Key::ArrowUp),
+        RollingItem::WordSeparator,
+      ]),
+      "hi[ArrowUp]".to_string()
+    );
+  }
 }
@@ -17,21 +17,82 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::{Extension, ExtensionOutput, ExtensionResult, Params};
+use anyhow::Result;
 use thiserror::Error;
 
+use super::choice::{Choice, ChoiceSelector, ChoiceSelectorResult};
+use crate::{Extension, ExtensionOutput, ExtensionResult, Number, Params, Value};
+
+const MAX_LABEL_LENGTH: usize = 80;
+
 pub trait ClipboardProvider {
   fn get_text(&self) -> Option<String>;
+
+  // Returns the previous clipboard contents, starting from the most recent one,
+  // or None if the clipboard history is disabled.
+  fn get_history(&self) -> Option<Vec<String>>;
 }
 
+/// Outputs the current clipboard content. The `index` param reads older
+/// entries of the clipboard history (0 being the current content), while
+/// `search: true` lets the user pick an entry with the search bar.
 pub struct ClipboardExtension<'a> {
   provider: &'a dyn ClipboardProvider,
+  selector: &'a dyn ChoiceSelector,
 }
 
 #[allow(clippy::new_without_default)]
 impl<'a> ClipboardExtension<'a> {
-  pub fn new(provider: &'a dyn ClipboardProvider) -> Self {
-    Self { provider }
+  pub fn new(provider: &'a dyn ClipboardProvider, selector: &'a dyn ChoiceSelector) -> Self {
+    Self { provider, selector }
+  }
+
+  // The history is polled periodically, so it might not contain the
+  // current clipboard content yet
+  fn get_entries(&self) -> Result<Vec<String>> {
+    let mut entries = self
+      .provider
+      .get_history()
+      .ok_or(ClipboardExtensionError::HistoryDisabled)?;
+    if let Some(current) = self.provider.get_text() {
+      if entries.first() != Some(&current) {
+        entries.insert(0, current);
+      }
+    }
+    Ok(entries)
+  }
+
+  fn get_entry(&self, index: i64) -> Result<String> {
+    let entries = self.get_entries()?;
+    usize::try_from(index)
+      .ok()
+      .and_then(|index| entries.into_iter().nth(index))
+      .ok_or_else(|| ClipboardExtensionError::MissingHistoryEntry(index).into())
+  }
+
+  fn select(&self) -> Result<Option<String>> {
+    let entries = self.get_entries()?;
+
+    let ids: Vec<String> = (0..entries.len()).map(|index| index.to_string()).collect();
+    let labels: Vec<String> = entries.iter().map(|entry| create_label(entry)).collect();
+    let choices: Vec<Choice> = ids
+      .iter()
+      .zip(labels.iter())
+      .map(|(id, label)| Choice { label, id })
+      .collect();
+
+    match self.selector.show(&choices) {
+      ChoiceSelectorResult::Success(id) => {
+        let entry = id
+          .parse::<usize>()
+          .ok()
+          .and_then(|index| entries.get(index))
+          .ok_or(ClipboardExtensionError::InvalidSelection(id))?;
+        Ok(Some(entry.clone()))
+      }
+      ChoiceSelectorResult::Aborted => Ok(None),
+      ChoiceSelectorResult::Error(err) => Err(err),
+    }
   }
 }
 
@@ -40,19 +101,64 @@ impl<'a> Extension for ClipboardExtension<'a> {
     "clipboard"
   }
 
-  fn calculate(&self, _: &crate::Context, _: &crate::Scope, _: &Params) -> crate::ExtensionResult {
-    if let Some(clipboard) = self.provider.get_text() {
-      ExtensionResult::Success(ExtensionOutput::Single(clipboard))
-    } else {
-      ExtensionResult::Error(ClipboardExtensionError::MissingClipboard.into())
+  fn calculate(
+    &self,
+    _: &crate::Context,
+    _: &crate::Scope,
+    params: &Params,
+  ) -> crate::ExtensionResult {
+    if let Some(Value::Bool(true)) = params.get("search") {
+      return match self.select() {
+        Ok(Some(entry)) => ExtensionResult::Success(ExtensionOutput::Single(entry)),
+        Ok(None) => ExtensionResult::Aborted,
+        Err(err) => ExtensionResult::Error(err),
+      };
+    }
+
+    match params.get("index") {
+      None | Some(Value::Number(Number::Integer(0))) => {
+        if let Some(clipboard) = self.provider.get_text() {
+          ExtensionResult::Success(ExtensionOutput::Single(clipboard))
+        } else {
+          ExtensionResult::Error(ClipboardExtensionError::MissingClipboard.into())
+        }
+      }
+      Some(Value::Number(Number::Integer(index))) if *index > 0 => match self.get_entry(*index) {
+        Ok(entry) => ExtensionResult::Success(ExtensionOutput::Single(entry)),
+        Err(err) => ExtensionResult::Error(err),
+      },
+      Some(_) => ExtensionResult::Error(ClipboardExtensionError::InvalidIndex.into()),
     }
   }
 }
 
+// Multi-line entries are shown on a single line in the search bar
+fn create_label(entry: &str) -> String {
+  let line = entry.split_whitespace().collect::<Vec<_>>().join(" ");
+  if line.chars().count() > MAX_LABEL_LENGTH {
+    let truncated: String = line.chars().take(MAX_LABEL_LENGTH).collect();
+    format!("{truncated}...")
+  } else {
+    line
+  }
+}
+
 #[derive(Error, Debug)]
 pub enum ClipboardExtensionError {
   #[error("clipboard provider returned error")]
   MissingClipboard,
+
+  #[error("clipboard history is disabled, enable it with the `clipboard_history` option")]
+  HistoryDisabled,
+
+  #[error("clipboard history doesn't contain an entry with index {0}")]
+  MissingHistoryEntry(i64),
+
+  #[error("invalid 'index' parameter, expected a non-negative integer")]
+  InvalidIndex,
+
+  #[error("invalid clipboard history selection: {0}")]
+  InvalidSelection(String),
 }
 
 #[cfg(test)]
@@ -63,6 +169,7 @@ mod tests {
 
   struct MockClipboardProvider {
     return_none: bool,
+    history: Option<Vec<String>>,
   }
 
   impl super::ClipboardProvider for MockClipboardProvider {
@@ -73,38 +180,156 @@ mod tests {
         Some("test".to_string())
       }
     }
+
+    fn get_history(&self) -> Option<Vec<String>> {
+      self.history.clone()
+    }
+  }
+
+  struct MockChoiceSelector {
+    selected_label: Option<&'static str>,
+  }
+
+  impl ChoiceSelector for MockChoiceSelector {
+    fn show(&self, choices: &[Choice]) -> ChoiceSelectorResult {
+      let Some(selected_label) = self.selected_label else {
+        return ChoiceSelectorResult::Aborted;
+      };
+      let choice = choices
+        .iter()
+        .find(|choice| choice.label == selected_label)
+        .unwrap();
+      ChoiceSelectorResult::Success(choice.id.to_string())
+    }
+  }
+
+  fn history(entries: &[&str]) -> Option<Vec<String>> {
+    Some(entries.iter().map(ToString::to_string).collect())
+  }
+
+  fn calculate(provider: &MockClipboardProvider, params: Vec<(&str, Value)>) -> ExtensionResult {
+    let selector = MockChoiceSelector {
+      selected_label: Some("second entry with multiple lines"),
+    };
+    let extension = ClipboardExtension::new(provider, &selector);
+    let params = params
+      .into_iter()
+      .map(|(key, value)| (key.to_string(), value))
+      .collect::<Params>();
+    extension.calculate(&crate::Context::default(), &HashMap::default(), &params)
   }
 
   #[test]
   fn clipboard_works_correctly() {
-    let provider = MockClipboardProvider { return_none: false };
-    let extension = ClipboardExtension::new(&provider);
+    let provider = MockClipboardProvider {
+      return_none: false,
+      history: None,
+    };
 
     assert_eq!(
-      extension
-        .calculate(
-          &crate::Context::default(),
-          &HashMap::default(),
-          &Params::new()
-        )
-        .into_success()
-        .unwrap(),
+      calculate(&provider, vec![]).into_success().unwrap(),
       ExtensionOutput::Single("test".to_string())
     );
   }
 
   #[test]
   fn none_clipboard_produces_error() {
-    let provider = MockClipboardProvider { return_none: true };
-    let extension = ClipboardExtension::new(&provider);
+    let provider = MockClipboardProvider {
+      return_none: true,
+      history: None,
+    };
 
     assert!(matches!(
-      extension.calculate(
-        &crate::Context::default(),
-        &HashMap::default(),
-        &Params::new()
-      ),
+      calculate(&provider, vec![]),
       ExtensionResult::Error(_)
     ));
   }
+
+  #[test]
+  fn history_index() {
+    let index = |index| ("index", Value::Number(Number::Integer(index)));
+
+    // The current clipboard content is not in the history yet
+    let provider = MockClipboardProvider {
+      return_none: false,
+      history: history(&["first", "second"]),
+    };
+    assert_eq!(
+      calculate(&provider, vec![index(0)]).into_success().unwrap(),
+      ExtensionOutput::Single("test".to_string())
+    );
+    assert_eq!(
+      calculate(&provider, vec![index(2)]).into_success().unwrap(),
+      ExtensionOutput::Single("second".to_string())
+    );
+    assert!(calculate(&provider, vec![index(3)]).into_error().is_ok());
+    assert!(calculate(&provider, vec![index(-1)]).into_error().is_ok());
+
+    // The current clipboard content is already the most recent entry
+    let provider = MockClipboardProvider {
+      return_none: false,
+      history: history(&["test", "first"]),
+    };
+    assert_eq!(
+      calculate(&provider, vec![index(1)]).into_success().unwrap(),
+      ExtensionOutput::Single("first".to_string())
+    );
+  }
+
+  #[test]
+  fn history_disabled() {
+    let provider = MockClipboardProvider {
+      return_none: false,
+      history: None,
+    };
+    assert!(calculate(
+      &provider,
+      vec![("index", Value::Number(Number::Integer(1)))]
+    )
+    .into_error()
+    .is_ok());
+    assert!(calculate(&provider, vec![("search", Value::Bool(true))])
+      .into_error()
+      .is_ok());
+  }
+
+  #[test]
+  fn history_search() {
+    let provider = MockClipboardProvider {
+      return_none: false,
+      history: history(&["first", "second entry\nwith   multiple lines"]),
+    };
+    assert_eq!(
+      calculate(&provider, vec![("search", Value::Bool(true))])
+        .into_success()
+        .unwrap(),
+      ExtensionOutput::Single("second entry\nwith   multiple lines".to_string())
+    );
+  }
+
+  #[test]
+  fn history_search_aborted() {
+    let provider = MockClipboardProvider {
+      return_none: false,
+      history: history(&["first"]),
+    };
+    let selector = MockChoiceSelector {
+      selected_label: None,
+    };
+    let extension = ClipboardExtension::new(&provider, &selector);
+    let params = vec![("search".to_string(), Value::Bool(true))]
+      .into_iter()
+      .collect::<Params>();
+    assert!(matches!(
+      extension.calculate(&crate::Context::default(), &HashMap::default(), &params),
+      ExtensionResult::Aborted
+    ));
+  }
+
+  #[test]
+  fn labels_are_truncated() {
+    assert_eq!(create_label("  hello\n\tworld "), "hello world");
+    let label = create_label(&"a".repeat(100));
+    assert_eq!(label, format!("{}...", "a".repeat(MAX_LABEL_LENGTH)));
+  }
 }
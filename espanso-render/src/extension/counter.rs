@@ -0,0 +1,305 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use thiserror::Error;
+
+use crate::{Extension, ExtensionOutput, ExtensionResult, Number, Params, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CounterState {
+  pub value: i64,
+
+  // The period the value belongs to, such as "2021-03-04" for daily counters
+  pub period: Option<String>,
+}
+
+pub trait CounterStore {
+  /// Atomically replace the state of the given counter with the one returned
+  /// by `update`, which receives the current state (if any).
+  fn update(
+    &self,
+    key: &str,
+    update: &mut dyn FnMut(Option<CounterState>) -> CounterState,
+  ) -> Result<CounterState>;
+}
+
+pub struct CounterExtension<'a> {
+  fixed_date: Option<DateTime<Local>>,
+  store: &'a dyn CounterStore,
+}
+
+#[allow(clippy::new_without_default)]
+impl<'a> CounterExtension<'a> {
+  pub fn new(store: &'a dyn CounterStore) -> Self {
+    Self {
+      fixed_date: None,
+      store,
+    }
+  }
+
+  fn get_date(&self) -> DateTime<Local> {
+    if let Some(fixed_date) = self.fixed_date {
+      fixed_date
+    } else {
+      Local::now()
+    }
+  }
+
+  fn next(&self, params: &Params) -> Result<String> {
+    let Some(Value::String(key)) = params.get("key") else {
+      return Err(CounterExtensionError::MissingKeyParameter.into());
+    };
+
+    let start = get_integer_param(params, "start", 1)?;
+    let step = get_integer_param(params, "step", 1)?;
+    let padding = get_integer_param(params, "padding", 0)?;
+    let padding = usize::try_from(padding)
+      .map_err(|_| CounterExtensionError::InvalidParameter("padding".to_string()))?;
+
+    let period = match params.get("reset") {
+      None => None,
+      Some(Value::String(reset)) => match reset.as_str() {
+        "never" => None,
+        "daily" => Some(self.get_date().format("%Y-%m-%d").to_string()),
+        "monthly" => Some(self.get_date().format("%Y-%m").to_string()),
+        _ => return Err(CounterExtensionError::InvalidParameter("reset".to_string()).into()),
+      },
+      Some(_) => return Err(CounterExtensionError::InvalidParameter("reset".to_string()).into()),
+    };
+
+    let mut overflow = false;
+    let state = self.store.update(key, &mut |current| match current {
+      Some(current) if current.period == period => {
+        let value = current.value.checked_add(step).unwrap_or_else(|| {
+          overflow = true;
+          current.value
+        });
+        CounterState {
+          value,
+          period: current.period,
+        }
+      }
+      // The counter starts over when a new period begins
+      _ => CounterState {
+        value: start,
+        period: period.clone(),
+      },
+    })?;
+
+    if overflow {
+      return Err(CounterExtensionError::Overflow(key.clone()).into());
+    }
+
+    Ok(format!("{:0padding$}", state.value))
+  }
+}
+
+fn get_integer_param(params: &Params, name: &str, default: i64) -> Result<i64> {
+  match params.get(name) {
+    Some(Value::Number(Number::Integer(value))) => Ok(*value),
+    None => Ok(default),
+    Some(_) => Err(CounterExtensionError::InvalidParameter(name.to_string()).into()),
+  }
+}
+
+impl<'a> Extension for CounterExtension<'a> {
+  fn name(&self) -> &str {
+    "counter"
+  }
+
+  fn calculate(
+    &self,
+    _: &crate::Context,
+    _: &crate::Scope,
+    params: &Params,
+  ) -> crate::ExtensionResult {
+    match self.next(params) {
+      Ok(value) => ExtensionResult::Success(ExtensionOutput::Single(value)),
+      Err(err) => ExtensionResult::Error(err),
+    }
+  }
+}
+
+#[derive(Error, Debug)]
+pub enum CounterExtensionError {
+  #[error("missing 'key' parameter")]
+  MissingKeyParameter,
+
+  #[error("invalid '{0}' parameter")]
+  InvalidParameter(String),
+
+  #[error("counter '{0}' overflowed")]
+  Overflow(String),
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{cell::RefCell, collections::HashMap};
+
+  use chrono::TimeZone;
+
+  use super::*;
+
+  #[derive(Default)]
+  struct MockCounterStore {
+    counters: RefCell<HashMap<String, CounterState>>,
+  }
+
+  impl CounterStore for MockCounterStore {
+    fn update(
+      &self,
+      key: &str,
+      update: &mut dyn FnMut(Option<CounterState>) -> CounterState,
+    ) -> Result<CounterState> {
+      let mut counters = self.counters.borrow_mut();
+      let state = update(counters.get(key).cloned());
+      counters.insert(key.to_string(), state.clone());
+      Ok(state)
+    }
+  }
+
+  fn params(values: Vec<(&str, Value)>) -> Params {
+    values
+      .into_iter()
+      .map(|(key, value)| (key.to_string(), value))
+      .collect()
+  }
+
+  fn next(extension: &CounterExtension, params: &Params) -> String {
+    match extension.calculate(&crate::Context::default(), &HashMap::default(), params) {
+      ExtensionResult::Success(ExtensionOutput::Single(value)) => value,
+      result => panic!("unexpected result: {result:?}"),
+    }
+  }
+
+  #[test]
+  fn counter_increments() {
+    let store = MockCounterStore::default();
+    let extension = CounterExtension::new(&store);
+
+    let params = params(vec![("key", Value::String("ticket".to_string()))]);
+    assert_eq!(next(&extension, &params), "1");
+    assert_eq!(next(&extension, &params), "2");
+    assert_eq!(next(&extension, &params), "3");
+  }
+
+  #[test]
+  fn counter_start_step_and_padding() {
+    let store = MockCounterStore::default();
+    let extension = CounterExtension::new(&store);
+
+    let params = params(vec![
+      ("key", Value::String("invoice".to_string())),
+      ("start", Value::Number(Number::Integer(100))),
+      ("step", Value::Number(Number::Integer(10))),
+      ("padding", Value::Number(Number::Integer(5))),
+    ]);
+    assert_eq!(next(&extension, &params), "00100");
+    assert_eq!(next(&extension, &params), "00110");
+  }
+
+  #[test]
+  fn counters_are_independent() {
+    let store = MockCounterStore::default();
+    let extension = CounterExtension::new(&store);
+
+    let first = params(vec![("key", Value::String("first".to_string()))]);
+    let second = params(vec![("key", Value::String("second".to_string()))]);
+    assert_eq!(next(&extension, &first), "1");
+    assert_eq!(next(&extension, &first), "2");
+    assert_eq!(next(&extension, &second), "1");
+  }
+
+  #[test]
+  fn daily_counter_resets() {
+    let store = MockCounterStore::default();
+    let mut extension = CounterExtension::new(&store);
+    let params = params(vec![
+      ("key", Value::String("daily".to_string())),
+      ("reset", Value::String("daily".to_string())),
+    ]);
+
+    extension.fixed_date = Some(Local.with_ymd_and_hms(2021, 3, 4, 10, 0, 0).unwrap());
+    assert_eq!(next(&extension, &params), "1");
+    assert_eq!(next(&extension, &params), "2");
+
+    extension.fixed_date = Some(Local.with_ymd_and_hms(2021, 3, 5, 10, 0, 0).unwrap());
+    assert_eq!(next(&extension, &params), "1");
+  }
+
+  #[test]
+  fn monthly_counter_resets() {
+    let store = MockCounterStore::default();
+    let mut extension = CounterExtension::new(&store);
+    let params = params(vec![
+      ("key", Value::String("monthly".to_string())),
+      ("reset", Value::String("monthly".to_string())),
+    ]);
+
+    extension.fixed_date = Some(Local.with_ymd_and_hms(2021, 3, 4, 10, 0, 0).unwrap());
+    assert_eq!(next(&extension, &params), "1");
+
+    extension.fixed_date = Some(Local.with_ymd_and_hms(2021, 3, 31, 10, 0, 0).unwrap());
+    assert_eq!(next(&extension, &params), "2");
+
+    extension.fixed_date = Some(Local.with_ymd_and_hms(2021, 4, 1, 10, 0, 0).unwrap());
+    assert_eq!(next(&extension, &params), "1");
+  }
+
+  #[test]
+  fn counter_overflow() {
+    let store = MockCounterStore::default();
+    let extension = CounterExtension::new(&store);
+    let params = params(vec![
+      ("key", Value::String("overflow".to_string())),
+      ("start", Value::Number(Number::Integer(i64::MAX))),
+    ]);
+
+    assert_eq!(next(&extension, &params), i64::MAX.to_string());
+    assert!(matches!(
+      extension.calculate(&crate::Context::default(), &HashMap::default(), &params),
+      ExtensionResult::Error(_)
+    ));
+  }
+
+  #[test]
+  fn invalid_parameters() {
+    let store = MockCounterStore::default();
+    let extension = CounterExtension::new(&store);
+
+    for params in [
+      Params::new(),
+      params(vec![
+        ("key", Value::String("test".to_string())),
+        ("reset", Value::String("weekly".to_string())),
+      ]),
+      params(vec![
+        ("key", Value::String("test".to_string())),
+        ("padding", Value::Number(Number::Integer(-1))),
+      ]),
+    ] {
+      assert!(matches!(
+        extension.calculate(&crate::Context::default(), &HashMap::default(), &params),
+        ExtensionResult::Error(_)
+      ));
+    }
+  }
+}
@@ -17,10 +17,27 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use chrono::{DateTime, Duration, Local, Locale};
+use std::{borrow::Cow, fmt::Write};
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, Locale, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use thiserror::Error;
 
 use crate::{Extension, ExtensionOutput, ExtensionResult, Number, Params, Value};
 
+mod relative;
+
+// Formats used to parse the `from` parameter when no `from_format` is given,
+// in addition to RFC 3339 and RFC 2822
+const FROM_DATE_TIME_FORMATS: &[&str] = &[
+  "%Y-%m-%dT%H:%M:%S",
+  "%Y-%m-%d %H:%M:%S",
+  "%Y-%m-%dT%H:%M",
+  "%Y-%m-%d %H:%M",
+];
+const FROM_DATE_FORMAT: &str = "%Y-%m-%d";
+
 pub trait LocaleProvider {
   fn get_system_locale(&self) -> String;
 }
@@ -51,28 +68,19 @@ impl<'a> Extension for DateExtension<'a> {
     _: &crate::Scope,
     params: &Params,
   ) -> crate::ExtensionResult {
-    let mut now = self.get_date();
-
-    // Compute the given offset
-    let offset = params.get("offset");
-    if let Some(Value::Number(Number::Integer(offset))) = offset {
-      let offset = Duration::seconds(*offset);
-      now = now + offset;
-    }
-
-    let format = params.get("format");
-    let locale = params
-      .get("locale")
-      .and_then(|val| val.as_string())
-      .map_or_else(|| self.locale_provider.get_system_locale(), String::from);
-
-    let date = if let Some(Value::String(format)) = format {
-      DateExtension::format_date_with_locale_string(now, format, &locale)
-    } else {
-      now.to_rfc2822()
+    let result = match params.get("timezone") {
+      Some(Value::String(timezone)) => match timezone.parse::<Tz>() {
+        Ok(timezone) => self.calculate_in_timezone(&timezone, params),
+        Err(_) => Err(DateExtensionError::UnknownTimezone(timezone.clone()).into()),
+      },
+      Some(_) => Err(DateExtensionError::InvalidParameter("timezone").into()),
+      None => self.calculate_in_timezone(&Local, params),
     };
 
-    ExtensionResult::Success(ExtensionOutput::Single(date))
+    match result {
+      Ok(date) => ExtensionResult::Success(ExtensionOutput::Single(date)),
+      Err(err) => ExtensionResult::Error(err),
+    }
   }
 }
 
@@ -85,20 +93,162 @@ impl<'a> DateExtension<'a> {
     }
   }
 
-  fn format_date_with_locale(date: DateTime<Local>, format: &str, locale: Locale) -> String {
-    date.format_localized(format, locale).to_string()
+  fn calculate_in_timezone<T: TimeZone>(&self, timezone: &T, params: &Params) -> Result<String>
+  where
+    T::Offset: std::fmt::Display,
+  {
+    let mut date = match params.get("from") {
+      Some(Value::String(from)) => {
+        let from_format = params.get("from_format").and_then(|val| val.as_string());
+        parse_date(from, from_format.map(String::as_str), timezone)?
+      }
+      Some(_) => return Err(DateExtensionError::InvalidParameter("from").into()),
+      None => self.get_date().with_timezone(timezone),
+    };
+
+    // Compute the given offset, either in seconds or as a relative expression
+    match params.get("offset") {
+      Some(Value::Number(Number::Integer(offset))) => {
+        date = Duration::try_seconds(*offset)
+          .and_then(|offset| date.checked_add_signed(offset))
+          .ok_or(DateExtensionError::OutOfRange)?;
+      }
+      Some(Value::String(expression)) => date = relative::apply(&date, expression)?,
+      Some(_) => return Err(DateExtensionError::InvalidParameter("offset").into()),
+      None => {}
+    }
+
+    let format = params.get("format");
+    let locale = params
+      .get("locale")
+      .and_then(|val| val.as_string())
+      .map_or_else(|| self.locale_provider.get_system_locale(), String::from);
+
+    if let Some(Value::String(format)) = format {
+      DateExtension::format_date_with_locale_string(&date, format, &locale)
+    } else {
+      Ok(date.to_rfc2822())
+    }
+  }
+
+  fn format_date_with_locale<T: TimeZone>(
+    date: &DateTime<T>,
+    format: &str,
+    locale: Locale,
+  ) -> Result<String>
+  where
+    T::Offset: std::fmt::Display,
+  {
+    let expanded_format = expand_quarter(format, date.month());
+    let mut output = String::new();
+    write!(
+      output,
+      "{}",
+      date.format_localized(&expanded_format, locale)
+    )
+    .map_err(|_| DateExtensionError::InvalidFormat(format.to_string()))?;
+    Ok(output)
   }
 
-  fn format_date_with_locale_string(
-    date: DateTime<Local>,
+  fn format_date_with_locale_string<T: TimeZone>(
+    date: &DateTime<T>,
     format: &str,
     locale_str: &str,
-  ) -> String {
+  ) -> Result<String>
+  where
+    T::Offset: std::fmt::Display,
+  {
     let locale = convert_locale_string_to_locale(locale_str).unwrap_or(Locale::en_US);
     Self::format_date_with_locale(date, format, locale)
   }
 }
 
+// chrono doesn't provide a specifier for the quarter, so we expand
+// `%Q` before formatting the date
+fn expand_quarter(format: &str, month: u32) -> Cow<'_, str> {
+  if !format.contains("%Q") {
+    return Cow::Borrowed(format);
+  }
+
+  let quarter = ((month - 1) / 3 + 1).to_string();
+  let mut output = String::with_capacity(format.len());
+  let mut chars = format.chars();
+  while let Some(c) = chars.next() {
+    if c != '%' {
+      output.push(c);
+      continue;
+    }
+
+    match chars.next() {
+      Some('Q') => output.push_str(&quarter),
+      Some(next) => {
+        output.push('%');
+        output.push(next);
+      }
+      None => output.push('%'),
+    }
+  }
+  Cow::Owned(output)
+}
+
+fn parse_date<T: TimeZone>(value: &str, format: Option<&str>, timezone: &T) -> Result<DateTime<T>> {
+  let value = value.trim();
+  let date = if let Some(format) = format {
+    DateTime::parse_from_str(value, format)
+      .ok()
+      .map(|date| date.with_timezone(timezone))
+      .or_else(|| {
+        let date = NaiveDateTime::parse_from_str(value, format)
+          .ok()
+          .or_else(|| {
+            NaiveDate::parse_from_str(value, format)
+              .ok()?
+              .and_hms_opt(0, 0, 0)
+          })?;
+        relative::localize(timezone, date)
+      })
+  } else {
+    DateTime::parse_from_rfc3339(value)
+      .or_else(|_| DateTime::parse_from_rfc2822(value))
+      .ok()
+      .map(|date| date.with_timezone(timezone))
+      .or_else(|| {
+        let date = FROM_DATE_TIME_FORMATS
+          .iter()
+          .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+          .or_else(|| {
+            NaiveDate::parse_from_str(value, FROM_DATE_FORMAT)
+              .ok()?
+              .and_hms_opt(0, 0, 0)
+          })?;
+        relative::localize(timezone, date)
+      })
+  };
+
+  date.ok_or_else(|| DateExtensionError::InvalidDate(value.to_string()).into())
+}
+
+#[derive(Error, Debug)]
+pub enum DateExtensionError {
+  #[error("invalid '{0}' parameter")]
+  InvalidParameter(&'static str),
+
+  #[error("invalid date expression: `{0}`")]
+  InvalidExpression(String),
+
+  #[error("unknown timezone: `{0}`")]
+  UnknownTimezone(String),
+
+  #[error("unable to parse date: `{0}`")]
+  InvalidDate(String),
+
+  #[error("invalid date format: `{0}`")]
+  InvalidFormat(String),
+
+  #[error("date is out of range")]
+  OutOfRange,
+}
+
 fn convert_locale_string_to_locale(locale_str: &str) -> Option<Locale> {
   match locale_str {
     "aa-DJ" => Some(Locale::aa_DJ),
@@ -542,4 +692,128 @@ mod tests {
       ExtensionOutput::Single("martedì".to_string())
     );
   }
+
+  fn calculate(params: Vec<(&str, Value)>) -> ExtensionResult {
+    let locale_provider = MockLocaleProvider::new();
+    let mut extension = DateExtension::new(&locale_provider);
+    extension.fixed_date = Some(Local.with_ymd_and_hms(2014, 7, 8, 9, 10, 11).unwrap());
+
+    let params = params
+      .into_iter()
+      .map(|(key, value)| (key.to_string(), value))
+      .collect::<Params>();
+    extension.calculate(&crate::Context::default(), &HashMap::default(), &params)
+  }
+
+  fn string(value: &str) -> Value {
+    Value::String(value.to_string())
+  }
+
+  fn single(value: &str) -> ExtensionOutput {
+    ExtensionOutput::Single(value.to_string())
+  }
+
+  #[test]
+  fn relative_offset() {
+    let format = ("format", string("%Y-%m-%d %H:%M"));
+    assert_eq!(
+      calculate(vec![format.clone(), ("offset", string("+1d 2h"))])
+        .into_success()
+        .unwrap(),
+      single("2014-07-09 11:10")
+    );
+    assert_eq!(
+      calculate(vec![format.clone(), ("offset", string("Next Monday"))])
+        .into_success()
+        .unwrap(),
+      single("2014-07-14 09:10")
+    );
+    assert_eq!(
+      calculate(vec![format.clone(), ("offset", string("+2 business days"))])
+        .into_success()
+        .unwrap(),
+      single("2014-07-10 09:10")
+    );
+    assert_eq!(
+      calculate(vec![format, ("offset", string("end of month"))])
+        .into_success()
+        .unwrap(),
+      single("2014-07-31 23:59")
+    );
+    assert!(calculate(vec![("offset", string("next fortnight"))])
+      .into_error()
+      .is_ok());
+  }
+
+  #[test]
+  fn quarter_and_iso_week() {
+    assert_eq!(
+      calculate(vec![("format", string("Q%Q %G-W%V %%Q"))])
+        .into_success()
+        .unwrap(),
+      single("Q3 2014-W28 %Q")
+    );
+  }
+
+  #[test]
+  fn invalid_format() {
+    assert!(calculate(vec![("format", string("%Y %!"))])
+      .into_error()
+      .is_ok());
+  }
+
+  #[test]
+  fn timezone() {
+    assert_eq!(
+      calculate(vec![
+        ("from", string("2014-07-08T09:10:11+00:00")),
+        ("timezone", string("Asia/Tokyo")),
+        ("format", string("%Y-%m-%d %H:%M %Z")),
+      ])
+      .into_success()
+      .unwrap(),
+      single("2014-07-08 18:10 JST")
+    );
+    assert_eq!(
+      calculate(vec![
+        ("from", string("2014-07-08 23:30")),
+        ("timezone", string("Europe/Rome")),
+        ("offset", string("end of day")),
+        ("format", string("%H:%M %z")),
+      ])
+      .into_success()
+      .unwrap(),
+      single("23:59 +0200")
+    );
+    assert!(calculate(vec![("timezone", string("Mars/Olympus"))])
+      .into_error()
+      .is_ok());
+  }
+
+  #[test]
+  fn from_date() {
+    assert_eq!(
+      calculate(vec![
+        ("from", string("2021-03-04")),
+        ("format", string("%d/%m/%Y"))
+      ])
+      .into_success()
+      .unwrap(),
+      single("04/03/2021")
+    );
+    assert_eq!(
+      calculate(vec![
+        ("from", string("25/12/2020")),
+        ("from_format", string("%d/%m/%Y")),
+        ("offset", string("+1 business day")),
+        ("format", string("%A %d"))
+      ])
+      .into_success()
+      .unwrap(),
+      single("Monday 28")
+    );
+    assert!(calculate(vec![("from", string("not a date"))])
+      .into_error()
+      .is_ok());
+  }
 }
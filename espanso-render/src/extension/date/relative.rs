@@ -0,0 +1,426 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Evaluation of the relative date expressions accepted by the `offset`
+//! parameter, such as "next monday", "+2 business days" or "end of month".
+//! Multiple expressions can be chained with commas, and they are applied
+//! from left to right (e.g. "next month, start of month").
+
+use anyhow::Result;
+use chrono::{
+  DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, TimeZone, Timelike, Weekday,
+};
+
+use super::DateExtensionError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+  Second,
+  Minute,
+  Hour,
+  Day,
+  BusinessDay,
+  Week,
+  Month,
+  Quarter,
+  Year,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+  Number(i64),
+  Word(&'a str),
+}
+
+pub(super) fn apply<Tz: TimeZone>(date: &DateTime<Tz>, expression: &str) -> Result<DateTime<Tz>> {
+  let expression = expression.to_lowercase();
+  let mut naive = date.naive_local();
+  for clause in expression.split(',') {
+    naive = apply_clause(naive, clause.trim())
+      .ok_or_else(|| DateExtensionError::InvalidExpression(clause.trim().to_string()))?;
+  }
+  localize(&date.timezone(), naive).ok_or_else(|| DateExtensionError::OutOfRange.into())
+}
+
+/// Converts a local date into the given timezone. Local times skipped by a
+/// DST transition are moved forward by one hour.
+pub(super) fn localize<Tz: TimeZone>(tz: &Tz, date: NaiveDateTime) -> Option<DateTime<Tz>> {
+  tz.from_local_datetime(&date).earliest().or_else(|| {
+    tz.from_local_datetime(&date.checked_add_signed(Duration::hours(1))?)
+      .earliest()
+  })
+}
+
+fn apply_clause(date: NaiveDateTime, clause: &str) -> Option<NaiveDateTime> {
+  let tokens = tokenize(clause)?;
+  match tokens.as_slice() {
+    [Token::Word("now" | "today")] => Some(date),
+    [Token::Word("tomorrow")] => add(date, 1, Unit::Day),
+    [Token::Word("yesterday")] => add(date, -1, Unit::Day),
+    [Token::Word("start" | "beginning"), Token::Word("of"), Token::Word(unit)] => {
+      start_of(date, parse_unit(unit)?)
+    }
+    [Token::Word("end"), Token::Word("of"), Token::Word(unit)] => end_of(date, parse_unit(unit)?),
+    [Token::Word(anchor @ ("next" | "last" | "this")), Token::Word(target)] => {
+      if let Some(weekday) = parse_weekday(target) {
+        move_to_weekday(date, anchor, weekday)
+      } else {
+        let amount = match *anchor {
+          "next" => 1,
+          "last" => -1,
+          _ => 0,
+        };
+        add(date, amount, parse_unit(target)?)
+      }
+    }
+    _ => apply_amounts(date, &tokens),
+  }
+}
+
+// Handles expressions such as "+2 days", "1h 30m", "in 3 weeks" and "2 business days ago"
+fn apply_amounts(mut date: NaiveDateTime, tokens: &[Token]) -> Option<NaiveDateTime> {
+  let (tokens, direction) = match tokens {
+    [Token::Word("in"), rest @ ..] => (rest, 1),
+    [rest @ .., Token::Word("ago")] => (rest, -1),
+    _ => (tokens, 1),
+  };
+  if tokens.is_empty() {
+    return None;
+  }
+
+  let mut tokens = tokens.iter();
+  while let Some(token) = tokens.next() {
+    let Token::Number(amount) = token else {
+      return None;
+    };
+    let unit = match tokens.next()? {
+      Token::Word("business" | "working") => match tokens.next()? {
+        Token::Word("day" | "days") => Unit::BusinessDay,
+        _ => return None,
+      },
+      Token::Word(unit) => parse_unit(unit)?,
+      Token::Number(_) => return None,
+    };
+    date = add(date, amount.checked_mul(direction)?, unit)?;
+  }
+  Some(date)
+}
+
+fn tokenize(clause: &str) -> Option<Vec<Token<'_>>> {
+  let mut tokens = Vec::new();
+  let mut chars = clause.char_indices().peekable();
+  while let Some((start, c)) = chars.next() {
+    if c.is_whitespace() {
+      continue;
+    }
+
+    let is_number = c.is_ascii_digit() || c == '+' || c == '-';
+    if !is_number && !c.is_alphabetic() {
+      return None;
+    }
+
+    let mut end = start + c.len_utf8();
+    while let Some(&(index, next)) = chars.peek() {
+      let same_kind = if is_number {
+        next.is_ascii_digit()
+      } else {
+        next.is_alphabetic()
+      };
+      if !same_kind {
+        break;
+      }
+      end = index + next.len_utf8();
+      chars.next();
+    }
+
+    let text = &clause[start..end];
+    if is_number {
+      tokens.push(Token::Number(text.parse().ok()?));
+    } else {
+      tokens.push(Token::Word(text));
+    }
+  }
+  Some(tokens)
+}
+
+fn parse_unit(unit: &str) -> Option<Unit> {
+  match unit {
+    "s" | "sec" | "secs" | "second" | "seconds" => Some(Unit::Second),
+    "m" | "min" | "mins" | "minute" | "minutes" => Some(Unit::Minute),
+    "h" | "hr" | "hrs" | "hour" | "hours" => Some(Unit::Hour),
+    "d" | "day" | "days" => Some(Unit::Day),
+    "bd" | "workday" | "workdays" => Some(Unit::BusinessDay),
+    "w" | "wk" | "week" | "weeks" => Some(Unit::Week),
+    "mo" | "month" | "months" => Some(Unit::Month),
+    "q" | "quarter" | "quarters" => Some(Unit::Quarter),
+    "y" | "yr" | "year" | "years" => Some(Unit::Year),
+    _ => None,
+  }
+}
+
+fn parse_weekday(weekday: &str) -> Option<Weekday> {
+  match weekday {
+    "monday" | "mon" => Some(Weekday::Mon),
+    "tuesday" | "tue" => Some(Weekday::Tue),
+    "wednesday" | "wed" => Some(Weekday::Wed),
+    "thursday" | "thu" => Some(Weekday::Thu),
+    "friday" | "fri" => Some(Weekday::Fri),
+    "saturday" | "sat" => Some(Weekday::Sat),
+    "sunday" | "sun" => Some(Weekday::Sun),
+    _ => None,
+  }
+}
+
+fn move_to_weekday(date: NaiveDateTime, anchor: &str, weekday: Weekday) -> Option<NaiveDateTime> {
+  let current = i64::from(date.weekday().num_days_from_monday());
+  let target = i64::from(weekday.num_days_from_monday());
+  let days = match anchor {
+    // "next" and "last" never refer to the current day
+    "next" => (target - current + 6).rem_euclid(7) + 1,
+    "last" => -((current - target + 6).rem_euclid(7) + 1),
+    // "this" refers to the given day in the current (ISO) week
+    _ => target - current,
+  };
+  add(date, days, Unit::Day)
+}
+
+fn add(date: NaiveDateTime, amount: i64, unit: Unit) -> Option<NaiveDateTime> {
+  match unit {
+    Unit::Second => date.checked_add_signed(Duration::try_seconds(amount)?),
+    Unit::Minute => date.checked_add_signed(Duration::try_minutes(amount)?),
+    Unit::Hour => date.checked_add_signed(Duration::try_hours(amount)?),
+    Unit::Day => date.checked_add_signed(Duration::try_days(amount)?),
+    Unit::Week => date.checked_add_signed(Duration::try_weeks(amount)?),
+    Unit::BusinessDay => add_business_days(date, amount),
+    Unit::Month => add_months(date, amount),
+    Unit::Quarter => add_months(date, amount.checked_mul(3)?),
+    Unit::Year => add_months(date, amount.checked_mul(12)?),
+  }
+}
+
+// Months are added in the calendar sense, clamping to the last day of the month when needed
+fn add_months(date: NaiveDateTime, amount: i64) -> Option<NaiveDateTime> {
+  let months = Months::new(u32::try_from(amount.unsigned_abs()).ok()?);
+  if amount >= 0 {
+    date.checked_add_months(months)
+  } else {
+    date.checked_sub_months(months)
+  }
+}
+
+fn add_business_days(date: NaiveDateTime, amount: i64) -> Option<NaiveDateTime> {
+  if amount == 0 {
+    return Some(date);
+  }
+  let step = amount.signum();
+
+  // Starting from a weekend is equivalent to starting from the closest business
+  // day in the opposite direction (e.g. Saturday + 1 is Friday + 1)
+  let mut date = date;
+  while is_weekend(date.date()) {
+    date = add(date, -step, Unit::Day)?;
+  }
+
+  // Each block of 5 business days corresponds to a whole week
+  date = add(date, (amount / 5).checked_mul(7)?, Unit::Day)?;
+  let mut remaining = (amount % 5).abs();
+  while remaining > 0 {
+    date = add(date, step, Unit::Day)?;
+    if !is_weekend(date.date()) {
+      remaining -= 1;
+    }
+  }
+  Some(date)
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+  matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+fn start_of(date: NaiveDateTime, unit: Unit) -> Option<NaiveDateTime> {
+  match unit {
+    Unit::Second => date.with_nanosecond(0),
+    Unit::Minute => date.with_nanosecond(0)?.with_second(0),
+    Unit::Hour => date.with_nanosecond(0)?.with_second(0)?.with_minute(0),
+    Unit::Day | Unit::BusinessDay => date.date().and_hms_opt(0, 0, 0),
+    Unit::Week => {
+      // Weeks start on Monday, as defined by ISO 8601
+      let days = i64::from(date.weekday().num_days_from_monday());
+      add(date.date().and_hms_opt(0, 0, 0)?, -days, Unit::Day)
+    }
+    Unit::Month => NaiveDate::from_ymd_opt(date.year(), date.month(), 1)?.and_hms_opt(0, 0, 0),
+    Unit::Quarter => {
+      let month = (date.month() - 1) / 3 * 3 + 1;
+      NaiveDate::from_ymd_opt(date.year(), month, 1)?.and_hms_opt(0, 0, 0)
+    }
+    Unit::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1)?.and_hms_opt(0, 0, 0),
+  }
+}
+
+fn end_of(date: NaiveDateTime, unit: Unit) -> Option<NaiveDateTime> {
+  let next_start = add(start_of(date, unit)?, 1, unit)?;
+  add(next_start, -1, Unit::Second)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn date(year: i32, month: u32, day: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(year, month, day)
+      .unwrap()
+      .and_hms_opt(9, 10, 11)
+      .unwrap()
+  }
+
+  fn eval(date: NaiveDateTime, expression: &str) -> Option<NaiveDateTime> {
+    expression
+      .split(',')
+      .try_fold(date, |date, clause| apply_clause(date, clause.trim()))
+  }
+
+  #[test]
+  fn tokenize_works_correctly() {
+    assert_eq!(
+      tokenize("+2h30m ago").unwrap(),
+      vec![
+        Token::Number(2),
+        Token::Word("h"),
+        Token::Number(30),
+        Token::Word("m"),
+        Token::Word("ago")
+      ]
+    );
+    assert_eq!(
+      tokenize("-3 days").unwrap(),
+      vec![Token::Number(-3), Token::Word("days")]
+    );
+    assert!(tokenize("+ 3 days").is_none());
+    assert!(tokenize("3 days!").is_none());
+  }
+
+  #[test]
+  fn amounts() {
+    let tuesday = date(2014, 7, 8);
+    assert_eq!(
+      eval(tuesday, "+1d 2h"),
+      Some(date(2014, 7, 9) + Duration::hours(2))
+    );
+    assert_eq!(eval(tuesday, "-30m"), Some(tuesday - Duration::minutes(30)));
+    assert_eq!(eval(tuesday, "3 days ago"), Some(date(2014, 7, 5)));
+    assert_eq!(eval(tuesday, "in 2 weeks"), Some(date(2014, 7, 22)));
+    assert_eq!(eval(tuesday, "-1 year"), Some(date(2013, 7, 8)));
+    assert_eq!(eval(tuesday, "2 quarters"), Some(date(2015, 1, 8)));
+    assert_eq!(eval(tuesday, "tomorrow"), Some(date(2014, 7, 9)));
+    assert_eq!(eval(tuesday, "yesterday"), Some(date(2014, 7, 7)));
+  }
+
+  #[test]
+  fn months_are_clamped() {
+    assert_eq!(eval(date(2021, 1, 31), "+1 month"), Some(date(2021, 2, 28)));
+    assert_eq!(
+      eval(date(2020, 3, 31), "1 month ago"),
+      Some(date(2020, 2, 29))
+    );
+  }
+
+  #[test]
+  fn business_days() {
+    let friday = date(2014, 7, 11);
+    let saturday = date(2014, 7, 12);
+    let monday = date(2014, 7, 14);
+    assert_eq!(eval(friday, "+1 business day"), Some(monday));
+    assert_eq!(eval(saturday, "+1 business day"), Some(monday));
+    assert_eq!(eval(saturday, "-1 business day"), Some(friday));
+    assert_eq!(eval(monday, "1 working day ago"), Some(friday));
+    assert_eq!(eval(friday, "+5bd"), Some(date(2014, 7, 18)));
+    assert_eq!(
+      eval(date(2014, 7, 8), "+7 business days"),
+      Some(date(2014, 7, 17))
+    );
+    assert_eq!(
+      eval(date(2014, 7, 8), "-6 business days"),
+      Some(date(2014, 6, 30))
+    );
+    assert_eq!(eval(saturday, "0 business days"), Some(saturday));
+  }
+
+  #[test]
+  fn weekdays() {
+    let tuesday = date(2014, 7, 8);
+    assert_eq!(eval(tuesday, "next monday"), Some(date(2014, 7, 14)));
+    assert_eq!(eval(tuesday, "next tuesday"), Some(date(2014, 7, 15)));
+    assert_eq!(eval(tuesday, "last monday"), Some(date(2014, 7, 7)));
+    assert_eq!(eval(tuesday, "last tuesday"), Some(date(2014, 7, 1)));
+    assert_eq!(eval(tuesday, "this friday"), Some(date(2014, 7, 11)));
+    assert_eq!(eval(tuesday, "this monday"), Some(date(2014, 7, 7)));
+    assert_eq!(eval(tuesday, "next week"), Some(date(2014, 7, 15)));
+    assert_eq!(eval(tuesday, "last month"), Some(date(2014, 6, 8)));
+  }
+
+  #[test]
+  fn start_and_end_of_periods() {
+    let tuesday = date(2014, 8, 12);
+    let at = |year, month, day, h, m, s| {
+      NaiveDate::from_ymd_opt(year, month, day)
+        .unwrap()
+        .and_hms_opt(h, m, s)
+    };
+    assert_eq!(eval(tuesday, "start of day"), at(2014, 8, 12, 0, 0, 0));
+    assert_eq!(eval(tuesday, "end of day"), at(2014, 8, 12, 23, 59, 59));
+    assert_eq!(eval(tuesday, "start of week"), at(2014, 8, 11, 0, 0, 0));
+    assert_eq!(eval(tuesday, "end of week"), at(2014, 8, 17, 23, 59, 59));
+    assert_eq!(eval(tuesday, "beginning of month"), at(2014, 8, 1, 0, 0, 0));
+    assert_eq!(eval(tuesday, "end of month"), at(2014, 8, 31, 23, 59, 59));
+    assert_eq!(eval(tuesday, "start of quarter"), at(2014, 7, 1, 0, 0, 0));
+    assert_eq!(eval(tuesday, "end of quarter"), at(2014, 9, 30, 23, 59, 59));
+    assert_eq!(eval(tuesday, "end of year"), at(2014, 12, 31, 23, 59, 59));
+    assert_eq!(eval(tuesday, "start of hour"), at(2014, 8, 12, 9, 0, 0));
+  }
+
+  #[test]
+  fn chained_expressions() {
+    let tuesday = date(2014, 7, 8);
+    assert_eq!(
+      eval(tuesday, "next month, start of month"),
+      NaiveDate::from_ymd_opt(2014, 8, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+    );
+    assert_eq!(
+      eval(tuesday, "end of month, -1 business day"),
+      NaiveDate::from_ymd_opt(2014, 7, 30)
+        .unwrap()
+        .and_hms_opt(23, 59, 59)
+    );
+  }
+
+  #[test]
+  fn invalid_expressions() {
+    let tuesday = date(2014, 7, 8);
+    assert!(eval(tuesday, "").is_none());
+    assert!(eval(tuesday, "next").is_none());
+    assert!(eval(tuesday, "next fortnight").is_none());
+    assert!(eval(tuesday, "3").is_none());
+    assert!(eval(tuesday, "3 days 2").is_none());
+    assert!(eval(tuesday, "end of").is_none());
+    assert!(eval(tuesday, "+2 business").is_none());
+    assert!(eval(tuesday, "9999999999 years").is_none());
+  }
+}
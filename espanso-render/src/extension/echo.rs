@@ -50,10 +50,20 @@ impl Extension for EchoExtension {
     _: &crate::Scope,
     params: &Params,
   ) -> crate::ExtensionResult {
-    if let Some(Value::String(echo)) = params.get("echo") {
-      ExtensionResult::Success(ExtensionOutput::Single(echo.clone()))
-    } else {
-      ExtensionResult::Error(EchoExtensionError::MissingEchoParameter.into())
+    let Some(Value::String(echo)) = params.get("echo") else {
+      return ExtensionResult::Error(EchoExtensionError::MissingEchoParameter.into());
+    };
+
+    // The optional fields can be referenced as {{var.field}}
+    match params.get("fields") {
+      Some(Value::Object(fields)) => ExtensionResult::Success(ExtensionOutput::Structured {
+        value: echo.clone(),
+        fields: fields
+          .iter()
+          .filter_map(|(name, value)| Some((name.clone(), value.as_string()?.clone())))
+          .collect(),
+      }),
+      _ => ExtensionResult::Success(ExtensionOutput::Single(echo.clone())),
     }
   }
 }
@@ -86,6 +96,33 @@ mod tests {
     );
   }
 
+  #[test]
+  fn echo_with_fields_produces_structured_output() {
+    let extension = EchoExtension::new();
+
+    let fields = vec![("index".to_string(), Value::String("0".to_string()))]
+      .into_iter()
+      .collect::<Params>();
+    let param = vec![
+      ("echo".to_string(), Value::String("kg".to_string())),
+      ("fields".to_string(), Value::Object(fields)),
+    ]
+    .into_iter()
+    .collect::<Params>();
+    assert_eq!(
+      extension
+        .calculate(&crate::Context::default(), &HashMap::default(), &param)
+        .into_success()
+        .unwrap(),
+      ExtensionOutput::Structured {
+        value: "kg".to_string(),
+        fields: vec![("index".to_string(), "0".to_string())]
+          .into_iter()
+          .collect()
+      }
+    );
+  }
+
   #[test]
   fn missing_echo_parameter() {
     let extension = EchoExtension::new();
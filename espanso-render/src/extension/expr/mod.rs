@@ -0,0 +1,610 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The expr extension evaluates arithmetic, comparisons and string
+//! functions over the previously computed variables, without spawning
+//! any process.
+//!
+//! Variables are referenced by name (`qty`, or `form1.price` for the
+//! fields of variables producing multiple values). As their values are
+//! strings, operators are loosely typed: `+` sums its operands if both
+//! are numeric and concatenates them otherwise, and comparisons are
+//! numeric only if both operands are numeric.
+//!
+//! The variables read by an expression are evaluated before it, so they
+//! don't need to be listed in `depends_on`.
+
+use std::{collections::HashSet, fmt::Display};
+
+use thiserror::Error;
+
+use crate::{Extension, ExtensionOutput, ExtensionResult, Params, Scope, Value};
+
+use self::parser::{BinaryOp, Expr, UnaryOp};
+
+mod parser;
+
+// Numbers are printed as integers when they have no fractional part,
+// as long as they are exactly representable
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+const MAX_DIGITS: f64 = 15.0;
+
+pub struct ExprExtension {}
+
+#[allow(clippy::new_without_default)]
+impl ExprExtension {
+  pub fn new() -> Self {
+    Self {}
+  }
+}
+
+impl Extension for ExprExtension {
+  fn name(&self) -> &str {
+    "expr"
+  }
+
+  fn calculate(
+    &self,
+    _: &crate::Context,
+    scope: &crate::Scope,
+    params: &Params,
+  ) -> crate::ExtensionResult {
+    let Some(Value::String(expr)) = params.get("expr") else {
+      return ExtensionResult::Error(ExprExtensionError::MissingExprParameter.into());
+    };
+
+    match parser::parse(expr).and_then(|expr| evaluate(&expr, scope)) {
+      Ok(value) => ExtensionResult::Success(ExtensionOutput::Single(value.to_string())),
+      Err(err) => ExtensionResult::Error(err.into()),
+    }
+  }
+
+  // Expressions refer to variables by name rather than with the {{var}} syntax.
+  // Invalid expressions have none, as the error is reported when evaluating them.
+  fn variable_dependencies(&self, params: &Params) -> Vec<String> {
+    let mut names = HashSet::new();
+    if let Some(Value::String(expr)) = params.get("expr") {
+      if let Ok(parsed) = parser::parse(expr) {
+        collect_variable_names(&parsed, &mut names);
+      }
+    }
+    names.into_iter().collect()
+  }
+}
+
+fn collect_variable_names(expr: &Expr, names: &mut HashSet<String>) {
+  match expr {
+    Expr::Number(_) | Expr::String(_) | Expr::Bool(_) => {}
+    Expr::Variable(name, _) => {
+      names.insert(name.clone());
+    }
+    Expr::Unary(_, operand) => collect_variable_names(operand, names),
+    Expr::Binary(_, left, right) => {
+      collect_variable_names(left, names);
+      collect_variable_names(right, names);
+    }
+    Expr::Conditional(condition, if_true, if_false) => {
+      collect_variable_names(condition, names);
+      collect_variable_names(if_true, names);
+      collect_variable_names(if_false, names);
+    }
+    Expr::Call(_, args) => {
+      for arg in args {
+        collect_variable_names(arg, names);
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ExprValue {
+  Number(f64),
+  String(String),
+  Bool(bool),
+}
+
+impl ExprValue {
+  fn number(value: f64) -> Result<Self, ExprExtensionError> {
+    if value.is_finite() {
+      Ok(ExprValue::Number(value))
+    } else {
+      Err(ExprExtensionError::InvalidResult)
+    }
+  }
+
+  fn as_number(&self) -> Result<f64, ExprExtensionError> {
+    let number = match self {
+      ExprValue::Number(number) => Some(*number),
+      ExprValue::String(string) => string.trim().parse::<f64>().ok(),
+      ExprValue::Bool(_) => None,
+    };
+
+    number
+      .filter(|number| number.is_finite())
+      .ok_or_else(|| ExprExtensionError::NotANumber(self.to_string()))
+  }
+
+  fn as_digits(&self) -> Result<usize, ExprExtensionError> {
+    let digits = self.as_number()?;
+    if digits.fract() == 0.0 && (0.0..=MAX_DIGITS).contains(&digits) {
+      Ok(digits as usize)
+    } else {
+      Err(ExprExtensionError::InvalidDigits(self.to_string()))
+    }
+  }
+
+  fn as_index(&self) -> Result<usize, ExprExtensionError> {
+    let index = self.as_number()?;
+    if index.fract() == 0.0 && index >= 0.0 {
+      Ok(index as usize)
+    } else {
+      Err(ExprExtensionError::NotAnIndex(self.to_string()))
+    }
+  }
+
+  fn is_truthy(&self) -> bool {
+    match self {
+      ExprValue::Number(number) => *number != 0.0,
+      ExprValue::String(string) => !string.is_empty(),
+      ExprValue::Bool(value) => *value,
+    }
+  }
+}
+
+impl Display for ExprValue {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ExprValue::Number(number) if number.fract() == 0.0 && number.abs() <= MAX_EXACT_INTEGER => {
+        write!(f, "{}", *number as i64)
+      }
+      ExprValue::Number(number) => write!(f, "{number}"),
+      ExprValue::String(string) => write!(f, "{string}"),
+      ExprValue::Bool(value) => write!(f, "{value}"),
+    }
+  }
+}
+
+fn evaluate(expr: &Expr, scope: &Scope) -> Result<ExprValue, ExprExtensionError> {
+  match expr {
+    Expr::Number(number) => Ok(ExprValue::Number(*number)),
+    Expr::String(string) => Ok(ExprValue::String(string.clone())),
+    Expr::Bool(value) => Ok(ExprValue::Bool(*value)),
+    Expr::Variable(name, field) => {
+      let value = match (scope.get(name.as_str()), field) {
+        (Some(ExtensionOutput::Single(value)), None) => Some(value),
+        (Some(ExtensionOutput::Multiple(values)), Some(field)) => values.get(field),
+        (Some(ExtensionOutput::Structured { value, .. }), None) => Some(value),
+        (Some(ExtensionOutput::Structured { fields, .. }), Some(field)) => fields.get(field),
+        _ => None,
+      };
+
+      match (value, field) {
+        (Some(value), _) => Ok(ExprValue::String(value.clone())),
+        (None, Some(field)) => Err(ExprExtensionError::MissingVariable(format!(
+          "{name}.{field}"
+        ))),
+        (None, None) => Err(ExprExtensionError::MissingVariable(name.clone())),
+      }
+    }
+    Expr::Unary(op, operand) => {
+      let operand = evaluate(operand, scope)?;
+      match op {
+        UnaryOp::Negate => ExprValue::number(-operand.as_number()?),
+        UnaryOp::Not => Ok(ExprValue::Bool(!operand.is_truthy())),
+      }
+    }
+    Expr::Binary(BinaryOp::And, left, right) => Ok(ExprValue::Bool(
+      evaluate(left, scope)?.is_truthy() && evaluate(right, scope)?.is_truthy(),
+    )),
+    Expr::Binary(BinaryOp::Or, left, right) => Ok(ExprValue::Bool(
+      evaluate(left, scope)?.is_truthy() || evaluate(right, scope)?.is_truthy(),
+    )),
+    Expr::Binary(op, left, right) => {
+      evaluate_binary(*op, &evaluate(left, scope)?, &evaluate(right, scope)?)
+    }
+    Expr::Conditional(condition, when_true, when_false) => {
+      if evaluate(condition, scope)?.is_truthy() {
+        evaluate(when_true, scope)
+      } else {
+        evaluate(when_false, scope)
+      }
+    }
+    Expr::Call(name, args) => {
+      let args = args
+        .iter()
+        .map(|arg| evaluate(arg, scope))
+        .collect::<Result<Vec<_>, _>>()?;
+      call(name, &args)
+    }
+  }
+}
+
+fn evaluate_binary(
+  op: BinaryOp,
+  left: &ExprValue,
+  right: &ExprValue,
+) -> Result<ExprValue, ExprExtensionError> {
+  let numbers = left
+    .as_number()
+    .and_then(|left| Ok((left, right.as_number()?)));
+
+  match op {
+    BinaryOp::Add => match numbers {
+      Ok((left, right)) => ExprValue::number(left + right),
+      Err(_) => Ok(ExprValue::String(format!("{left}{right}"))),
+    },
+    BinaryOp::Subtract
+    | BinaryOp::Multiply
+    | BinaryOp::Divide
+    | BinaryOp::Remainder
+    | BinaryOp::Power => {
+      let (left, right) = numbers?;
+      match op {
+        BinaryOp::Subtract => ExprValue::number(left - right),
+        BinaryOp::Multiply => ExprValue::number(left * right),
+        BinaryOp::Divide | BinaryOp::Remainder if right == 0.0 => {
+          Err(ExprExtensionError::DivisionByZero)
+        }
+        BinaryOp::Divide => ExprValue::number(left / right),
+        BinaryOp::Remainder => ExprValue::number(left % right),
+        _ => ExprValue::number(left.powf(right)),
+      }
+    }
+    BinaryOp::Equal
+    | BinaryOp::NotEqual
+    | BinaryOp::Less
+    | BinaryOp::LessEqual
+    | BinaryOp::Greater
+    | BinaryOp::GreaterEqual => {
+      let ordering = match numbers {
+        Ok((left, right)) => left.partial_cmp(&right),
+        Err(_) => Some(left.to_string().cmp(&right.to_string())),
+      };
+      let result = ordering.is_some_and(|ordering| match op {
+        BinaryOp::Equal => ordering.is_eq(),
+        BinaryOp::NotEqual => ordering.is_ne(),
+        BinaryOp::Less => ordering.is_lt(),
+        BinaryOp::LessEqual => ordering.is_le(),
+        BinaryOp::Greater => ordering.is_gt(),
+        _ => ordering.is_ge(),
+      });
+      Ok(ExprValue::Bool(result))
+    }
+    BinaryOp::And | BinaryOp::Or => unreachable!("logical operators are evaluated lazily"),
+  }
+}
+
+fn call(name: &str, args: &[ExprValue]) -> Result<ExprValue, ExprExtensionError> {
+  let invalid_arguments = || ExprExtensionError::InvalidArguments(name.to_string());
+
+  match (name, args) {
+    ("abs", [x]) => ExprValue::number(x.as_number()?.abs()),
+    ("floor", [x]) => ExprValue::number(x.as_number()?.floor()),
+    ("ceil", [x]) => ExprValue::number(x.as_number()?.ceil()),
+    ("sqrt", [x]) => ExprValue::number(x.as_number()?.sqrt()),
+    ("pow", [x, y]) => ExprValue::number(x.as_number()?.powf(y.as_number()?)),
+    ("round", [x]) => ExprValue::number(x.as_number()?.round()),
+    ("round", [x, digits]) => {
+      let factor = 10f64.powi(digits.as_digits()? as i32);
+      ExprValue::number((x.as_number()? * factor).round() / factor)
+    }
+    ("min" | "max", [first, rest @ ..]) => {
+      let mut result = first.as_number()?;
+      for arg in rest {
+        let number = arg.as_number()?;
+        result = if name == "min" {
+          result.min(number)
+        } else {
+          result.max(number)
+        };
+      }
+      ExprValue::number(result)
+    }
+    ("len", [s]) => {
+      let length = u32::try_from(s.to_string().chars().count()).map_err(|_| invalid_arguments())?;
+      ExprValue::number(f64::from(length))
+    }
+    ("upper", [s]) => Ok(ExprValue::String(s.to_string().to_uppercase())),
+    ("lower", [s]) => Ok(ExprValue::String(s.to_string().to_lowercase())),
+    ("trim", [s]) => Ok(ExprValue::String(s.to_string().trim().to_string())),
+    ("substr", [s, start] | [s, start, _]) => {
+      let start = start.as_index()?;
+      let chars = s.to_string().chars().skip(start).collect::<Vec<_>>();
+      let length = match args.get(2) {
+        Some(length) => length.as_index()?,
+        None => chars.len(),
+      };
+      Ok(ExprValue::String(chars.into_iter().take(length).collect()))
+    }
+    ("replace", [s, from, to]) => Ok(ExprValue::String(
+      s.to_string().replace(&from.to_string(), &to.to_string()),
+    )),
+    ("contains", [s, sub]) => Ok(ExprValue::Bool(s.to_string().contains(&sub.to_string()))),
+    ("starts_with", [s, prefix]) => Ok(ExprValue::Bool(
+      s.to_string().starts_with(&prefix.to_string()),
+    )),
+    ("ends_with", [s, suffix]) => Ok(ExprValue::Bool(
+      s.to_string().ends_with(&suffix.to_string()),
+    )),
+    ("concat", args) => Ok(ExprValue::String(
+      args.iter().map(ToString::to_string).collect(),
+    )),
+    ("number", [x]) => Ok(ExprValue::Number(x.as_number()?)),
+    ("string", [x]) => Ok(ExprValue::String(x.to_string())),
+    ("fixed", [x, digits]) => Ok(ExprValue::String(format!(
+      "{:.*}",
+      digits.as_digits()?,
+      x.as_number()?
+    ))),
+    ("format", [x, digits, rest @ ..]) if rest.len() <= 2 => {
+      let separator = rest.first().map_or(",".to_string(), ToString::to_string);
+      let decimal_separator = rest.get(1).map_or(".".to_string(), ToString::to_string);
+      Ok(ExprValue::String(format_number(
+        x.as_number()?,
+        digits.as_digits()?,
+        &separator,
+        &decimal_separator,
+      )))
+    }
+    (
+      "abs" | "floor" | "ceil" | "sqrt" | "pow" | "round" | "min" | "max" | "len" | "upper"
+      | "lower" | "trim" | "substr" | "replace" | "contains" | "starts_with" | "ends_with"
+      | "number" | "string" | "fixed" | "format",
+      _,
+    ) => Err(invalid_arguments()),
+    _ => Err(ExprExtensionError::UnknownFunction(name.to_string())),
+  }
+}
+
+// Format the number with the given decimal digits, grouping the
+// integer part in thousands
+fn format_number(number: f64, digits: usize, separator: &str, decimal_separator: &str) -> String {
+  let formatted = format!("{:.*}", digits, number.abs());
+  let (integer, decimals) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+  let mut output = String::new();
+  if number < 0.0 {
+    output.push('-');
+  }
+  for (index, c) in integer.chars().enumerate() {
+    if index > 0 && (integer.len() - index) % 3 == 0 {
+      output.push_str(separator);
+    }
+    output.push(c);
+  }
+  if !decimals.is_empty() {
+    output.push_str(decimal_separator);
+    output.push_str(decimals);
+  }
+
+  output
+}
+
+#[derive(Error, Debug)]
+pub enum ExprExtensionError {
+  #[error("missing 'expr' parameter")]
+  MissingExprParameter,
+
+  #[error("syntax error: {0}")]
+  SyntaxError(String),
+
+  #[error("expression is too complex")]
+  TooComplex,
+
+  #[error("missing variable: {0}")]
+  MissingVariable(String),
+
+  #[error("unknown function: {0}")]
+  UnknownFunction(String),
+
+  #[error("invalid arguments for function: {0}")]
+  InvalidArguments(String),
+
+  #[error("'{0}' is not a number")]
+  NotANumber(String),
+
+  #[error("'{0}' is not a valid index")]
+  NotAnIndex(String),
+
+  #[error("invalid number of digits: {0}")]
+  InvalidDigits(String),
+
+  #[error("division by zero")]
+  DivisionByZero,
+
+  #[error("expression result is not a finite number")]
+  InvalidResult,
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use super::*;
+
+  fn scope() -> Scope<'static> {
+    let mut scope = Scope::new();
+    scope.insert("qty", ExtensionOutput::Single("3".to_string()));
+    scope.insert("name", ExtensionOutput::Single(" John ".to_string()));
+    scope.insert("zip", ExtensionOutput::Single("01234".to_string()));
+    let fields = vec![
+      ("price".to_string(), "2.5".to_string()),
+      ("currency".to_string(), "EUR".to_string()),
+    ]
+    .into_iter()
+    .collect::<HashMap<_, _>>();
+    scope.insert("form1", ExtensionOutput::Multiple(fields));
+    scope
+  }
+
+  fn variable_dependencies(expr: &str) -> HashSet<String> {
+    let params = vec![("expr".to_string(), Value::String(expr.to_string()))]
+      .into_iter()
+      .collect::<Params>();
+    ExprExtension::new()
+      .variable_dependencies(&params)
+      .into_iter()
+      .collect()
+  }
+
+  #[test]
+  fn variable_dependencies_are_collected() {
+    let names = variable_dependencies("round(qty * form1.price, 2) + (flag ? 'a' : other)");
+    assert_eq!(
+      names,
+      vec!["qty", "form1", "flag", "other"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+    );
+    assert!(variable_dependencies("1 + (").is_empty());
+  }
+
+  fn eval(expr: &str) -> String {
+    let extension = ExprExtension::new();
+    let params = vec![("expr".to_string(), Value::String(expr.to_string()))]
+      .into_iter()
+      .collect::<Params>();
+    match extension.calculate(&crate::Context::default(), &scope(), &params) {
+      ExtensionResult::Success(ExtensionOutput::Single(output)) => output,
+      result => panic!("unexpected result for '{expr}': {result:?}"),
+    }
+  }
+
+  fn eval_error(expr: &str) -> String {
+    let extension = ExprExtension::new();
+    let params = vec![("expr".to_string(), Value::String(expr.to_string()))]
+      .into_iter()
+      .collect::<Params>();
+    extension
+      .calculate(&crate::Context::default(), &scope(), &params)
+      .into_error()
+      .unwrap()
+      .to_string()
+  }
+
+  #[test]
+  fn arithmetic() {
+    assert_eq!(eval("1 + 2 * 3"), "7");
+    assert_eq!(eval("(1 + 2) * 3"), "9");
+    assert_eq!(eval("7 / 2"), "3.5");
+    assert_eq!(eval("7 % 4"), "3");
+    assert_eq!(eval("2 ^ 3 ^ 2"), "512");
+    assert_eq!(eval("-2 ^ 2"), "-4");
+    assert_eq!(eval("10 - 4 - 3"), "3");
+  }
+
+  #[test]
+  fn variables() {
+    assert_eq!(eval("qty * form1.price"), "7.5");
+    assert_eq!(eval("qty + 1"), "4");
+    assert_eq!(eval("form1.currency + ' ' + qty"), "EUR 3");
+    assert_eq!(eval("'ZIP ' + zip"), "ZIP 01234");
+  }
+
+  #[test]
+  fn comparisons_and_logic() {
+    assert_eq!(eval("qty > 2"), "true");
+    assert_eq!(eval("qty == '3.0'"), "true");
+    assert_eq!(eval("'abc' < 'abd'"), "true");
+    assert_eq!(eval("form1.currency != 'EUR'"), "false");
+    assert_eq!(eval("qty > 5 || form1.currency == 'EUR'"), "true");
+    assert_eq!(eval("!(qty > 2) && true"), "false");
+    assert_eq!(eval("qty > 2 ? 'many' : 'few'"), "many");
+  }
+
+  #[test]
+  fn logical_operators_short_circuit() {
+    assert_eq!(eval("false && missing"), "false");
+    assert_eq!(eval("true || 1 / 0"), "true");
+    assert_eq!(eval("qty > 0 ? 'ok' : missing"), "ok");
+  }
+
+  #[test]
+  fn string_functions() {
+    assert_eq!(eval("upper(trim(name))"), "JOHN");
+    assert_eq!(eval("lower('ABC')"), "abc");
+    assert_eq!(eval("len(name)"), "6");
+    assert_eq!(eval("substr('espanso', 1, 3)"), "spa");
+    assert_eq!(eval("substr('espanso', 4)"), "nso");
+    assert_eq!(eval("replace('a-b-c', '-', '+')"), "a+b+c");
+    assert_eq!(eval("contains('espanso', 'pan')"), "true");
+    assert_eq!(eval("starts_with('espanso', 'es')"), "true");
+    assert_eq!(eval("ends_with('espanso', 'es')"), "false");
+    assert_eq!(eval("concat(1, '+', 2)"), "1+2");
+  }
+
+  #[test]
+  fn number_functions() {
+    assert_eq!(eval("abs(-3)"), "3");
+    assert_eq!(eval("floor(2.7) + ceil(2.1)"), "5");
+    assert_eq!(eval("sqrt(16)"), "4");
+    assert_eq!(eval("pow(2, 10)"), "1024");
+    assert_eq!(eval("round(2.5)"), "3");
+    assert_eq!(eval("round(3.14159, 2)"), "3.14");
+    assert_eq!(eval("min(3, 1, 2) + max(qty, 10)"), "11");
+    assert_eq!(eval("number(zip) + 1"), "1235");
+    assert_eq!(eval("string(1) + 2"), "3");
+  }
+
+  #[test]
+  fn number_formatting() {
+    assert_eq!(eval("fixed(qty * form1.price, 2)"), "7.50");
+    assert_eq!(eval("fixed(2, 0)"), "2");
+    assert_eq!(eval("format(1234567.891, 2)"), "1,234,567.89");
+    assert_eq!(eval("format(-1234.5, 1, '.', ',')"), "-1.234,5");
+    assert_eq!(eval("format(123, 0, ' ')"), "123");
+    assert_eq!(eval("0.1 + 0.2"), "0.30000000000000004");
+  }
+
+  #[test]
+  fn errors() {
+    assert_eq!(eval_error("1 / 0"), "division by zero");
+    assert_eq!(eval_error("missing + 1"), "missing variable: missing");
+    assert_eq!(
+      eval_error("form1.missing"),
+      "missing variable: form1.missing"
+    );
+    assert_eq!(eval_error("form1"), "missing variable: form1");
+    assert_eq!(eval_error("name * 2"), "' John ' is not a number");
+    assert_eq!(eval_error("unknown(1)"), "unknown function: unknown");
+    assert_eq!(
+      eval_error("abs(1, 2)"),
+      "invalid arguments for function: abs"
+    );
+    assert_eq!(
+      eval_error("sqrt(-1)"),
+      "expression result is not a finite number"
+    );
+    assert_eq!(eval_error("fixed(1, 100)"), "invalid number of digits: 100");
+    assert_eq!(
+      eval_error("1 +"),
+      "syntax error: unexpected end of expression"
+    );
+  }
+
+  #[test]
+  fn missing_expr_parameter() {
+    let extension = ExprExtension::new();
+    assert!(matches!(
+      extension.calculate(&crate::Context::default(), &scope(), &Params::new()),
+      ExtensionResult::Error(_)
+    ));
+  }
+}
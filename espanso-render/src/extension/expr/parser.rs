@@ -0,0 +1,491 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::Display;
+
+use super::ExprExtensionError;
+
+// Limits applied to keep the evaluation of arbitrary expressions cheap
+const MAX_TOKENS: usize = 1024;
+const MAX_DEPTH: usize = 64;
+
+const OPERATORS: &[&str] = &[
+  "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "^", "<", ">", "!", "(", ")", ",",
+  ".", "?", ":",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Expr {
+  Number(f64),
+  String(String),
+  Bool(bool),
+  Variable(String, Option<String>),
+  Unary(UnaryOp, Box<Expr>),
+  Binary(BinaryOp, Box<Expr>, Box<Expr>),
+  Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+  Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum UnaryOp {
+  Negate,
+  Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum BinaryOp {
+  Add,
+  Subtract,
+  Multiply,
+  Divide,
+  Remainder,
+  Power,
+  Equal,
+  NotEqual,
+  Less,
+  LessEqual,
+  Greater,
+  GreaterEqual,
+  And,
+  Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Number(f64),
+  String(String),
+  Identifier(String),
+  Operator(&'static str),
+}
+
+impl Display for Token {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Token::Number(number) => write!(f, "number '{number}'"),
+      Token::String(string) => write!(f, "string '{string}'"),
+      Token::Identifier(name) => write!(f, "identifier '{name}'"),
+      Token::Operator(op) => write!(f, "'{op}'"),
+    }
+  }
+}
+
+pub(super) fn parse(expr: &str) -> Result<Expr, ExprExtensionError> {
+  let tokens = tokenize(expr)?;
+  if tokens.len() > MAX_TOKENS {
+    return Err(ExprExtensionError::TooComplex);
+  }
+
+  let mut parser = Parser {
+    tokens,
+    position: 0,
+    depth: 0,
+  };
+  let expr = parser.parse_expression()?;
+
+  if let Some(token) = parser.peek() {
+    return Err(ExprExtensionError::SyntaxError(format!(
+      "unexpected {token}"
+    )));
+  }
+
+  Ok(expr)
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, ExprExtensionError> {
+  let chars: Vec<char> = expr.chars().collect();
+  let mut tokens = Vec::new();
+  let mut position = 0;
+
+  while let Some(&c) = chars.get(position) {
+    if c.is_whitespace() {
+      position += 1;
+    } else if c.is_alphanumeric() || c == '_' {
+      let start = position;
+      while chars
+        .get(position)
+        .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+      {
+        position += 1;
+      }
+      let word: String = chars[start..position].iter().collect();
+
+      // Field names, such as the "1" in "form.1", are always identifiers
+      let is_field = tokens.last() == Some(&Token::Operator("."));
+      if c.is_ascii_digit() && !is_field {
+        position = start;
+        tokens.push(tokenize_number(&chars, &mut position)?);
+      } else {
+        tokens.push(Token::Identifier(word));
+      }
+    } else if c == '\'' || c == '"' {
+      tokens.push(tokenize_string(&chars, &mut position)?);
+    } else if let Some(op) = OPERATORS.iter().find(|op| {
+      op.chars()
+        .enumerate()
+        .all(|(offset, op_char)| chars.get(position + offset) == Some(&op_char))
+    }) {
+      tokens.push(Token::Operator(op));
+      position += op.chars().count();
+    } else {
+      return Err(ExprExtensionError::SyntaxError(format!(
+        "unexpected character '{c}' at position {position}"
+      )));
+    }
+  }
+
+  Ok(tokens)
+}
+
+fn tokenize_number(chars: &[char], position: &mut usize) -> Result<Token, ExprExtensionError> {
+  let start = *position;
+  let is_digit = |position: usize| chars.get(position).is_some_and(char::is_ascii_digit);
+
+  while is_digit(*position) {
+    *position += 1;
+  }
+  if chars.get(*position) == Some(&'.') && is_digit(*position + 1) {
+    *position += 1;
+    while is_digit(*position) {
+      *position += 1;
+    }
+  }
+
+  let number: String = chars[start..*position].iter().collect();
+  if chars
+    .get(*position)
+    .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+  {
+    return Err(ExprExtensionError::SyntaxError(format!(
+      "invalid number at position {start}"
+    )));
+  }
+
+  number
+    .parse()
+    .map(Token::Number)
+    .map_err(|_| ExprExtensionError::SyntaxError(format!("invalid number '{number}'")))
+}
+
+fn tokenize_string(chars: &[char], position: &mut usize) -> Result<Token, ExprExtensionError> {
+  let start = *position;
+  let quote = chars[start];
+  let mut string = String::new();
+  *position += 1;
+
+  loop {
+    match chars.get(*position) {
+      Some(c) if *c == quote => {
+        *position += 1;
+        return Ok(Token::String(string));
+      }
+      Some('\\') => {
+        let escaped = match chars.get(*position + 1) {
+          Some('n') => '\n',
+          Some('t') => '\t',
+          Some(c) => *c,
+          None => break,
+        };
+        string.push(escaped);
+        *position += 2;
+      }
+      Some(c) => {
+        string.push(*c);
+        *position += 1;
+      }
+      None => break,
+    }
+  }
+
+  Err(ExprExtensionError::SyntaxError(format!(
+    "unterminated string at position {start}"
+  )))
+}
+
+fn binary_operator(token: &Token) -> Option<(BinaryOp, u8)> {
+  let Token::Operator(op) = token else {
+    return None;
+  };
+
+  Some(match *op {
+    "||" => (BinaryOp::Or, 1),
+    "&&" => (BinaryOp::And, 2),
+    "==" => (BinaryOp::Equal, 3),
+    "!=" => (BinaryOp::NotEqual, 3),
+    "<" => (BinaryOp::Less, 4),
+    "<=" => (BinaryOp::LessEqual, 4),
+    ">" => (BinaryOp::Greater, 4),
+    ">=" => (BinaryOp::GreaterEqual, 4),
+    "+" => (BinaryOp::Add, 5),
+    "-" => (BinaryOp::Subtract, 5),
+    "*" => (BinaryOp::Multiply, 6),
+    "/" => (BinaryOp::Divide, 6),
+    "%" => (BinaryOp::Remainder, 6),
+    _ => return None,
+  })
+}
+
+struct Parser {
+  tokens: Vec<Token>,
+  position: usize,
+  depth: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.position)
+  }
+
+  fn next(&mut self) -> Option<Token> {
+    let token = self.tokens.get(self.position).cloned();
+    self.position += 1;
+    token
+  }
+
+  fn consume(&mut self, op: &str) -> bool {
+    if matches!(self.peek(), Some(Token::Operator(current)) if *current == op) {
+      self.position += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn expect(&mut self, op: &str) -> Result<(), ExprExtensionError> {
+    if self.consume(op) {
+      Ok(())
+    } else {
+      Err(self.unexpected(self.peek().cloned()))
+    }
+  }
+
+  fn unexpected(&self, token: Option<Token>) -> ExprExtensionError {
+    match token {
+      Some(token) => ExprExtensionError::SyntaxError(format!("unexpected {token}")),
+      None => ExprExtensionError::SyntaxError("unexpected end of expression".to_string()),
+    }
+  }
+
+  fn enter(&mut self) -> Result<(), ExprExtensionError> {
+    self.depth += 1;
+    if self.depth > MAX_DEPTH {
+      return Err(ExprExtensionError::TooComplex);
+    }
+    Ok(())
+  }
+
+  // expression := binary ('?' expression ':' expression)?
+  fn parse_expression(&mut self) -> Result<Expr, ExprExtensionError> {
+    self.enter()?;
+    let mut expr = self.parse_binary(1)?;
+    if self.consume("?") {
+      let when_true = self.parse_expression()?;
+      self.expect(":")?;
+      let when_false = self.parse_expression()?;
+      expr = Expr::Conditional(Box::new(expr), Box::new(when_true), Box::new(when_false));
+    }
+    self.depth -= 1;
+    Ok(expr)
+  }
+
+  fn parse_binary(&mut self, min_precedence: u8) -> Result<Expr, ExprExtensionError> {
+    let mut left = self.parse_unary()?;
+
+    while let Some((op, precedence)) = self.peek().and_then(binary_operator) {
+      if precedence < min_precedence {
+        break;
+      }
+      self.position += 1;
+      let right = self.parse_binary(precedence + 1)?;
+      left = Expr::Binary(op, Box::new(left), Box::new(right));
+    }
+
+    Ok(left)
+  }
+
+  // unary := ('-' | '!') unary | primary ('^' unary)?
+  fn parse_unary(&mut self) -> Result<Expr, ExprExtensionError> {
+    self.enter()?;
+    let expr = if self.consume("-") {
+      Expr::Unary(UnaryOp::Negate, Box::new(self.parse_unary()?))
+    } else if self.consume("!") {
+      Expr::Unary(UnaryOp::Not, Box::new(self.parse_unary()?))
+    } else {
+      let base = self.parse_primary()?;
+      if self.consume("^") {
+        let exponent = self.parse_unary()?;
+        Expr::Binary(BinaryOp::Power, Box::new(base), Box::new(exponent))
+      } else {
+        base
+      }
+    };
+    self.depth -= 1;
+    Ok(expr)
+  }
+
+  fn parse_primary(&mut self) -> Result<Expr, ExprExtensionError> {
+    match self.next() {
+      Some(Token::Number(number)) => Ok(Expr::Number(number)),
+      Some(Token::String(string)) => Ok(Expr::String(string)),
+      Some(Token::Identifier(name)) if name == "true" => Ok(Expr::Bool(true)),
+      Some(Token::Identifier(name)) if name == "false" => Ok(Expr::Bool(false)),
+      Some(Token::Identifier(name)) => {
+        if self.consume("(") {
+          let mut args = Vec::new();
+          if !self.consume(")") {
+            loop {
+              args.push(self.parse_expression()?);
+              if self.consume(")") {
+                break;
+              }
+              self.expect(",")?;
+            }
+          }
+          Ok(Expr::Call(name, args))
+        } else if self.consume(".") {
+          match self.next() {
+            Some(Token::Identifier(field)) => Ok(Expr::Variable(name, Some(field))),
+            token => Err(self.unexpected(token)),
+          }
+        } else {
+          Ok(Expr::Variable(name, None))
+        }
+      }
+      Some(Token::Operator("(")) => {
+        let expr = self.parse_expression()?;
+        self.expect(")")?;
+        Ok(expr)
+      }
+      token => Err(self.unexpected(token)),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn tokenize_works_correctly() {
+    assert_eq!(
+      tokenize("form1.qty * 2.5 >= 'a\\'b'").unwrap(),
+      vec![
+        Token::Identifier("form1".to_string()),
+        Token::Operator("."),
+        Token::Identifier("qty".to_string()),
+        Token::Operator("*"),
+        Token::Number(2.5),
+        Token::Operator(">="),
+        Token::String("a'b".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn tokenize_numeric_field_names() {
+    assert_eq!(
+      tokenize("form.1").unwrap(),
+      vec![
+        Token::Identifier("form".to_string()),
+        Token::Operator("."),
+        Token::Identifier("1".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn tokenize_invalid_input() {
+    assert!(tokenize("1 # 2").is_err());
+    assert!(tokenize("'unterminated").is_err());
+    assert!(tokenize("12abc").is_err());
+  }
+
+  #[test]
+  fn parse_respects_precedence() {
+    assert_eq!(
+      parse("1 + 2 * 3").unwrap(),
+      Expr::Binary(
+        BinaryOp::Add,
+        Box::new(Expr::Number(1.0)),
+        Box::new(Expr::Binary(
+          BinaryOp::Multiply,
+          Box::new(Expr::Number(2.0)),
+          Box::new(Expr::Number(3.0))
+        ))
+      )
+    );
+    assert_eq!(
+      parse("-2 ^ 2").unwrap(),
+      Expr::Unary(
+        UnaryOp::Negate,
+        Box::new(Expr::Binary(
+          BinaryOp::Power,
+          Box::new(Expr::Number(2.0)),
+          Box::new(Expr::Number(2.0))
+        ))
+      )
+    );
+    assert_eq!(
+      parse("10 - 4 - 3").unwrap(),
+      Expr::Binary(
+        BinaryOp::Subtract,
+        Box::new(Expr::Binary(
+          BinaryOp::Subtract,
+          Box::new(Expr::Number(10.0)),
+          Box::new(Expr::Number(4.0))
+        )),
+        Box::new(Expr::Number(3.0))
+      )
+    );
+  }
+
+  #[test]
+  fn parse_calls_and_conditionals() {
+    assert_eq!(
+      parse("x > 1 ? max(x, 2) : y.z").unwrap(),
+      Expr::Conditional(
+        Box::new(Expr::Binary(
+          BinaryOp::Greater,
+          Box::new(Expr::Variable("x".to_string(), None)),
+          Box::new(Expr::Number(1.0))
+        )),
+        Box::new(Expr::Call(
+          "max".to_string(),
+          vec![Expr::Variable("x".to_string(), None), Expr::Number(2.0)]
+        )),
+        Box::new(Expr::Variable("y".to_string(), Some("z".to_string())))
+      )
+    );
+  }
+
+  #[test]
+  fn parse_invalid_expressions() {
+    assert!(parse("").is_err());
+    assert!(parse("1 +").is_err());
+    assert!(parse("(1 + 2").is_err());
+    assert!(parse("1 2").is_err());
+    assert!(parse("max(1,)").is_err());
+    assert!(parse("x ? 1").is_err());
+  }
+
+  #[test]
+  fn parse_rejects_deeply_nested_expressions() {
+    let expr = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+    assert!(matches!(parse(&expr), Err(ExprExtensionError::TooComplex)));
+  }
+}
@@ -0,0 +1,369 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use encoding_rs::Encoding;
+use thiserror::Error;
+
+use crate::{Extension, ExtensionOutput, ExtensionResult, Number, Params, Value};
+
+/// Reads the content of the file at `path`, which is resolved relative to
+/// the config directory when not absolute. Optionally, the file content can be
+/// decoded with a given `encoding`, restricted to the lines between `from_line`
+/// and `to_line` (1-based, inclusive) and rendered with the previously
+/// computed variables (`inject_vars`). In that case, the variables referenced
+/// inside the file are evaluated before the file one.
+pub struct FileExtension {
+  config_path: PathBuf,
+}
+
+#[allow(clippy::new_without_default)]
+impl FileExtension {
+  pub fn new(config_path: &Path) -> Self {
+    Self {
+      config_path: config_path.to_owned(),
+    }
+  }
+
+  fn read(&self, scope: &crate::Scope, params: &Params) -> Result<String> {
+    let content = self.read_content(params)?;
+    if is_inject_vars(params) {
+      crate::renderer::template::render_template(&content, scope)
+    } else {
+      Ok(content)
+    }
+  }
+
+  fn read_content(&self, params: &Params) -> Result<String> {
+    let Some(Value::String(path)) = params.get("path") else {
+      return Err(FileExtensionError::MissingPathParameter.into());
+    };
+    let path = self.config_path.join(path);
+
+    let encoding = match params.get("encoding") {
+      Some(Value::String(label)) => Encoding::for_label(label.as_bytes())
+        .ok_or_else(|| FileExtensionError::InvalidEncoding(label.clone()))?,
+      None => encoding_rs::UTF_8,
+      Some(_) => return Err(FileExtensionError::InvalidParameter("encoding").into()),
+    };
+
+    let bytes = std::fs::read(&path)
+      .map_err(|err| FileExtensionError::ReadError(path.clone(), err.to_string()))?;
+    let (content, _, had_errors) = encoding.decode(&bytes);
+    if had_errors {
+      return Err(FileExtensionError::DecodeError(path, encoding.name().to_string()).into());
+    }
+
+    let from_line = get_line_param(params, "from_line")?;
+    let to_line = get_line_param(params, "to_line")?;
+    if from_line.is_some() || to_line.is_some() {
+      Ok(select_lines(
+        &content,
+        from_line.unwrap_or(1),
+        to_line.unwrap_or(usize::MAX),
+      ))
+    } else {
+      Ok(content.into_owned())
+    }
+  }
+}
+
+fn is_inject_vars(params: &Params) -> bool {
+  params
+    .get("inject_vars")
+    .and_then(|v| v.as_bool())
+    .copied()
+    .unwrap_or(false)
+}
+
+fn get_line_param(params: &Params, name: &'static str) -> Result<Option<usize>> {
+  match params.get(name) {
+    Some(Value::Number(Number::Integer(line))) if *line >= 1 => Ok(Some(*line as usize)),
+    None => Ok(None),
+    Some(_) => Err(FileExtensionError::InvalidParameter(name).into()),
+  }
+}
+
+fn select_lines(content: &str, from_line: usize, to_line: usize) -> String {
+  content
+    .lines()
+    .skip(from_line - 1)
+    .take(to_line.saturating_sub(from_line - 1))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+impl Extension for FileExtension {
+  fn name(&self) -> &str {
+    "file"
+  }
+
+  fn calculate(
+    &self,
+    _: &crate::Context,
+    scope: &crate::Scope,
+    params: &Params,
+  ) -> crate::ExtensionResult {
+    match self.read(scope, params) {
+      Ok(content) => ExtensionResult::Success(ExtensionOutput::Single(content)),
+      Err(err) => ExtensionResult::Error(err),
+    }
+  }
+
+  // With `inject_vars`, the variables are referenced inside the file rather
+  // than in the params. Unreadable files have none, as the error is reported
+  // when calculating the variable.
+  fn variable_dependencies(&self, params: &Params) -> Vec<String> {
+    if !is_inject_vars(params) {
+      return Vec::new();
+    }
+
+    match self.read_content(params) {
+      Ok(content) => crate::renderer::template::get_variable_names(&content)
+        .into_iter()
+        .map(String::from)
+        .collect(),
+      Err(_) => Vec::new(),
+    }
+  }
+}
+
+#[derive(Error, Debug)]
+pub enum FileExtensionError {
+  #[error("missing 'path' parameter")]
+  MissingPathParameter,
+
+  #[error("invalid '{0}' parameter")]
+  InvalidParameter(&'static str),
+
+  #[error("unknown encoding: {0}")]
+  InvalidEncoding(String),
+
+  #[error("unable to read file {0:?}: {1}")]
+  ReadError(PathBuf, String),
+
+  #[error("file {0:?} is not valid {1}")]
+  DecodeError(PathBuf, String),
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use tempdir::TempDir;
+
+  use super::*;
+
+  fn params(values: Vec<(&str, Value)>) -> Params {
+    values
+      .into_iter()
+      .map(|(key, value)| (key.to_string(), value))
+      .collect()
+  }
+
+  fn read(config_path: &Path, scope: &crate::Scope, params: &Params) -> Result<String> {
+    let extension = FileExtension::new(config_path);
+    match extension.calculate(&crate::Context::default(), scope, params) {
+      ExtensionResult::Success(ExtensionOutput::Single(content)) => Ok(content),
+      ExtensionResult::Error(err) => Err(err),
+      result => panic!("unexpected result: {result:?}"),
+    }
+  }
+
+  #[test]
+  fn read_relative_to_config_dir() {
+    let dir = TempDir::new("espanso-file").unwrap();
+    std::fs::create_dir(dir.path().join("templates")).unwrap();
+    std::fs::write(dir.path().join("templates/license.txt"), "MIT License\n").unwrap();
+
+    let params = params(vec![(
+      "path",
+      Value::String("templates/license.txt".to_string()),
+    )]);
+    assert_eq!(
+      read(dir.path(), &HashMap::default(), &params).unwrap(),
+      "MIT License\n"
+    );
+  }
+
+  #[test]
+  fn read_absolute_path() {
+    let dir = TempDir::new("espanso-file").unwrap();
+    let file = dir.path().join("file.txt");
+    std::fs::write(&file, "content").unwrap();
+
+    let params = params(vec![(
+      "path",
+      Value::String(file.to_string_lossy().to_string()),
+    )]);
+    assert_eq!(
+      read(
+        &PathBuf::from("/non/existent"),
+        &HashMap::default(),
+        &params
+      )
+      .unwrap(),
+      "content"
+    );
+  }
+
+  #[test]
+  fn line_selection() {
+    let dir = TempDir::new("espanso-file").unwrap();
+    std::fs::write(dir.path().join("file.txt"), "one\ntwo\nthree\nfour\n").unwrap();
+
+    let path = ("path", Value::String("file.txt".to_string()));
+    let from = |line| ("from_line", Value::Number(Number::Integer(line)));
+    let to = |line| ("to_line", Value::Number(Number::Integer(line)));
+
+    let scope = HashMap::default();
+    assert_eq!(
+      read(
+        dir.path(),
+        &scope,
+        &params(vec![path.clone(), from(2), to(3)])
+      )
+      .unwrap(),
+      "two\nthree"
+    );
+    assert_eq!(
+      read(dir.path(), &scope, &params(vec![path.clone(), from(3)])).unwrap(),
+      "three\nfour"
+    );
+    assert_eq!(
+      read(dir.path(), &scope, &params(vec![path.clone(), to(1)])).unwrap(),
+      "one"
+    );
+    assert_eq!(
+      read(
+        dir.path(),
+        &scope,
+        &params(vec![path.clone(), from(3), to(2)])
+      )
+      .unwrap(),
+      ""
+    );
+    assert!(read(dir.path(), &scope, &params(vec![path, from(0)])).is_err());
+  }
+
+  #[test]
+  fn encoding() {
+    let dir = TempDir::new("espanso-file").unwrap();
+    std::fs::write(dir.path().join("latin1.txt"), b"caf\xe9").unwrap();
+
+    let path = ("path", Value::String("latin1.txt".to_string()));
+    let scope = HashMap::default();
+    assert_eq!(
+      read(
+        dir.path(),
+        &scope,
+        &params(vec![
+          path.clone(),
+          ("encoding", Value::String("latin1".to_string()))
+        ])
+      )
+      .unwrap(),
+      "café"
+    );
+    assert!(read(dir.path(), &scope, &params(vec![path.clone()])).is_err());
+    assert!(read(
+      dir.path(),
+      &scope,
+      &params(vec![
+        path,
+        ("encoding", Value::String("unknown".to_string()))
+      ])
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn variable_injection() {
+    let dir = TempDir::new("espanso-file").unwrap();
+    std::fs::write(
+      dir.path().join("email.txt"),
+      "Dear {{form1.name}},{{#if signature}}\n{{signature|upper}}{{/if}}",
+    )
+    .unwrap();
+
+    let mut scope = crate::Scope::new();
+    let fields = vec![("name".to_string(), "John".to_string())]
+      .into_iter()
+      .collect();
+    scope.insert("form1", ExtensionOutput::Multiple(fields));
+    scope.insert("signature", ExtensionOutput::Single("Jane".to_string()));
+
+    let path = ("path", Value::String("email.txt".to_string()));
+    assert_eq!(
+      read(
+        dir.path(),
+        &scope,
+        &params(vec![path.clone(), ("inject_vars", Value::Bool(true))])
+      )
+      .unwrap(),
+      "Dear John,\nJANE"
+    );
+    assert_eq!(
+      read(dir.path(), &scope, &params(vec![path])).unwrap(),
+      "Dear {{form1.name}},{{#if signature}}\n{{signature|upper}}{{/if}}"
+    );
+  }
+
+  #[test]
+  fn injected_variables_are_dependencies() {
+    let dir = TempDir::new("espanso-file").unwrap();
+    std::fs::write(
+      dir.path().join("email.txt"),
+      "Dear {{form1.name}},{{#if signature}}\n{{signature|upper}}{{/if}}",
+    )
+    .unwrap();
+
+    let extension = FileExtension::new(dir.path());
+    let path = ("path", Value::String("email.txt".to_string()));
+    let mut names = extension.variable_dependencies(&params(vec![
+      path.clone(),
+      ("inject_vars", Value::Bool(true)),
+    ]));
+    names.sort();
+    assert_eq!(names, vec!["form1", "signature"]);
+    assert!(extension
+      .variable_dependencies(&params(vec![path]))
+      .is_empty());
+    assert!(extension
+      .variable_dependencies(&params(vec![
+        ("path", Value::String("missing.txt".to_string())),
+        ("inject_vars", Value::Bool(true)),
+      ]))
+      .is_empty());
+  }
+
+  #[test]
+  fn missing_file() {
+    let dir = TempDir::new("espanso-file").unwrap();
+    assert!(read(
+      dir.path(),
+      &HashMap::default(),
+      &params(vec![("path", Value::String("missing.txt".to_string()))])
+    )
+    .is_err());
+    assert!(read(dir.path(), &HashMap::default(), &Params::new()).is_err());
+  }
+}
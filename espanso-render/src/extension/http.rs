@@ -0,0 +1,387 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::Result;
+use reqwest::{blocking::Client, Method};
+use thiserror::Error;
+
+use crate::{Extension, ExtensionOutput, ExtensionResult, Number, Params, Value};
+
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+
+pub struct HttpExtension {}
+
+#[allow(clippy::new_without_default)]
+impl HttpExtension {
+  pub fn new() -> Self {
+    Self {}
+  }
+
+  fn request(&self, params: &Params) -> Result<ExtensionOutput> {
+    let Some(Value::String(url)) = params.get("url") else {
+      return Err(HttpExtensionError::MissingUrlParameter.into());
+    };
+
+    let method = match params.get("method") {
+      Some(Value::String(method)) => Method::from_bytes(method.to_uppercase().as_bytes())
+        .map_err(|_| HttpExtensionError::InvalidMethod(method.clone()))?,
+      None => Method::GET,
+      Some(_) => return Err(HttpExtensionError::InvalidParameter("method").into()),
+    };
+
+    let timeout = match params.get("timeout") {
+      Some(Value::Number(Number::Integer(timeout))) if *timeout > 0 => {
+        Duration::from_millis(*timeout as u64)
+      }
+      None => Duration::from_millis(DEFAULT_TIMEOUT_MS),
+      Some(_) => return Err(HttpExtensionError::InvalidParameter("timeout").into()),
+    };
+
+    let client = Client::builder().timeout(timeout).build()?;
+    let mut request = client.request(method, url);
+
+    match params.get("headers") {
+      Some(Value::Object(headers)) => {
+        for (name, value) in headers {
+          let Value::String(value) = value else {
+            return Err(HttpExtensionError::InvalidParameter("headers").into());
+          };
+          request = request.header(name, value);
+        }
+      }
+      None => {}
+      Some(_) => return Err(HttpExtensionError::InvalidParameter("headers").into()),
+    }
+
+    match params.get("body") {
+      Some(Value::String(body)) => request = request.body(body.clone()),
+      None => {}
+      Some(_) => return Err(HttpExtensionError::InvalidParameter("body").into()),
+    }
+
+    let response = request.send()?;
+    let status = response.status();
+    let body = response.text()?;
+    if !status.is_success() {
+      return Err(HttpExtensionError::StatusError(status.as_u16()).into());
+    }
+
+    match params.get("extract") {
+      None => Ok(ExtensionOutput::Single(body)),
+      Some(Value::String(path)) => {
+        let json = parse_json(&body)?;
+        Ok(ExtensionOutput::Single(extract(&json, path)?))
+      }
+      Some(Value::Object(paths)) => {
+        let json = parse_json(&body)?;
+        let mut values = HashMap::new();
+        for (name, path) in paths {
+          let Value::String(path) = path else {
+            return Err(HttpExtensionError::InvalidParameter("extract").into());
+          };
+          values.insert(name.clone(), extract(&json, path)?);
+        }
+        Ok(ExtensionOutput::Multiple(values))
+      }
+      Some(_) => Err(HttpExtensionError::InvalidParameter("extract").into()),
+    }
+  }
+}
+
+impl Extension for HttpExtension {
+  fn name(&self) -> &str {
+    "http"
+  }
+
+  fn calculate(
+    &self,
+    _: &crate::Context,
+    _: &crate::Scope,
+    params: &Params,
+  ) -> crate::ExtensionResult {
+    match self.request(params) {
+      Ok(output) => ExtensionResult::Success(output),
+      Err(err) => ExtensionResult::Error(err),
+    }
+  }
+}
+
+fn parse_json(body: &str) -> Result<serde_json::Value> {
+  serde_json::from_str(body).map_err(|err| HttpExtensionError::InvalidJson(err).into())
+}
+
+// Extract the value at the given path, which can either be a JSON pointer
+// (such as "/items/0/name") or a jq-like path (such as ".items[0].name")
+fn extract(json: &serde_json::Value, path: &str) -> Result<String> {
+  let pointer = if path.is_empty() || path.starts_with('/') {
+    path.to_string()
+  } else {
+    jq_path_to_pointer(path)
+  };
+
+  match json.pointer(&pointer) {
+    Some(serde_json::Value::String(value)) => Ok(value.clone()),
+    Some(value) => Ok(value.to_string()),
+    None => Err(HttpExtensionError::MissingValue(path.to_string()).into()),
+  }
+}
+
+fn jq_path_to_pointer(path: &str) -> String {
+  let mut pointer = String::new();
+
+  for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+    // Array indexes are specified as "items[0]"
+    let (key, indexes) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+
+    if !key.is_empty() {
+      pointer.push('/');
+      pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+    }
+
+    for index in indexes.split(['[', ']']).filter(|index| !index.is_empty()) {
+      pointer.push('/');
+      pointer.push_str(index);
+    }
+  }
+
+  pointer
+}
+
+#[derive(Error, Debug)]
+pub enum HttpExtensionError {
+  #[error("missing 'url' parameter")]
+  MissingUrlParameter,
+
+  #[error("invalid '{0}' parameter")]
+  InvalidParameter(&'static str),
+
+  #[error("invalid HTTP method: {0}")]
+  InvalidMethod(String),
+
+  #[error("request failed with status code {0}")]
+  StatusError(u16),
+
+  #[error("response is not valid JSON: {0}")]
+  InvalidJson(serde_json::Error),
+
+  #[error("no value found in the response at path: {0}")]
+  MissingValue(String),
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    thread::JoinHandle,
+  };
+
+  // Serve a single request with the given response, returning the server
+  // url and a handle yielding the raw request received
+  fn serve(status: &str, body: &str) -> (String, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let response = format!(
+      "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+      body.len()
+    );
+
+    let handle = std::thread::spawn(move || {
+      let (mut stream, _) = listener.accept().unwrap();
+      let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+      let mut request = String::new();
+      let mut content_length = 0;
+      loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        if let Some((name, value)) = line.split_once(':') {
+          if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse().unwrap();
+          }
+        }
+        request.push_str(&line);
+        if line == "\r\n" {
+          break;
+        }
+      }
+
+      let mut body = vec![0; content_length];
+      reader.read_exact(&mut body).unwrap();
+      request.push_str(&String::from_utf8(body).unwrap());
+
+      stream.write_all(response.as_bytes()).unwrap();
+      request
+    });
+
+    (url, handle)
+  }
+
+  fn params(values: Vec<(&str, Value)>) -> Params {
+    values
+      .into_iter()
+      .map(|(key, value)| (key.to_string(), value))
+      .collect()
+  }
+
+  fn calculate(params: &Params) -> ExtensionResult {
+    HttpExtension::new().calculate(&crate::Context::default(), &HashMap::default(), params)
+  }
+
+  #[test]
+  fn get_returns_the_body() {
+    let (url, server) = serve("200 OK", "hello world");
+
+    let result = calculate(&params(vec![("url", Value::String(url))]));
+    assert_eq!(
+      result.into_success().unwrap(),
+      ExtensionOutput::Single("hello world".to_string())
+    );
+    assert!(server.join().unwrap().starts_with("GET / HTTP/1.1"));
+  }
+
+  #[test]
+  fn post_sends_headers_and_body() {
+    let (url, server) = serve("201 Created", "ok");
+
+    let headers = vec![("X-Custom".to_string(), Value::String("value".to_string()))]
+      .into_iter()
+      .collect();
+    let result = calculate(&params(vec![
+      ("url", Value::String(format!("{url}/items"))),
+      ("method", Value::String("post".to_string())),
+      ("headers", Value::Object(headers)),
+      ("body", Value::String("{\"name\": \"test\"}".to_string())),
+    ]));
+    assert_eq!(
+      result.into_success().unwrap(),
+      ExtensionOutput::Single("ok".to_string())
+    );
+
+    let request = server.join().unwrap().to_lowercase();
+    assert!(request.starts_with("post /items http/1.1"));
+    assert!(request.contains("x-custom: value"));
+    assert!(request.ends_with("{\"name\": \"test\"}"));
+  }
+
+  #[test]
+  fn extract_single_value() {
+    let (url, server) = serve("200 OK", r#"{"items": [{"name": "first"}]}"#);
+
+    let result = calculate(&params(vec![
+      ("url", Value::String(url)),
+      ("extract", Value::String(".items[0].name".to_string())),
+    ]));
+    assert_eq!(
+      result.into_success().unwrap(),
+      ExtensionOutput::Single("first".to_string())
+    );
+    server.join().unwrap();
+  }
+
+  #[test]
+  fn extract_multiple_values() {
+    let (url, server) = serve("200 OK", r#"{"name": "test", "stats": {"count": 42}}"#);
+
+    let paths = vec![
+      ("name".to_string(), Value::String("/name".to_string())),
+      (
+        "count".to_string(),
+        Value::String("stats.count".to_string()),
+      ),
+      ("stats".to_string(), Value::String("/stats".to_string())),
+    ]
+    .into_iter()
+    .collect();
+    let result = calculate(&params(vec![
+      ("url", Value::String(url)),
+      ("extract", Value::Object(paths)),
+    ]));
+
+    let expected = vec![
+      ("name".to_string(), "test".to_string()),
+      ("count".to_string(), "42".to_string()),
+      ("stats".to_string(), r#"{"count":42}"#.to_string()),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+      result.into_success().unwrap(),
+      ExtensionOutput::Multiple(expected)
+    );
+    server.join().unwrap();
+  }
+
+  #[test]
+  fn extract_missing_value() {
+    let (url, server) = serve("200 OK", r#"{"name": "test"}"#);
+
+    let result = calculate(&params(vec![
+      ("url", Value::String(url)),
+      ("extract", Value::String("/missing".to_string())),
+    ]));
+    assert!(matches!(result, ExtensionResult::Error(_)));
+    server.join().unwrap();
+  }
+
+  #[test]
+  fn non_success_status_is_an_error() {
+    let (url, server) = serve("404 Not Found", "not found");
+
+    let result = calculate(&params(vec![("url", Value::String(url))]));
+    assert_eq!(
+      result.into_error().unwrap().to_string(),
+      "request failed with status code 404"
+    );
+    server.join().unwrap();
+  }
+
+  #[test]
+  fn timeout_is_an_error() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    // The connection is accepted by the OS, but no response is ever sent
+    let result = calculate(&params(vec![
+      ("url", Value::String(url)),
+      ("timeout", Value::Number(Number::Integer(100))),
+    ]));
+    assert!(matches!(result, ExtensionResult::Error(_)));
+  }
+
+  #[test]
+  fn missing_url_parameter() {
+    let result = calculate(&Params::new());
+    assert!(matches!(result, ExtensionResult::Error(_)));
+  }
+
+  #[test]
+  fn jq_path_to_pointer_works_correctly() {
+    assert_eq!(jq_path_to_pointer(".name"), "/name");
+    assert_eq!(
+      jq_path_to_pointer("data.items[0].name"),
+      "/data/items/0/name"
+    );
+    assert_eq!(jq_path_to_pointer(".[1][2]"), "/1/2");
+    assert_eq!(jq_path_to_pointer(".a/b.c~d"), "/a~1b/c~0d");
+  }
+}
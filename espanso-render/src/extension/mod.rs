@@ -19,11 +19,17 @@
 
 pub mod choice;
 pub mod clipboard;
+pub mod counter;
 pub mod date;
 pub mod echo;
 mod exec_util;
+pub mod expr;
+pub mod file;
 pub mod form;
+pub mod http;
 pub mod random;
 pub mod script;
 pub mod shell;
+pub mod store;
+pub mod transform;
 mod util;
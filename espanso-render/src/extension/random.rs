@@ -17,10 +17,35 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::{Extension, ExtensionOutput, ExtensionResult, Params, Value};
-use rand::seq::SliceRandom;
+use anyhow::Result;
+use md5::Md5;
+use rand::{rngs::OsRng, seq::SliceRandom, Rng};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
+use ulid::Ulid;
+use uuid::Uuid;
 
+use super::transform::read_variable;
+use crate::{Extension, ExtensionOutput, ExtensionResult, Number, Params, Value};
+
+const HEX_CHARSET: &[u8] = b"0123456789abcdef";
+const BASE62_CHARSET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+const PASSWORD_CHARACTER_CLASSES: &[(&str, &[u8])] = &[
+  ("lowercase", b"abcdefghijklmnopqrstuvwxyz"),
+  ("uppercase", b"ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+  ("digits", b"0123456789"),
+  ("symbols", b"!#$%&()*+,-./:;<=>?@[]^_{|}~"),
+];
+
+const DEFAULT_HEX_LENGTH: usize = 32;
+const DEFAULT_BASE62_LENGTH: usize = 22;
+const DEFAULT_PASSWORD_LENGTH: usize = 16;
+const MAX_LENGTH: usize = 4096;
+
+/// Picks one of the given `choices` or, when the `generator` param is
+/// specified, generates a value with it. Random strings and passwords
+/// are generated with the OS secure random source.
 pub struct RandomExtension {}
 
 #[allow(clippy::new_without_default)]
@@ -38,25 +63,122 @@ impl Extension for RandomExtension {
   fn calculate(
     &self,
     _: &crate::Context,
-    _: &crate::Scope,
+    scope: &crate::Scope,
     params: &Params,
   ) -> crate::ExtensionResult {
-    if let Some(Value::Array(choices)) = params.get("choices") {
-      let choices: Vec<String> = choices
-        .iter()
-        .filter_map(|arg| arg.as_string())
-        .cloned()
-        .collect();
-
-      if let Some(choice) = choices.choose(&mut rand::thread_rng()) {
-        ExtensionResult::Success(ExtensionOutput::Single(choice.clone()))
-      } else {
-        ExtensionResult::Error(RandomExtensionError::SelectionError.into())
-      }
-    } else {
-      ExtensionResult::Error(RandomExtensionError::MissingChoicesParameter.into())
+    let result = match params.get("generator") {
+      Some(Value::String(generator)) => generate(generator, scope, params),
+      Some(_) => Err(RandomExtensionError::InvalidParameter("generator").into()),
+      None => choose(params),
+    };
+
+    match result {
+      Ok(value) => ExtensionResult::Success(ExtensionOutput::Single(value)),
+      Err(err) => ExtensionResult::Error(err),
+    }
+  }
+}
+
+fn choose(params: &Params) -> Result<String> {
+  if let Some(Value::Array(choices)) = params.get("choices") {
+    let choices: Vec<String> = choices
+      .iter()
+      .filter_map(|arg| arg.as_string())
+      .cloned()
+      .collect();
+
+    choices
+      .choose(&mut rand::thread_rng())
+      .cloned()
+      .ok_or_else(|| RandomExtensionError::SelectionError.into())
+  } else {
+    Err(RandomExtensionError::MissingChoicesParameter.into())
+  }
+}
+
+fn generate(generator: &str, scope: &crate::Scope, params: &Params) -> Result<String> {
+  match generator {
+    "uuid" | "uuid4" => Ok(Uuid::new_v4().to_string()),
+    "uuid7" => Ok(Uuid::now_v7().to_string()),
+    "ulid" => Ok(Ulid::new().to_string()),
+    "hex" => Ok(random_string(
+      HEX_CHARSET,
+      get_length(params, DEFAULT_HEX_LENGTH)?,
+    )),
+    "base62" => Ok(random_string(
+      BASE62_CHARSET,
+      get_length(params, DEFAULT_BASE62_LENGTH)?,
+    )),
+    "password" => generate_password(params),
+    "sha256" => Ok(hex::encode(Sha256::digest(
+      get_hash_input(scope, params)?.as_bytes(),
+    ))),
+    "md5" => Ok(hex::encode(Md5::digest(
+      get_hash_input(scope, params)?.as_bytes(),
+    ))),
+    _ => Err(RandomExtensionError::UnknownGenerator(generator.to_string()).into()),
+  }
+}
+
+fn get_length(params: &Params, default: usize) -> Result<usize> {
+  match params.get("length") {
+    Some(Value::Number(Number::Integer(length))) => usize::try_from(*length)
+      .ok()
+      .filter(|length| (1..=MAX_LENGTH).contains(length))
+      .ok_or_else(|| RandomExtensionError::InvalidParameter("length").into()),
+    None => Ok(default),
+    Some(_) => Err(RandomExtensionError::InvalidParameter("length").into()),
+  }
+}
+
+// The value to hash is either the output of another variable (`var` param)
+// or a string (`value` param), as in the transform extension
+fn get_hash_input(scope: &crate::Scope, params: &Params) -> Result<String> {
+  match (params.get("var"), params.get("value")) {
+    (Some(Value::String(var)), None) => read_variable(scope, var),
+    (None, Some(Value::String(value))) => Ok(value.clone()),
+    _ => Err(RandomExtensionError::MissingInputParameter.into()),
+  }
+}
+
+fn random_string(charset: &[u8], length: usize) -> String {
+  (0..length)
+    .map(|_| char::from(charset[OsRng.gen_range(0..charset.len())]))
+    .collect()
+}
+
+// Passwords contain at least one character of each enabled class
+// (`lowercase`, `uppercase`, `digits` and `symbols`, all enabled by default)
+fn generate_password(params: &Params) -> Result<String> {
+  let length = get_length(params, DEFAULT_PASSWORD_LENGTH)?;
+
+  let mut classes = Vec::new();
+  for (name, charset) in PASSWORD_CHARACTER_CLASSES {
+    match params.get(*name) {
+      Some(Value::Bool(false)) => {}
+      Some(Value::Bool(true)) | None => classes.push(*charset),
+      Some(_) => return Err(RandomExtensionError::InvalidParameter(name).into()),
     }
   }
+
+  if classes.is_empty() {
+    return Err(RandomExtensionError::NoCharacterClasses.into());
+  }
+  if length < classes.len() {
+    return Err(RandomExtensionError::PasswordTooShort(classes.len()).into());
+  }
+
+  let mut password: Vec<u8> = classes
+    .iter()
+    .map(|charset| charset[OsRng.gen_range(0..charset.len())])
+    .collect();
+  let charset = classes.concat();
+  while password.len() < length {
+    password.push(charset[OsRng.gen_range(0..charset.len())]);
+  }
+  password.shuffle(&mut OsRng);
+
+  Ok(password.into_iter().map(char::from).collect())
 }
 
 #[derive(Error, Debug)]
@@ -66,8 +188,22 @@ pub enum RandomExtensionError {
 
   #[error("could not select a choice randomly")]
   SelectionError,
-}
 
+  #[error("unknown generator: `{0}`")]
+  UnknownGenerator(String),
+
+  #[error("invalid '{0}' parameter")]
+  InvalidParameter(&'static str),
+
+  #[error("either the 'var' or the 'value' parameter must be specified")]
+  MissingInputParameter,
+
+  #[error("at least one password character class must be enabled")]
+  NoCharacterClasses,
+
+  #[error("password length must be at least {0} to include all character classes")]
+  PasswordTooShort(usize),
+}
 #[cfg(test)]
 mod tests {
   use std::collections::HashMap;
@@ -107,4 +243,131 @@ mod tests {
       ExtensionResult::Error(_)
     ));
   }
+
+  fn generate(params: Vec<(&str, Value)>) -> ExtensionResult {
+    let mut scope = crate::Scope::new();
+    scope.insert("name", ExtensionOutput::Single("hello".to_string()));
+
+    let params = params
+      .into_iter()
+      .map(|(key, value)| (key.to_string(), value))
+      .collect::<Params>();
+    RandomExtension::new().calculate(&crate::Context::default(), &scope, &params)
+  }
+
+  fn generate_single(params: Vec<(&str, Value)>) -> String {
+    match generate(params).into_success().unwrap() {
+      ExtensionOutput::Single(value) => value,
+      output => panic!("unexpected output: {output:?}"),
+    }
+  }
+
+  fn generator(name: &str) -> (&'static str, Value) {
+    ("generator", Value::String(name.to_string()))
+  }
+
+  #[test]
+  fn uuid_and_ulid() {
+    let uuid = Uuid::parse_str(&generate_single(vec![generator("uuid")])).unwrap();
+    assert_eq!(uuid.get_version_num(), 4);
+    let uuid = Uuid::parse_str(&generate_single(vec![generator("uuid7")])).unwrap();
+    assert_eq!(uuid.get_version_num(), 7);
+
+    let ulid = generate_single(vec![generator("ulid")]);
+    assert_eq!(ulid.len(), 26);
+    assert!(Ulid::from_string(&ulid).is_ok());
+  }
+
+  #[test]
+  fn random_strings() {
+    let hex = generate_single(vec![generator("hex")]);
+    assert_eq!(hex.len(), DEFAULT_HEX_LENGTH);
+    assert!(hex.bytes().all(|c| HEX_CHARSET.contains(&c)));
+
+    let base62 = generate_single(vec![
+      generator("base62"),
+      ("length", Value::Number(Number::Integer(50))),
+    ]);
+    assert_eq!(base62.len(), 50);
+    assert!(base62.bytes().all(|c| c.is_ascii_alphanumeric()));
+
+    assert!(generate(vec![
+      generator("hex"),
+      ("length", Value::Number(Number::Integer(0)))
+    ])
+    .into_error()
+    .is_ok());
+  }
+
+  #[test]
+  fn password() {
+    let password = generate_single(vec![generator("password")]);
+    assert_eq!(password.len(), DEFAULT_PASSWORD_LENGTH);
+
+    for _ in 0..20 {
+      let password = generate_single(vec![
+        generator("password"),
+        ("length", Value::Number(Number::Integer(4))),
+      ]);
+      for (_, charset) in PASSWORD_CHARACTER_CLASSES {
+        assert!(password.bytes().any(|c| charset.contains(&c)));
+      }
+    }
+
+    let pin = generate_single(vec![
+      generator("password"),
+      ("length", Value::Number(Number::Integer(6))),
+      ("lowercase", Value::Bool(false)),
+      ("uppercase", Value::Bool(false)),
+      ("symbols", Value::Bool(false)),
+    ]);
+    assert_eq!(pin.len(), 6);
+    assert!(pin.bytes().all(|c| c.is_ascii_digit()));
+
+    assert!(generate(vec![
+      generator("password"),
+      ("length", Value::Number(Number::Integer(3)))
+    ])
+    .into_error()
+    .is_ok());
+    assert!(generate(vec![
+      generator("password"),
+      ("lowercase", Value::Bool(false)),
+      ("uppercase", Value::Bool(false)),
+      ("digits", Value::Bool(false)),
+      ("symbols", Value::Bool(false)),
+    ])
+    .into_error()
+    .is_ok());
+  }
+
+  #[test]
+  fn hashes() {
+    assert_eq!(
+      generate_single(vec![
+        generator("sha256"),
+        ("value", Value::String("hello".to_string()))
+      ]),
+      "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+    );
+    assert_eq!(
+      generate_single(vec![
+        generator("md5"),
+        ("var", Value::String("name".to_string()))
+      ]),
+      "5d41402abc4b2a76b9719d911017c592"
+    );
+    assert!(generate(vec![generator("md5")]).into_error().is_ok());
+    assert!(generate(vec![
+      generator("md5"),
+      ("var", Value::String("missing".to_string()))
+    ])
+    .into_error()
+    .is_ok());
+  }
+
+  #[test]
+  fn unknown_generator() {
+    assert!(generate(vec![generator("uuid9")]).into_error().is_ok());
+  }
 }
@@ -0,0 +1,238 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use thiserror::Error;
+
+use crate::{Extension, ExtensionOutput, ExtensionResult, Params, Value};
+
+pub trait ValueStore {
+  fn get(&self, key: &str) -> Result<Option<String>>;
+  fn set(&self, key: &str, value: &str) -> Result<()>;
+}
+
+/// Persists the `value` param under the given `key`, producing the value itself
+pub struct StoreExtension<'a> {
+  store: &'a dyn ValueStore,
+}
+
+#[allow(clippy::new_without_default)]
+impl<'a> StoreExtension<'a> {
+  pub fn new(store: &'a dyn ValueStore) -> Self {
+    Self { store }
+  }
+}
+
+impl<'a> Extension for StoreExtension<'a> {
+  fn name(&self) -> &str {
+    "store"
+  }
+
+  fn calculate(
+    &self,
+    _: &crate::Context,
+    _: &crate::Scope,
+    params: &Params,
+  ) -> crate::ExtensionResult {
+    let Some(Value::String(key)) = params.get("key") else {
+      return ExtensionResult::Error(StoreExtensionError::MissingKeyParameter.into());
+    };
+    let Some(Value::String(value)) = params.get("value") else {
+      return ExtensionResult::Error(StoreExtensionError::MissingValueParameter.into());
+    };
+
+    match self.store.set(key, value) {
+      Ok(()) => ExtensionResult::Success(ExtensionOutput::Single(value.clone())),
+      Err(err) => ExtensionResult::Error(err),
+    }
+  }
+}
+
+/// Reads the value stored under the given `key`, falling back to the `default` param
+pub struct RecallExtension<'a> {
+  store: &'a dyn ValueStore,
+}
+
+#[allow(clippy::new_without_default)]
+impl<'a> RecallExtension<'a> {
+  pub fn new(store: &'a dyn ValueStore) -> Self {
+    Self { store }
+  }
+}
+
+impl<'a> Extension for RecallExtension<'a> {
+  fn name(&self) -> &str {
+    "recall"
+  }
+
+  fn calculate(
+    &self,
+    _: &crate::Context,
+    _: &crate::Scope,
+    params: &Params,
+  ) -> crate::ExtensionResult {
+    let Some(Value::String(key)) = params.get("key") else {
+      return ExtensionResult::Error(StoreExtensionError::MissingKeyParameter.into());
+    };
+
+    match (self.store.get(key), params.get("default")) {
+      (Ok(Some(value)), _) => ExtensionResult::Success(ExtensionOutput::Single(value)),
+      (Ok(None), Some(Value::String(default))) => {
+        ExtensionResult::Success(ExtensionOutput::Single(default.clone()))
+      }
+      (Ok(None), _) => {
+        ExtensionResult::Error(StoreExtensionError::MissingValue(key.clone()).into())
+      }
+      (Err(err), _) => ExtensionResult::Error(err),
+    }
+  }
+}
+
+#[derive(Error, Debug)]
+pub enum StoreExtensionError {
+  #[error("missing 'key' parameter")]
+  MissingKeyParameter,
+
+  #[error("missing 'value' parameter")]
+  MissingValueParameter,
+
+  #[error("no value stored for key '{0}'")]
+  MissingValue(String),
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{cell::RefCell, collections::HashMap};
+
+  use super::*;
+
+  #[derive(Default)]
+  struct MockValueStore {
+    values: RefCell<HashMap<String, String>>,
+  }
+
+  impl ValueStore for MockValueStore {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+      Ok(self.values.borrow().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<()> {
+      self
+        .values
+        .borrow_mut()
+        .insert(key.to_string(), value.to_string());
+      Ok(())
+    }
+  }
+
+  fn params(values: Vec<(&str, &str)>) -> Params {
+    values
+      .into_iter()
+      .map(|(key, value)| (key.to_string(), Value::String(value.to_string())))
+      .collect()
+  }
+
+  #[test]
+  fn store_and_recall() {
+    let store = MockValueStore::default();
+    let store_extension = StoreExtension::new(&store);
+    let recall_extension = RecallExtension::new(&store);
+
+    assert_eq!(
+      store_extension
+        .calculate(
+          &crate::Context::default(),
+          &HashMap::default(),
+          &params(vec![("key", "client"), ("value", "ACME")])
+        )
+        .into_success()
+        .unwrap(),
+      ExtensionOutput::Single("ACME".to_string())
+    );
+    assert_eq!(
+      recall_extension
+        .calculate(
+          &crate::Context::default(),
+          &HashMap::default(),
+          &params(vec![("key", "client"), ("default", "none")])
+        )
+        .into_success()
+        .unwrap(),
+      ExtensionOutput::Single("ACME".to_string())
+    );
+  }
+
+  #[test]
+  fn recall_default() {
+    let store = MockValueStore::default();
+    let extension = RecallExtension::new(&store);
+
+    assert_eq!(
+      extension
+        .calculate(
+          &crate::Context::default(),
+          &HashMap::default(),
+          &params(vec![("key", "client"), ("default", "none")])
+        )
+        .into_success()
+        .unwrap(),
+      ExtensionOutput::Single("none".to_string())
+    );
+    assert!(matches!(
+      extension.calculate(
+        &crate::Context::default(),
+        &HashMap::default(),
+        &params(vec![("key", "client")])
+      ),
+      ExtensionResult::Error(_)
+    ));
+  }
+
+  #[test]
+  fn missing_parameters() {
+    let store = MockValueStore::default();
+    let store_extension = StoreExtension::new(&store);
+    let recall_extension = RecallExtension::new(&store);
+
+    assert!(matches!(
+      store_extension.calculate(
+        &crate::Context::default(),
+        &HashMap::default(),
+        &params(vec![("key", "client")])
+      ),
+      ExtensionResult::Error(_)
+    ));
+    assert!(matches!(
+      store_extension.calculate(
+        &crate::Context::default(),
+        &HashMap::default(),
+        &params(vec![("value", "ACME")])
+      ),
+      ExtensionResult::Error(_)
+    ));
+    assert!(matches!(
+      recall_extension.calculate(
+        &crate::Context::default(),
+        &HashMap::default(),
+        &Params::new()
+      ),
+      ExtensionResult::Error(_)
+    ));
+  }
+}
@@ -0,0 +1,474 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use heck::{ToKebabCase, ToLowerCamelCase, ToSnakeCase};
+use lazy_static::lazy_static;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use regex::{Captures, Regex};
+use thiserror::Error;
+
+use crate::{Extension, ExtensionOutput, ExtensionResult, Number, Params, Value};
+
+lazy_static! {
+  static ref WORD_REGEX: Regex = Regex::new(r"(\w+)").unwrap();
+}
+
+// Characters left untouched by the url encoding, as defined by RFC 3986
+const URL_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+  .remove(b'-')
+  .remove(b'_')
+  .remove(b'.')
+  .remove(b'~');
+
+/// Applies a chain of transformations to the output of another variable
+/// (`var` param, such as "form1.name") or to a string (`value` param).
+/// When transforming a global variable, it must be listed in `depends_on`.
+pub struct TransformExtension {}
+
+#[allow(clippy::new_without_default)]
+impl TransformExtension {
+  pub fn new() -> Self {
+    Self {}
+  }
+
+  fn transform(&self, scope: &crate::Scope, params: &Params) -> Result<String> {
+    let mut value = match (params.get("var"), params.get("value")) {
+      (Some(Value::String(var)), None) => read_variable(scope, var)?,
+      (None, Some(Value::String(value))) => value.clone(),
+      _ => return Err(TransformExtensionError::MissingInputParameter.into()),
+    };
+
+    let transformations = match params.get("filters") {
+      Some(Value::Array(filters)) => filters
+        .iter()
+        .map(Transformation::from_value)
+        .collect::<Result<Vec<_>>>()?,
+      None => Vec::new(),
+      Some(_) => return Err(TransformExtensionError::InvalidFiltersParameter.into()),
+    };
+
+    for transformation in &transformations {
+      value = transformation.apply(&value)?;
+    }
+
+    Ok(value)
+  }
+}
+
+impl Extension for TransformExtension {
+  fn name(&self) -> &str {
+    "transform"
+  }
+
+  fn calculate(
+    &self,
+    _: &crate::Context,
+    scope: &crate::Scope,
+    params: &Params,
+  ) -> crate::ExtensionResult {
+    match self.transform(scope, params) {
+      Ok(value) => ExtensionResult::Success(ExtensionOutput::Single(value)),
+      Err(err) => ExtensionResult::Error(err),
+    }
+  }
+}
+
+pub(crate) fn read_variable(scope: &crate::Scope, var: &str) -> Result<String> {
+  let (name, subname) = match var.split_once('.') {
+    Some((name, subname)) => (name, Some(subname)),
+    None => (var, None),
+  };
+
+  let value = match (scope.get(name), subname) {
+    (Some(ExtensionOutput::Single(value)), None) => Some(value),
+    (Some(ExtensionOutput::Multiple(values)), Some(subname)) => values.get(subname),
+    (Some(ExtensionOutput::Structured { value, .. }), None) => Some(value),
+    (Some(ExtensionOutput::Structured { fields, .. }), Some(subname)) => fields.get(subname),
+    _ => None,
+  };
+
+  value
+    .cloned()
+    .ok_or_else(|| TransformExtensionError::MissingVariable(var.to_string()).into())
+}
+
+#[derive(Debug)]
+pub(crate) enum Transformation {
+  Upper,
+  Lower,
+  Capitalize,
+  Title,
+  Snake,
+  Camel,
+  Kebab,
+  Trim,
+  UrlEncode,
+  Base64Encode,
+  Base64Decode,
+  RegexReplace { regex: Regex, replacement: String },
+  Substring { start: usize, length: Option<usize> },
+}
+
+impl Transformation {
+  /// Return the transformation with the given name, if it doesn't require any argument
+  pub(crate) fn from_name(name: &str) -> Option<Self> {
+    Some(match name {
+      "upper" => Transformation::Upper,
+      "lower" => Transformation::Lower,
+      "capitalize" => Transformation::Capitalize,
+      "title" | "capitalize_words" => Transformation::Title,
+      "snake" => Transformation::Snake,
+      "camel" => Transformation::Camel,
+      "kebab" => Transformation::Kebab,
+      "trim" => Transformation::Trim,
+      "url_encode" => Transformation::UrlEncode,
+      "base64_encode" => Transformation::Base64Encode,
+      "base64_decode" => Transformation::Base64Decode,
+      _ => return None,
+    })
+  }
+
+  // Filters are either specified by name (`- upper`) or, when they
+  // need arguments, as a single-key map (`- substring: { start: 2 }`)
+  fn from_value(value: &Value) -> Result<Self> {
+    let (name, args) = match value {
+      Value::String(name) => {
+        return Self::from_name(name)
+          .ok_or_else(|| TransformExtensionError::UnknownFilter(name.clone()).into())
+      }
+      Value::Object(fields) if fields.len() == 1 => fields.iter().next().unwrap(),
+      _ => return Err(TransformExtensionError::InvalidFiltersParameter.into()),
+    };
+
+    let empty = HashMap::new();
+    let args = match args {
+      Value::Object(args) => args,
+      Value::Null => &empty,
+      _ => return Err(TransformExtensionError::InvalidFilterArguments(name.clone()).into()),
+    };
+    let invalid_arguments = || TransformExtensionError::InvalidFilterArguments(name.clone());
+
+    match name.as_str() {
+      "regex_replace" => {
+        let Some(Value::String(pattern)) = args.get("pattern") else {
+          return Err(invalid_arguments().into());
+        };
+        let replacement = match args.get("replacement") {
+          Some(Value::String(replacement)) => replacement.clone(),
+          None => String::new(),
+          Some(_) => return Err(invalid_arguments().into()),
+        };
+        Ok(Transformation::RegexReplace {
+          regex: Regex::new(pattern)?,
+          replacement,
+        })
+      }
+      "substring" => {
+        let as_index = |value: Option<&Value>| match value {
+          Some(Value::Number(Number::Integer(index))) if *index >= 0 => Ok(Some(*index as usize)),
+          None => Ok(None),
+          Some(_) => Err(invalid_arguments()),
+        };
+        Ok(Transformation::Substring {
+          start: as_index(args.get("start"))?.unwrap_or(0),
+          length: as_index(args.get("length"))?,
+        })
+      }
+      _ => Self::from_name(name)
+        .ok_or_else(|| TransformExtensionError::UnknownFilter(name.clone()).into()),
+    }
+  }
+
+  pub(crate) fn apply(&self, value: &str) -> Result<String> {
+    Ok(match self {
+      Transformation::Upper => value.to_uppercase(),
+      Transformation::Lower => value.to_lowercase(),
+      Transformation::Capitalize => capitalize(value),
+      Transformation::Title => capitalize_words(value),
+      Transformation::Snake => value.to_snake_case(),
+      Transformation::Camel => value.to_lower_camel_case(),
+      Transformation::Kebab => value.to_kebab_case(),
+      Transformation::Trim => value.trim().to_string(),
+      Transformation::UrlEncode => utf8_percent_encode(value, URL_ENCODE_SET).to_string(),
+      Transformation::Base64Encode => BASE64.encode(value),
+      Transformation::Base64Decode => {
+        let decoded = BASE64
+          .decode(value.trim())
+          .map_err(|_| TransformExtensionError::InvalidBase64)?;
+        String::from_utf8(decoded).map_err(|_| TransformExtensionError::InvalidBase64)?
+      }
+      Transformation::RegexReplace { regex, replacement } => {
+        regex.replace_all(value, replacement.as_str()).to_string()
+      }
+      Transformation::Substring { start, length } => {
+        let chars = value.chars().skip(*start);
+        match length {
+          Some(length) => chars.take(*length).collect(),
+          None => chars.collect(),
+        }
+      }
+    })
+  }
+}
+
+// Capitalize the first letter
+pub(crate) fn capitalize(text: &str) -> String {
+  let mut chars = text.chars();
+  match chars.next() {
+    Some(first) => first.to_uppercase().take(1).chain(chars).collect(),
+    None => String::new(),
+  }
+}
+
+// Capitalize the first letter of each word
+pub(crate) fn capitalize_words(text: &str) -> String {
+  WORD_REGEX
+    .replace_all(text, |caps: &Captures| capitalize(&caps[0]))
+    .to_string()
+}
+
+#[derive(Error, Debug)]
+pub enum TransformExtensionError {
+  #[error("either the 'var' or the 'value' parameter must be specified")]
+  MissingInputParameter,
+
+  #[error("invalid 'filters' parameter")]
+  InvalidFiltersParameter,
+
+  #[error("missing variable: {0}")]
+  MissingVariable(String),
+
+  #[error("unknown filter: {0}")]
+  UnknownFilter(String),
+
+  #[error("invalid arguments for filter: {0}")]
+  InvalidFilterArguments(String),
+
+  #[error("value is not valid base64")]
+  InvalidBase64,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn transform(value: &str, filters: Vec<Value>) -> Result<String> {
+    let extension = TransformExtension::new();
+    let params = vec![
+      ("value".to_string(), Value::String(value.to_string())),
+      ("filters".to_string(), Value::Array(filters)),
+    ]
+    .into_iter()
+    .collect::<Params>();
+    match extension.calculate(&crate::Context::default(), &HashMap::default(), &params) {
+      ExtensionResult::Success(ExtensionOutput::Single(value)) => Ok(value),
+      ExtensionResult::Error(err) => Err(err),
+      result => panic!("unexpected result: {result:?}"),
+    }
+  }
+
+  fn filter(name: &str) -> Value {
+    Value::String(name.to_string())
+  }
+
+  fn filter_with_args(name: &str, args: Vec<(&str, Value)>) -> Value {
+    let args = args
+      .into_iter()
+      .map(|(key, value)| (key.to_string(), value))
+      .collect();
+    Value::Object(
+      vec![(name.to_string(), Value::Object(args))]
+        .into_iter()
+        .collect(),
+    )
+  }
+
+  #[test]
+  fn case_filters() {
+    assert_eq!(
+      transform("Hello World", vec![filter("upper")]).unwrap(),
+      "HELLO WORLD"
+    );
+    assert_eq!(
+      transform("Hello World", vec![filter("lower")]).unwrap(),
+      "hello world"
+    );
+    assert_eq!(
+      transform("hello world", vec![filter("capitalize")]).unwrap(),
+      "Hello world"
+    );
+    assert_eq!(
+      transform("hello big-world", vec![filter("title")]).unwrap(),
+      "Hello Big-World"
+    );
+    assert_eq!(
+      transform("Hello big World", vec![filter("snake")]).unwrap(),
+      "hello_big_world"
+    );
+    assert_eq!(
+      transform("hello big world", vec![filter("camel")]).unwrap(),
+      "helloBigWorld"
+    );
+    assert_eq!(
+      transform("HelloBigWorld", vec![filter("kebab")]).unwrap(),
+      "hello-big-world"
+    );
+  }
+
+  #[test]
+  fn encoding_filters() {
+    assert_eq!(
+      transform("a b&c/d~e", vec![filter("url_encode")]).unwrap(),
+      "a%20b%26c%2Fd~e"
+    );
+    assert_eq!(
+      transform("espanso", vec![filter("base64_encode")]).unwrap(),
+      "ZXNwYW5zbw=="
+    );
+    assert_eq!(
+      transform("ZXNwYW5zbw==", vec![filter("base64_decode")]).unwrap(),
+      "espanso"
+    );
+    assert!(transform("not base64!", vec![filter("base64_decode")]).is_err());
+  }
+
+  #[test]
+  fn filters_with_arguments() {
+    assert_eq!(
+      transform(
+        "  2021-03-04 ",
+        vec![
+          filter("trim"),
+          filter_with_args(
+            "regex_replace",
+            vec![
+              ("pattern", Value::String(r"(\d+)-(\d+)-(\d+)".to_string())),
+              ("replacement", Value::String("$3/$2/$1".to_string())),
+            ]
+          ),
+        ]
+      )
+      .unwrap(),
+      "04/03/2021"
+    );
+    assert_eq!(
+      transform(
+        "espanso",
+        vec![filter_with_args(
+          "substring",
+          vec![
+            ("start", Value::Number(Number::Integer(1))),
+            ("length", Value::Number(Number::Integer(3)))
+          ]
+        )]
+      )
+      .unwrap(),
+      "spa"
+    );
+    assert_eq!(
+      transform(
+        "espanso",
+        vec![filter_with_args(
+          "substring",
+          vec![("start", Value::Number(Number::Integer(4)))]
+        )]
+      )
+      .unwrap(),
+      "nso"
+    );
+  }
+
+  #[test]
+  fn invalid_filters() {
+    assert!(transform("test", vec![filter("unknown")]).is_err());
+    assert!(transform("test", vec![filter_with_args("regex_replace", vec![])]).is_err());
+    assert!(transform(
+      "test",
+      vec![filter_with_args(
+        "substring",
+        vec![("start", Value::Number(Number::Integer(-1)))]
+      )]
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn transform_variable_from_scope() {
+    let extension = TransformExtension::new();
+    let mut scope = crate::Scope::new();
+    scope.insert("title", ExtensionOutput::Single("My Blog Post".to_string()));
+    let fields = vec![("name".to_string(), "John Snow".to_string())]
+      .into_iter()
+      .collect();
+    scope.insert("form1", ExtensionOutput::Multiple(fields));
+
+    let params = vec![
+      ("var".to_string(), Value::String("title".to_string())),
+      ("filters".to_string(), Value::Array(vec![filter("kebab")])),
+    ]
+    .into_iter()
+    .collect::<Params>();
+    assert_eq!(
+      extension
+        .calculate(&crate::Context::default(), &scope, &params)
+        .into_success()
+        .unwrap(),
+      ExtensionOutput::Single("my-blog-post".to_string())
+    );
+
+    let params = vec![
+      ("var".to_string(), Value::String("form1.name".to_string())),
+      ("filters".to_string(), Value::Array(vec![filter("upper")])),
+    ]
+    .into_iter()
+    .collect::<Params>();
+    assert_eq!(
+      extension
+        .calculate(&crate::Context::default(), &scope, &params)
+        .into_success()
+        .unwrap(),
+      ExtensionOutput::Single("JOHN SNOW".to_string())
+    );
+  }
+
+  #[test]
+  fn missing_input() {
+    let extension = TransformExtension::new();
+    assert!(matches!(
+      extension.calculate(
+        &crate::Context::default(),
+        &HashMap::default(),
+        &Params::new()
+      ),
+      ExtensionResult::Error(_)
+    ));
+
+    let params = vec![("var".to_string(), Value::String("missing".to_string()))]
+      .into_iter()
+      .collect::<Params>();
+    assert!(matches!(
+      extension.calculate(&crate::Context::default(), &HashMap::default(), &params),
+      ExtensionResult::Error(_)
+    ));
+  }
+}
@@ -35,6 +35,13 @@ pub fn convert_to_env_variables(scope: &Scope) -> HashMap<String, String> {
           output.insert(name, sub_value.clone());
         }
       }
+      ExtensionOutput::Structured { value, fields } => {
+        output.insert(format!("ESPANSO_{}", key.to_uppercase()), value.clone());
+        for (sub_key, sub_value) in fields {
+          let name = format!("ESPANSO_{}_{}", key.to_uppercase(), sub_key.to_uppercase());
+          output.insert(name, sub_value.clone());
+        }
+      }
     }
   }
 
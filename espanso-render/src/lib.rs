@@ -116,6 +116,13 @@ pub enum Number {
 pub trait Extension {
   fn name(&self) -> &str;
   fn calculate(&self, context: &Context, scope: &Scope, params: &Params) -> ExtensionResult;
+
+  /// Return the names of the variables read by the extension that are not
+  /// referenced in the params with the {{var}} syntax, so that they can be
+  /// evaluated first.
+  fn variable_dependencies(&self, _params: &Params) -> Vec<String> {
+    Vec::new()
+  }
 }
 
 pub type Scope<'a> = HashMap<&'a str, ExtensionOutput>;
@@ -124,6 +131,12 @@ pub type Scope<'a> = HashMap<&'a str, ExtensionOutput>;
 pub enum ExtensionOutput {
   Single(String),
   Multiple(HashMap<String, String>),
+  // A single value exposing some nested fields, such as a typed regex capture:
+  // {{date}} is replaced with the value, {{date.year}} with one of the fields
+  Structured {
+    value: String,
+    fields: HashMap<String, String>,
+  },
 }
 
 #[derive(Debug, EnumAsInner)]
@@ -20,23 +20,24 @@
 use std::{borrow::Cow, collections::HashMap};
 
 use crate::{
+  extension::transform::{capitalize, capitalize_words},
   CasingStyle, Context, Extension, ExtensionOutput, ExtensionResult, RenderOptions, RenderResult,
   Renderer, Scope, Template, Value, Variable,
 };
 use lazy_static::lazy_static;
 use log::{error, warn};
-use regex::{Captures, Regex};
+use regex::Regex;
 use thiserror::Error;
 
-use self::util::{inject_variables_into_params, render_variables};
+use self::util::inject_variables_into_params;
 
 mod resolve;
+pub(crate) mod template;
 mod util;
 
 lazy_static! {
   pub(crate) static ref VAR_REGEX: Regex =
     Regex::new(r"\{\{\s*((?P<name>\w+)(\.(?P<subname>(\w+)))?)\s*\}\}").unwrap();
-  static ref WORD_REGEX: Regex = Regex::new(r"(\w+)").unwrap();
 }
 
 pub(crate) struct DefaultRenderer<'a> {
@@ -60,7 +61,7 @@ impl<'a> Renderer for DefaultRenderer<'a> {
     context: &Context,
     options: &RenderOptions,
   ) -> RenderResult {
-    let body = if VAR_REGEX.is_match(&template.body) {
+    let body = if template::is_template(&template.body) {
       // Convert "global" variable type aliases when needed
       let local_variables: Vec<&Variable> =
         if template.vars.iter().any(|var| var.var_type == "global") {
@@ -90,6 +91,7 @@ impl<'a> Renderer for DefaultRenderer<'a> {
         &template.body,
         &local_variables,
         &context.global_vars,
+        &self.extensions,
       ) {
         Ok(variables) => variables,
         Err(err) => return RenderResult::Error(err),
@@ -166,7 +168,7 @@ impl<'a> Renderer for DefaultRenderer<'a> {
       }
 
       // Replace the variables
-      match render_variables(&template.body, &scope) {
+      match template::render_template(&template.body, &scope) {
         Ok(output) => output,
         Err(error) => {
           return RenderResult::Error(error);
@@ -182,27 +184,8 @@ impl<'a> Renderer for DefaultRenderer<'a> {
     let body_with_casing = match options.casing_style {
       CasingStyle::None => body,
       CasingStyle::Uppercase => body.to_uppercase(),
-      CasingStyle::Capitalize => {
-        // Capitalize the first letter
-        let mut v: Vec<char> = body.chars().collect();
-        v[0] = v[0].to_uppercase().next().unwrap();
-        v.into_iter().collect()
-      }
-      CasingStyle::CapitalizeWords => {
-        // Capitalize the first letter of each word
-        WORD_REGEX
-          .replace_all(&body, |caps: &Captures| {
-            if let Some(word_match) = caps.get(0) {
-              let mut v: Vec<char> = word_match.as_str().chars().collect();
-              v[0] = v[0].to_uppercase().next().unwrap();
-              let capitalized_word: String = v.into_iter().collect();
-              capitalized_word
-            } else {
-              String::new()
-            }
-          })
-          .to_string()
-      }
+      CasingStyle::Capitalize => capitalize(&body),
+      CasingStyle::CapitalizeWords => capitalize_words(&body),
     };
 
     RenderResult::Success(body_with_casing)
@@ -253,6 +236,9 @@ pub enum RendererError {
 
   #[error("circular dependency: `{0}` -> `{1}`")]
   CircularDependency(String, String),
+
+  #[error("invalid template: {0}")]
+  InvalidTemplate(String),
 }
 
 #[cfg(test)]
@@ -621,6 +607,76 @@ mod tests {
     assert!(matches!(res, RenderResult::Aborted));
   }
 
+  #[test]
+  fn global_variable_read_by_expr() {
+    let expr_extension = crate::extension::expr::ExprExtension::new();
+    let renderer = DefaultRenderer::new(vec![&MockExtension {}, &expr_extension]);
+    let template = Template {
+      body: "total {{total}}".to_string(),
+      vars: vec![Variable {
+        name: "total".to_string(),
+        var_type: "expr".to_string(),
+        params: Params::from_iter(vec![(
+          "expr".to_string(),
+          Value::String("qty * 2".to_string()),
+        )]),
+        ..Default::default()
+      }],
+      ..Default::default()
+    };
+    let res = renderer.render(
+      &template,
+      &Context {
+        global_vars: vec![&Variable {
+          name: "qty".to_string(),
+          var_type: "mock".to_string(),
+          params: Params::from_iter(vec![("echo".to_string(), Value::String("3".to_string()))]),
+          ..Default::default()
+        }],
+        ..Default::default()
+      },
+      &RenderOptions::default(),
+    );
+    assert!(matches!(res, RenderResult::Success(str) if str == "total 6"));
+  }
+
+  #[test]
+  fn global_variable_read_by_injected_file() {
+    let dir = tempdir::TempDir::new("espanso-render").unwrap();
+    std::fs::write(dir.path().join("letter.txt"), "Dear {{name}}").unwrap();
+    let file_extension = crate::extension::file::FileExtension::new(dir.path());
+    let renderer = DefaultRenderer::new(vec![&MockExtension {}, &file_extension]);
+    let res = renderer.render(
+      &template_for_str("{{letter}}"),
+      &Context {
+        // The file variable is declared before the variable it uses
+        global_vars: vec![
+          &Variable {
+            name: "letter".to_string(),
+            var_type: "file".to_string(),
+            params: Params::from_iter(vec![
+              ("path".to_string(), Value::String("letter.txt".to_string())),
+              ("inject_vars".to_string(), Value::Bool(true)),
+            ]),
+            ..Default::default()
+          },
+          &Variable {
+            name: "name".to_string(),
+            var_type: "mock".to_string(),
+            params: Params::from_iter(vec![(
+              "echo".to_string(),
+              Value::String("John".to_string()),
+            )]),
+            ..Default::default()
+          },
+        ],
+        ..Default::default()
+      },
+      &RenderOptions::default(),
+    );
+    assert!(matches!(res, RenderResult::Success(str) if str == "Dear John"));
+  }
+
   #[test]
   fn local_variable_explicit_ordering() {
     let renderer = get_renderer();
@@ -941,6 +997,33 @@ mod tests {
     assert!(matches!(res, RenderResult::Success(str) if str == "hello local"));
   }
 
+  #[test]
+  fn if_block() {
+    let renderer = get_renderer();
+    let template = template(
+      "{{#if var}}hello {{var|upper}}{{else}}nobody{{/if}}",
+      &[("var", "world")],
+    );
+    let res = renderer.render(&template, &Context::default(), &RenderOptions::default());
+    assert!(matches!(res, RenderResult::Success(str) if str == "hello WORLD"));
+  }
+
+  #[test]
+  fn each_block() {
+    let renderer = get_renderer();
+    let template = template("{{#each var}}[{{this}}]{{/each}}", &[("var", "one\ntwo")]);
+    let res = renderer.render(&template, &Context::default(), &RenderOptions::default());
+    assert!(matches!(res, RenderResult::Success(str) if str == "[one][two]"));
+  }
+
+  #[test]
+  fn invalid_template_block() {
+    let renderer = get_renderer();
+    let template = template("{{#if var}}hello", &[("var", "world")]);
+    let res = renderer.render(&template, &Context::default(), &RenderOptions::default());
+    assert!(matches!(res, RenderResult::Error(_)));
+  }
+
   #[test]
   fn variable_escape() {
     let renderer = get_renderer();
@@ -18,6 +18,7 @@
  */
 
 use std::{
+  borrow::Cow,
   cell::RefCell,
   collections::{HashMap, HashSet},
 };
@@ -25,22 +26,23 @@ use std::{
 use anyhow::{anyhow, Result};
 use log::error;
 
-use crate::Variable;
+use crate::{Extension, Variable};
 
 use super::RendererError;
 
 struct Node<'a> {
   name: &'a str,
   variable: Option<&'a Variable>,
-  dependencies: Option<HashSet<&'a str>>,
+  dependencies: HashSet<Cow<'a, str>>,
 }
 
 pub(crate) fn resolve_evaluation_order<'a>(
   body: &'a str,
   local_vars: &'a [&'a Variable],
   global_vars: &'a [&'a Variable],
+  extensions: &HashMap<String, &dyn Extension>,
 ) -> Result<Vec<&'a Variable>> {
-  let node_map = generate_nodes(body, local_vars, global_vars);
+  let node_map = generate_nodes(body, local_vars, global_vars, extensions);
 
   let body_node = node_map
     .get("__match_body")
@@ -72,42 +74,43 @@ fn generate_nodes<'a>(
   body: &'a str,
   local_vars: &'a [&'a Variable],
   global_vars: &'a [&'a Variable],
+  extensions: &HashMap<String, &dyn Extension>,
 ) -> HashMap<&'a str, Node<'a>> {
   let mut local_vars_nodes = Vec::new();
   for (index, var) in local_vars.iter().enumerate() {
-    let mut dependencies = HashSet::new();
-    if var.inject_vars {
-      dependencies.extend(super::util::get_params_variable_names(&var.params));
-    }
-    dependencies.extend(var.depends_on.iter().map(String::as_str));
+    let mut node = create_node_from_var(var, extensions);
 
     // Every local variable depends on the one before it.
     // Needed to guarantee execution order within local vars.
     if index > 0 {
       let previous_var = local_vars.get(index - 1);
       if let Some(previous_var) = previous_var {
-        dependencies.insert(&previous_var.name);
+        node.dependencies.insert(Cow::Borrowed(&previous_var.name));
       }
     }
 
-    local_vars_nodes.push(Node {
-      name: &var.name,
-      variable: Some(var),
-      dependencies: Some(dependencies),
-    });
+    local_vars_nodes.push(node);
   }
 
-  let global_vars_nodes = global_vars.iter().map(|var| create_node_from_var(var));
+  let global_vars_nodes = global_vars
+    .iter()
+    .map(|var| create_node_from_var(var, extensions));
 
   // The body depends on all local variables + the variables read inside it (which might be global)
-  let mut body_dependencies: HashSet<&str> =
-    local_vars_nodes.iter().map(|node| node.name).collect();
-  body_dependencies.extend(super::util::get_body_variable_names(body));
+  let mut body_dependencies: HashSet<Cow<str>> = local_vars_nodes
+    .iter()
+    .map(|node| Cow::Borrowed(node.name))
+    .collect();
+  body_dependencies.extend(
+    super::template::get_variable_names(body)
+      .into_iter()
+      .map(Cow::Borrowed),
+  );
 
   let body_node = Node {
     name: "__match_body",
     variable: None,
-    dependencies: Some(body_dependencies),
+    dependencies: body_dependencies,
   };
 
   let mut node_map = HashMap::new();
@@ -123,20 +126,36 @@ fn generate_nodes<'a>(
   node_map
 }
 
-fn create_node_from_var(var: &Variable) -> Node {
-  let dependencies = if var.inject_vars || !var.depends_on.is_empty() {
-    let mut vars = HashSet::new();
-
-    if var.inject_vars {
-      vars.extend(super::util::get_params_variable_names(&var.params));
-    }
-
-    vars.extend(var.depends_on.iter().map(String::as_str));
+fn create_node_from_var<'a>(
+  var: &'a Variable,
+  extensions: &HashMap<String, &dyn Extension>,
+) -> Node<'a> {
+  let mut dependencies = HashSet::new();
+
+  if var.inject_vars {
+    dependencies.extend(
+      super::util::get_params_variable_names(&var.params)
+        .into_iter()
+        .map(Cow::Borrowed),
+    );
+  }
 
-    Some(vars)
-  } else {
-    None
-  };
+  dependencies.extend(
+    var
+      .depends_on
+      .iter()
+      .map(|name| Cow::Borrowed(name.as_str())),
+  );
+
+  // Extensions can also read variables that are not referenced with the {{var}} syntax
+  if let Some(extension) = extensions.get(&var.var_type) {
+    dependencies.extend(
+      extension
+        .variable_dependencies(&var.params)
+        .into_iter()
+        .map(Cow::Owned),
+    );
+  }
 
   Node {
     name: &var.name,
@@ -157,36 +176,34 @@ fn resolve_dependencies<'a>(
     seen_ref.insert(node.name);
   }
 
-  if let Some(dependencies) = &node.dependencies {
-    for dependency in dependencies {
-      let has_been_resolved = {
-        let resolved_ref = resolved.borrow();
-        resolved_ref.contains(dependency)
-      };
-      let has_been_seen = {
-        let seen_ref = seen.borrow();
-        seen_ref.contains(dependency)
-      };
-
-      if !has_been_resolved {
-        if has_been_seen {
-          return Err(
-            RendererError::CircularDependency(node.name.to_string(), (*dependency).to_string())
-              .into(),
-          );
-        }
+  for dependency in &node.dependencies {
+    let dependency = dependency.as_ref();
+    let has_been_resolved = {
+      let resolved_ref = resolved.borrow();
+      resolved_ref.contains(dependency)
+    };
+    let has_been_seen = {
+      let seen_ref = seen.borrow();
+      seen_ref.contains(dependency)
+    };
+
+    if !has_been_resolved {
+      if has_been_seen {
+        return Err(
+          RendererError::CircularDependency(node.name.to_string(), dependency.to_string()).into(),
+        );
+      }
 
-        if let Some(dependency_node) = node_map.get(dependency) {
-          resolve_dependencies(dependency_node, node_map, eval_order, resolved, seen)?;
-        } else {
-          error!("could not resolve variable {:?}", dependency);
-          if let Some(variable) = &node.variable {
-            if variable.var_type == "form" {
-              super::log_new_form_syntax_tip();
-            }
+      if let Some(dependency_node) = node_map.get(dependency) {
+        resolve_dependencies(dependency_node, node_map, eval_order, resolved, seen)?;
+      } else {
+        error!("could not resolve variable {:?}", dependency);
+        if let Some(variable) = &node.variable {
+          if variable.var_type == "form" {
+            super::log_new_form_syntax_tip();
           }
-          return Err(RendererError::MissingVariable((*dependency).to_string()).into());
         }
+        return Err(RendererError::MissingVariable(dependency.to_string()).into());
       }
     }
   }
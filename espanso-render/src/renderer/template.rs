@@ -0,0 +1,549 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A small template language on top of the `{{var}}` substitutions, supporting
+//! conditionals (`{{#if var}}...{{else}}...{{/if}}`), loops over the lines of
+//! a variable or the fields of a multi-value variable (`{{#each var}}...{{/each}}`,
+//! exposing `{{this}}`, `{{@key}}` and `{{@index}}`) and filters (`{{var|upper}}`).
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use log::error;
+use regex::Regex;
+
+use super::{util, RendererError};
+use crate::{extension::transform::Transformation, ExtensionOutput, Scope};
+
+lazy_static! {
+  static ref TAG_REGEX: Regex = Regex::new(
+    r"\{\{\s*(?:#(?P<block>if|each)\s+(?P<block_path>@?\w+(?:\.\w+)?)|/(?P<end>if|each)|(?P<path>@?\w+(?:\.\w+)?)(?P<filters>(?:\s*\|\s*\w+)*))\s*\}\}"
+  )
+  .unwrap();
+}
+
+const THIS: &str = "this";
+const ELSE: &str = "else";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Path<'a> {
+  name: &'a str,
+  subname: Option<&'a str>,
+}
+
+impl<'a> Path<'a> {
+  fn parse(path: &'a str) -> Self {
+    match path.split_once('.') {
+      Some((name, subname)) => Self {
+        name,
+        subname: Some(subname),
+      },
+      None => Self {
+        name: path,
+        subname: None,
+      },
+    }
+  }
+}
+
+#[derive(Debug, PartialEq)]
+enum Node<'a> {
+  Text(&'a str),
+  Variable {
+    tag: &'a str,
+    path: Path<'a>,
+    filters: Vec<&'a str>,
+  },
+  If {
+    path: Path<'a>,
+    when_true: Vec<Node<'a>>,
+    when_false: Vec<Node<'a>>,
+  },
+  Each {
+    path: Path<'a>,
+    body: Vec<Node<'a>>,
+  },
+}
+
+struct Block<'a> {
+  kind: &'a str,
+  path: Path<'a>,
+  nodes: Vec<Node<'a>>,
+  else_nodes: Option<Vec<Node<'a>>>,
+}
+
+impl<'a> Block<'a> {
+  fn nodes_mut(&mut self) -> &mut Vec<Node<'a>> {
+    self.else_nodes.as_mut().unwrap_or(&mut self.nodes)
+  }
+}
+
+pub(crate) fn is_template(body: &str) -> bool {
+  TAG_REGEX.is_match(body)
+}
+
+pub(crate) fn render_template(body: &str, scope: &Scope) -> Result<String> {
+  let nodes = parse(body)?;
+  let mut output = String::new();
+  render_nodes(&nodes, scope, None, &mut output)?;
+  Ok(util::unescape_variable_inections(&output))
+}
+
+/// Return the names of the variables used by the template, falling back to the
+/// plain `{{var}}` references if the template is invalid.
+pub(crate) fn get_variable_names(body: &str) -> HashSet<&str> {
+  match parse(body) {
+    Ok(nodes) => {
+      let mut names = HashSet::new();
+      collect_variable_names(&nodes, false, &mut names);
+      names
+    }
+    Err(_) => util::get_body_variable_names(body),
+  }
+}
+
+fn collect_variable_names<'a>(nodes: &[Node<'a>], in_loop: bool, names: &mut HashSet<&'a str>) {
+  // Loop variables are only defined inside an `#each` block
+  let add = |path: &Path<'a>, names: &mut HashSet<&'a str>| {
+    if !(path.name.starts_with('@') || (in_loop && path.name == THIS)) {
+      names.insert(path.name);
+    }
+  };
+
+  for node in nodes {
+    match node {
+      Node::Text(_) => {}
+      Node::Variable { path, .. } => add(path, names),
+      Node::If {
+        path,
+        when_true,
+        when_false,
+      } => {
+        add(path, names);
+        collect_variable_names(when_true, in_loop, names);
+        collect_variable_names(when_false, in_loop, names);
+      }
+      Node::Each { path, body } => {
+        add(path, names);
+        collect_variable_names(body, true, names);
+      }
+    }
+  }
+}
+
+fn parse(body: &str) -> Result<Vec<Node<'_>>, RendererError> {
+  let mut root = Vec::new();
+  let mut blocks: Vec<Block> = Vec::new();
+  let mut last_end = 0;
+
+  for caps in TAG_REGEX.captures_iter(body) {
+    let tag = caps.get(0).unwrap();
+    let nodes = blocks.last_mut().map_or(&mut root, Block::nodes_mut);
+    if tag.start() > last_end {
+      nodes.push(Node::Text(&body[last_end..tag.start()]));
+    }
+    last_end = tag.end();
+
+    if let (Some(kind), Some(path)) = (caps.name("block"), caps.name("block_path")) {
+      blocks.push(Block {
+        kind: kind.as_str(),
+        path: Path::parse(path.as_str()),
+        nodes: Vec::new(),
+        else_nodes: None,
+      });
+    } else if let Some(end) = caps.name("end") {
+      let block = match blocks.pop() {
+        Some(block) if block.kind == end.as_str() => block,
+        _ => {
+          return Err(RendererError::InvalidTemplate(format!(
+            "unexpected '{}'",
+            tag.as_str()
+          )))
+        }
+      };
+
+      let node = if block.kind == "if" {
+        Node::If {
+          path: block.path,
+          when_true: block.nodes,
+          when_false: block.else_nodes.unwrap_or_default(),
+        }
+      } else {
+        Node::Each {
+          path: block.path,
+          body: block.nodes,
+        }
+      };
+      blocks
+        .last_mut()
+        .map_or(&mut root, Block::nodes_mut)
+        .push(node);
+    } else if let Some(path) = caps.name("path") {
+      let filters: Vec<&str> = caps
+        .name("filters")
+        .map(|filters| filters.as_str())
+        .unwrap_or_default()
+        .split('|')
+        .map(str::trim)
+        .filter(|filter| !filter.is_empty())
+        .collect();
+
+      // Outside of an `#if` block, `{{else}}` is a regular variable
+      if let Some(block) = blocks.last_mut() {
+        if path.as_str() == ELSE
+          && filters.is_empty()
+          && block.kind == "if"
+          && block.else_nodes.is_none()
+        {
+          block.else_nodes = Some(Vec::new());
+          continue;
+        }
+      }
+
+      let nodes = blocks.last_mut().map_or(&mut root, Block::nodes_mut);
+      nodes.push(Node::Variable {
+        tag: tag.as_str(),
+        path: Path::parse(path.as_str()),
+        filters,
+      });
+    }
+  }
+
+  if let Some(block) = blocks.last() {
+    return Err(RendererError::InvalidTemplate(format!(
+      "missing '{{{{/{}}}}}'",
+      block.kind
+    )));
+  }
+
+  if last_end < body.len() {
+    root.push(Node::Text(&body[last_end..]));
+  }
+
+  Ok(root)
+}
+
+// The item currently being iterated by an `#each` block
+struct LoopItem<'a> {
+  key: &'a str,
+  value: &'a str,
+  index: usize,
+}
+
+enum Resolved<'a> {
+  Single(&'a str),
+  Multiple(&'a HashMap<String, String>),
+}
+
+fn resolve<'a>(
+  path: &Path,
+  scope: &'a Scope,
+  item: Option<&'a LoopItem>,
+) -> Result<Option<Resolved<'a>>, RendererError> {
+  if let Some(item) = item {
+    match path.name {
+      THIS => return Ok(Some(Resolved::Single(item.value))),
+      "@key" => return Ok(Some(Resolved::Single(item.key))),
+      _ => {}
+    }
+  }
+  if path.name.starts_with('@') {
+    return Ok(None);
+  }
+
+  match scope.get(path.name) {
+    Some(ExtensionOutput::Single(value)) => Ok(Some(Resolved::Single(value))),
+    Some(ExtensionOutput::Multiple(values)) => match path.subname {
+      Some(subname) => Ok(Some(Resolved::Single(
+        values.get(subname).map_or("", String::as_str),
+      ))),
+      None => Ok(Some(Resolved::Multiple(values))),
+    },
+    Some(ExtensionOutput::Structured { value, fields }) => match path.subname {
+      Some(subname) => Ok(Some(Resolved::Single(
+        fields.get(subname).map_or("", String::as_str),
+      ))),
+      None => Ok(Some(Resolved::Single(value))),
+    },
+    None => Err(RendererError::MissingVariable(format!(
+      "variable '{}' is missing",
+      path.name
+    ))),
+  }
+}
+
+fn render_nodes(
+  nodes: &[Node],
+  scope: &Scope,
+  item: Option<&LoopItem>,
+  output: &mut String,
+) -> Result<()> {
+  for node in nodes {
+    match node {
+      Node::Text(text) => output.push_str(text),
+      Node::Variable { tag, path, filters } => {
+        if let (Some(item), "@index") = (item, path.name) {
+          output.push_str(&item.index.to_string());
+          continue;
+        }
+
+        let value = match resolve(path, scope, item)? {
+          Some(Resolved::Single(value)) => value,
+          Some(Resolved::Multiple(_)) => {
+            error!(
+              "nested name missing from multi-value variable: {}",
+              path.name
+            );
+            return Err(
+              RendererError::MissingVariable(format!(
+                "nested name missing from multi-value variable: {}",
+                path.name
+              ))
+              .into(),
+            );
+          }
+          // Unknown loop variables are left untouched
+          None => {
+            output.push_str(tag);
+            continue;
+          }
+        };
+
+        // Variables with unknown filters are left untouched, as they were
+        // before filters existed
+        let Some(transformations) = filters
+          .iter()
+          .map(|filter| Transformation::from_name(filter))
+          .collect::<Option<Vec<_>>>()
+        else {
+          output.push_str(tag);
+          continue;
+        };
+
+        let mut value = value.to_string();
+        for transformation in transformations {
+          value = transformation.apply(&value)?;
+        }
+        output.push_str(&value);
+      }
+      Node::If {
+        path,
+        when_true,
+        when_false,
+      } => {
+        let condition = match resolve(path, scope, item)? {
+          Some(Resolved::Single(value)) => is_truthy(value),
+          Some(Resolved::Multiple(values)) => !values.is_empty(),
+          None => false,
+        };
+        let branch = if condition { when_true } else { when_false };
+        render_nodes(branch, scope, item, output)?;
+      }
+      Node::Each { path, body } => {
+        let entries: Vec<(String, &str)> = match resolve(path, scope, item)? {
+          Some(Resolved::Single(value)) => value
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(index, line)| (index.to_string(), line))
+            .collect(),
+          Some(Resolved::Multiple(values)) => {
+            let mut entries: Vec<_> = values
+              .iter()
+              .map(|(key, value)| (key.clone(), value.as_str()))
+              .collect();
+            entries.sort();
+            entries
+          }
+          None => Vec::new(),
+        };
+
+        for (index, (key, value)) in entries.iter().enumerate() {
+          let item = LoopItem { key, value, index };
+          render_nodes(body, scope, Some(&item), output)?;
+        }
+      }
+    }
+  }
+
+  Ok(())
+}
+
+fn is_truthy(value: &str) -> bool {
+  let value = value.trim();
+  !(value.is_empty() || value == "0" || value.eq_ignore_ascii_case("false"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn scope() -> Scope<'static> {
+    let mut scope = Scope::new();
+    scope.insert("name", ExtensionOutput::Single("  john snow ".to_string()));
+    scope.insert("empty", ExtensionOutput::Single(String::new()));
+    scope.insert("flag", ExtensionOutput::Single("false".to_string()));
+    scope.insert(
+      "items",
+      ExtensionOutput::Single("first\nsecond\n\nthird".to_string()),
+    );
+    let fields = vec![
+      ("b".to_string(), "two".to_string()),
+      ("a".to_string(), "one".to_string()),
+    ]
+    .into_iter()
+    .collect();
+    scope.insert("form1", ExtensionOutput::Multiple(fields));
+    let date = vec![("year".to_string(), "2026".to_string())]
+      .into_iter()
+      .collect();
+    scope.insert(
+      "date",
+      ExtensionOutput::Structured {
+        value: "25/12/2026".to_string(),
+        fields: date,
+      },
+    );
+    scope
+  }
+
+  fn render(body: &str) -> String {
+    render_template(body, &scope()).unwrap()
+  }
+
+  #[test]
+  fn plain_variables() {
+    assert_eq!(render("hello {{name}}!"), "hello   john snow !");
+    assert_eq!(render("{{ form1.a }} {{form1.b}}"), "one two");
+    assert_eq!(render("{{form1.missing}}"), "");
+    assert_eq!(render("{{date}} {{date.year}}"), "25/12/2026 2026");
+    assert!(render_template("{{form1}}", &scope()).is_err());
+    assert_eq!(render("no variables"), "no variables");
+  }
+
+  #[test]
+  fn filters() {
+    assert_eq!(render("{{name|trim}}"), "john snow");
+    assert_eq!(render("{{name | trim | upper}}"), "JOHN SNOW");
+    assert_eq!(render("{{name|trim|capitalize}}"), "John snow");
+    assert_eq!(render("{{name|trim|capitalize_words}}"), "John Snow");
+    assert_eq!(render("{{form1.a|upper|lower}}"), "one");
+    assert_eq!(render("{{name|trim|snake}}"), "john_snow");
+    assert_eq!(render("{{name|trim|url_encode}}"), "john%20snow");
+    assert_eq!(render("{{name|unknown}}"), "{{name|unknown}}");
+    assert_eq!(
+      render("{{name | trim | unknown}}"),
+      "{{name | trim | unknown}}"
+    );
+  }
+
+  #[test]
+  fn if_blocks() {
+    assert_eq!(render("{{#if name}}yes{{/if}}"), "yes");
+    assert_eq!(render("{{#if empty}}yes{{/if}}"), "");
+    assert_eq!(render("{{#if flag}}yes{{else}}no{{/if}}"), "no");
+    assert_eq!(
+      render("{{#if form1.a}}{{form1.a}}{{else}}none{{/if}}"),
+      "one"
+    );
+    assert_eq!(
+      render("{{#if form1.c}}{{form1.c}}{{else}}none{{/if}}"),
+      "none"
+    );
+    assert_eq!(
+      render("{{#if name}}{{#if empty}}a{{else}}b{{/if}}{{else}}c{{/if}}"),
+      "b"
+    );
+  }
+
+  #[test]
+  fn each_blocks() {
+    assert_eq!(
+      render("{{#each items}}- {{this}}\n{{/each}}"),
+      "- first\n- second\n- third\n"
+    );
+    assert_eq!(
+      render("{{#each items}}{{@index}}:{{this|upper}} {{/each}}"),
+      "0:FIRST 1:SECOND 2:THIRD "
+    );
+    assert_eq!(
+      render("{{#each form1}}{{@key}}={{this}};{{/each}}"),
+      "a=one;b=two;"
+    );
+    assert_eq!(
+      render("{{#each items}}{{#if flag}}{{this}}{{else}}{{name|trim}}{{/if}},{{/each}}"),
+      "john snow,john snow,john snow,"
+    );
+  }
+
+  #[test]
+  fn else_outside_if_is_a_variable() {
+    let mut scope = scope();
+    scope.insert("else", ExtensionOutput::Single("value".to_string()));
+    assert_eq!(render_template("{{else}}", &scope).unwrap(), "value");
+  }
+
+  #[test]
+  fn loop_variables_outside_each_are_left_untouched() {
+    assert_eq!(render("{{@index}} {{@key}}"), "{{@index}} {{@key}}");
+  }
+
+  #[test]
+  fn escaped_tags() {
+    assert_eq!(render("\\{\\{#if name\\}\\}"), "{{#if name}}");
+  }
+
+  #[test]
+  fn invalid_templates() {
+    assert_eq!(
+      render_template("{{#if name}}yes", &scope())
+        .unwrap_err()
+        .to_string(),
+      "invalid template: missing '{{/if}}'"
+    );
+    assert_eq!(
+      render_template("{{#each items}}yes{{/if}}", &scope())
+        .unwrap_err()
+        .to_string(),
+      "invalid template: unexpected '{{/if}}'"
+    );
+    assert!(render_template("{{/each}}", &scope()).is_err());
+    assert!(render_template("{{#if missing}}yes{{/if}}", &scope()).is_err());
+    assert!(render_template("{{form1}}", &scope()).is_err());
+  }
+
+  #[test]
+  fn get_variable_names_works_correctly() {
+    assert_eq!(
+      get_variable_names(
+        "{{#if a}}{{b|upper}}{{else}}{{c.d}}{{/if}}{{#each e}}{{this}} {{@key}}{{/each}}{{this}}"
+      ),
+      HashSet::from_iter(vec!["a", "b", "c", "e", "this"]),
+    );
+  }
+
+  #[test]
+  fn get_variable_names_of_invalid_template() {
+    assert_eq!(
+      get_variable_names("{{#if a}}{{b}}"),
+      HashSet::from_iter(vec!["b"]),
+    );
+  }
+}
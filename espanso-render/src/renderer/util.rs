@@ -87,6 +87,10 @@ pub(crate) fn render_variables(body: &str, scope: &Scope) -> Result<String> {
               ""
             }
           }
+          ExtensionOutput::Structured { value, fields } => match var_subname {
+            Some(var_subname) => fields.get(var_subname.as_str()).map_or("", |value| value),
+            None => value,
+          },
         }
       } else {
         replacing_error = Some(RendererError::MissingVariable(format!(
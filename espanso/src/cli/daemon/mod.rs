@@ -17,7 +17,11 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::{path::Path, process::Command, time::Instant};
+use std::{
+  path::{Path, PathBuf},
+  process::Command,
+  time::Instant,
+};
 
 use crossbeam::{
   channel::{unbounded, Sender},
@@ -35,7 +39,7 @@ use crate::{
     DAEMON_LEGACY_ALREADY_RUNNING, DAEMON_SUCCESS, WORKER_ERROR_EXIT_NO_CODE,
     WORKER_EXIT_ALL_PROCESSES, WORKER_RESTART, WORKER_SUCCESS,
   },
-  ipc::{create_ipc_client_to_worker, IPCEvent},
+  ipc::{create_ipc_client_to_worker, IPCEvent, ReloadMatchesPayload},
   lock::{acquire_daemon_lock, acquire_legacy_lock, acquire_worker_lock},
   VERSION,
 };
@@ -94,7 +98,7 @@ fn daemon_main(args: CliModuleArgs) -> i32 {
   // #[allow(unused_variables)]
   let mut _current_troubleshoot_guard = None;
 
-  let (watcher_notify, watcher_signal) = unbounded::<()>();
+  let (watcher_notify, watcher_signal) = unbounded::<Vec<PathBuf>>();
 
   watcher::initialize_and_spawn(&paths.config, watcher_notify)
     .expect("unable to initialize config watcher thread");
@@ -141,12 +145,12 @@ fn daemon_main(args: CliModuleArgs) -> i32 {
 
   loop {
     select! {
-      recv(watcher_signal) -> _ => {
+      recv(watcher_signal) -> changed_paths => {
         if !config_store.default().auto_restart() {
           continue;
         }
 
-        info!("configuration change detected, restarting worker process...");
+        info!("configuration change detected, reloading...");
 
         // Before killing the previous worker, we make sure there is no fatal error
         // in the configs.
@@ -167,7 +171,13 @@ fn daemon_main(args: CliModuleArgs) -> i32 {
         };
 
         if should_restart_worker {
-          restart_worker(&paths, &paths_overrides, exit_notify.clone(), Some(WORKER_START_REASON_CONFIG_CHANGED.to_string()));
+          let changed_paths = changed_paths.unwrap_or_default();
+          if request_matches_reload(&paths, &changed_paths) {
+            info!("requested the worker to reload the changed match files");
+          } else {
+            info!("restarting worker process...");
+            restart_worker(&paths, &paths_overrides, exit_notify.clone(), Some(WORKER_START_REASON_CONFIG_CHANGED.to_string()));
+          }
         }
       }
       recv(keyboard_layout_watcher_signal) -> _ => {
@@ -286,6 +296,41 @@ fn spawn_worker(
     .expect("Unable to spawn worker monitor thread");
 }
 
+// If only match files have changed, the worker can reload them without restarting.
+// The worker takes care of the cases it can't handle in place by requesting a restart.
+fn request_matches_reload(paths: &Paths, changed_paths: &[PathBuf]) -> bool {
+  let config_dir = paths.config.join("config");
+  let only_match_files_changed = !changed_paths.is_empty()
+    && changed_paths
+      .iter()
+      .all(|path| path.is_file() && !path.starts_with(&config_dir));
+  if !only_match_files_changed {
+    return false;
+  }
+
+  let payload = ReloadMatchesPayload {
+    paths: changed_paths
+      .iter()
+      .map(|path| path.to_string_lossy().to_string())
+      .collect(),
+  };
+
+  match create_ipc_client_to_worker(&paths.runtime) {
+    Ok(mut worker_ipc) => {
+      if let Err(err) = worker_ipc.send_async(IPCEvent::ReloadMatches(payload)) {
+        error!("unable to send reload request to worker process: {}", err);
+        false
+      } else {
+        true
+      }
+    }
+    Err(err) => {
+      error!("could not establish IPC connection with worker: {}", err);
+      false
+    }
+  }
+}
+
 fn restart_worker(
   paths: &Paths,
   paths_overrides: &PathsOverrides,
@@ -17,6 +17,7 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::path::PathBuf;
 use std::process::{Child, Command};
 use std::time::Duration;
 
@@ -108,7 +109,7 @@ pub fn load_config_or_troubleshoot(paths: &Paths, paths_overrides: &PathsOverrid
 pub fn load_config_or_troubleshoot_until_config_is_correct_or_abort(
   paths: &Paths,
   paths_overrides: &PathsOverrides,
-  watcher_receiver: Receiver<()>,
+  watcher_receiver: Receiver<Vec<PathBuf>>,
 ) -> Result<(ConfigLoadResult, Option<TroubleshootGuard>)> {
   let mut _troubleshoot_guard = None;
 
@@ -17,7 +17,11 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::{path::Path, time::Duration};
+use std::{
+  collections::HashSet,
+  path::{Path, PathBuf},
+  time::Duration,
+};
 
 use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
 
@@ -28,7 +32,7 @@ use log::{error, info, warn};
 const WATCHER_NOTIFY_DELAY_MS: u64 = 500;
 const WATCHER_DEBOUNCE_DURATION_MS: u64 = 1000;
 
-pub fn initialize_and_spawn(config_dir: &Path, watcher_notify: Sender<()>) -> Result<()> {
+pub fn initialize_and_spawn(config_dir: &Path, watcher_notify: Sender<Vec<PathBuf>>) -> Result<()> {
   let config_dir = config_dir.to_path_buf();
 
   let (debounce_tx, debounce_rx) = crossbeam::channel::unbounded();
@@ -48,7 +52,7 @@ pub fn initialize_and_spawn(config_dir: &Path, watcher_notify: Sender<()>) -> Re
   Ok(())
 }
 
-fn watcher_main(config_dir: &Path, debounce_tx: Sender<()>) {
+fn watcher_main(config_dir: &Path, debounce_tx: Sender<PathBuf>) {
   let (tx, rx) = std::sync::mpsc::channel();
 
   let mut watcher: RecommendedWatcher =
@@ -62,7 +66,7 @@ fn watcher_main(config_dir: &Path, debounce_tx: Sender<()>) {
   info!("watching for changes in path: {:?}", config_dir);
 
   loop {
-    let should_reload = match rx.recv() {
+    let changed_path = match rx.recv() {
       Ok(event) => {
         let path = match event {
           DebouncedEvent::Create(path) => Some(path),
@@ -79,25 +83,34 @@ fn watcher_main(config_dir: &Path, debounce_tx: Sender<()>) {
             .to_string_lossy()
             .to_ascii_lowercase();
 
-          if ["yml", "yaml"].iter().any(|ext| ext == &extension) {
-            // Only load non-hidden yml files
+          let should_reload = if ["yml", "yaml", "json", "toml"]
+            .iter()
+            .any(|ext| ext == &extension)
+          {
+            // Only load non-hidden config and match files
             !is_file_hidden(&path)
           } else {
             // If there is no extension, it's probably a folder
             extension.is_empty()
+          };
+
+          if should_reload {
+            Some(path)
+          } else {
+            None
           }
         } else {
-          false
+          None
         }
       }
       Err(e) => {
         warn!("error while watching files: {:?}", e);
-        false
+        None
       }
     };
 
-    if should_reload {
-      if let Err(error) = debounce_tx.send(()) {
+    if let Some(path) = changed_path {
+      if let Err(error) = debounce_tx.send(path) {
         error!(
           "unable to send watcher file changed event to debouncer: {}",
           error
@@ -107,22 +120,25 @@ fn watcher_main(config_dir: &Path, debounce_tx: Sender<()>) {
   }
 }
 
-fn debouncer_main(debounce_rx: crossbeam::channel::Receiver<()>, watcher_notify: &Sender<()>) {
-  let mut has_received_event = false;
+fn debouncer_main(
+  debounce_rx: crossbeam::channel::Receiver<PathBuf>,
+  watcher_notify: &Sender<Vec<PathBuf>>,
+) {
+  let mut changed_paths = HashSet::new();
 
   loop {
     select! {
-      recv(debounce_rx) -> _ => {
-        has_received_event = true;
+      recv(debounce_rx) -> path => {
+        if let Ok(path) = path {
+          changed_paths.insert(path);
+        }
       },
       default(Duration::from_millis(WATCHER_DEBOUNCE_DURATION_MS)) => {
-        if has_received_event {
-          if let Err(error) = watcher_notify.send(()) {
+        if !changed_paths.is_empty() {
+          if let Err(error) = watcher_notify.send(changed_paths.drain().collect()) {
             error!("unable to send watcher file changed event: {}", error);
           }
         }
-
-        has_received_event = false;
       },
     }
   }
@@ -0,0 +1,111 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use clap::ArgMatches;
+use espanso_kvs::KVS;
+
+use super::{CliModule, CliModuleArgs};
+
+pub fn new() -> CliModule {
+  CliModule {
+    requires_paths: true,
+    subcommand: "kvs".to_string(),
+    entry: kvs_main,
+    ..Default::default()
+  }
+}
+
+fn kvs_main(args: CliModuleArgs) -> i32 {
+  let paths = args.paths.expect("missing paths argument");
+  let cli_args = args.cli_args.expect("missing cli_args argument");
+
+  let kvs = match espanso_kvs::get_persistent(&paths.runtime) {
+    Ok(kvs) => kvs,
+    Err(err) => {
+      eprintln!("unable to open the key-value store: {err:?}");
+      return 1;
+    }
+  };
+
+  let result = if cli_args.subcommand_matches("list").is_some() {
+    list_main(&kvs)
+  } else if let Some(sub_args) = cli_args.subcommand_matches("get") {
+    get_main(&kvs, sub_args)
+  } else if let Some(sub_args) = cli_args.subcommand_matches("set") {
+    set_main(&kvs, sub_args)
+  } else if let Some(sub_args) = cli_args.subcommand_matches("delete") {
+    delete_main(&kvs, sub_args)
+  } else {
+    eprintln!("Invalid use, please run 'espanso kvs --help' to get more information.");
+    return 1;
+  };
+
+  match result {
+    Ok(code) => code,
+    Err(err) => {
+      eprintln!("unable to access the key-value store: {err:?}");
+      1
+    }
+  }
+}
+
+fn list_main(kvs: &impl KVS) -> Result<i32> {
+  for key in kvs.list()? {
+    if let Some(value) = kvs.get::<serde_json::Value>(&key)? {
+      println!("{key}: {value}");
+    }
+  }
+
+  Ok(0)
+}
+
+fn get_main(kvs: &impl KVS, args: &ArgMatches) -> Result<i32> {
+  let key = args.value_of("key").expect("missing key argument");
+
+  match kvs.get::<serde_json::Value>(key)? {
+    Some(serde_json::Value::String(value)) => println!("{value}"),
+    Some(value) => println!("{value}"),
+    None => {
+      eprintln!("no value stored for key '{key}'");
+      return Ok(2);
+    }
+  }
+
+  Ok(0)
+}
+
+fn set_main(kvs: &impl KVS, args: &ArgMatches) -> Result<i32> {
+  let key = args.value_of("key").expect("missing key argument");
+  let value = args.value_of("value").expect("missing value argument");
+
+  // Values are stored as JSON, falling back to plain strings
+  let value = serde_json::from_str::<serde_json::Value>(value)
+    .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+  kvs.set(key, value)?;
+
+  Ok(0)
+}
+
+fn delete_main(kvs: &impl KVS, args: &ArgMatches) -> Result<i32> {
+  let key = args.value_of("key").expect("missing key argument");
+  kvs.delete(key)?;
+
+  Ok(0)
+}
@@ -0,0 +1,46 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use espanso_config::{error::ErrorLevel, lint::lint};
+use espanso_path::Paths;
+
+/// Check the match files for problems, returning the number of issues found
+pub fn check_main(paths: &Paths) -> usize {
+  let issues = lint(&paths.config);
+
+  for issue in &issues {
+    let level = match issue.level {
+      ErrorLevel::Error => "error",
+      ErrorLevel::Warning => "warning",
+    };
+    let location = match issue.line {
+      Some(line) => format!("{}:{}", issue.file.display(), line),
+      None => issue.file.display().to_string(),
+    };
+    println!("{location}: {level}: {}", issue.message);
+  }
+
+  if issues.is_empty() {
+    println!("No problems found");
+  } else {
+    eprintln!("\n{} problem(s) found", issues.len());
+  }
+
+  issues.len()
+}
@@ -0,0 +1,68 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use espanso_config::{config::ConfigStore, matches::store::MatchStore};
+use espanso_match::rolling::matcher::{RollingMatcher, RollingMatcherOptions};
+
+use crate::cli::worker::{
+  builtin::get_builtin_matches, engine::process::middleware::matcher::convert::MatchConverter,
+};
+
+pub fn conflicts_main(
+  config_store: Box<dyn ConfigStore>,
+  match_store: Box<dyn MatchStore>,
+) -> Result<()> {
+  // Convert the matches in the same way as the worker does
+  let builtin_matches = get_builtin_matches(&*config_store.default());
+  let match_converter = MatchConverter::new(&*config_store, &*match_store, &builtin_matches);
+  let matcher = RollingMatcher::new(
+    &match_converter.get_rolling_matches(),
+    RollingMatcherOptions::default(),
+  );
+
+  if matcher.conflicts().is_empty() {
+    println!("No conflicts found");
+    return Ok(());
+  }
+
+  let paths = config_store.get_all_match_paths();
+  let match_set = match_store.query(&paths.into_iter().collect::<Vec<_>>());
+  let descriptions: HashMap<i32, &str> = match_set
+    .matches
+    .iter()
+    .map(|m| (m.id, m.description()))
+    .chain(builtin_matches.iter().map(|m| (m.id, m.label)))
+    .collect();
+
+  for conflict in matcher.conflicts() {
+    println!("{conflict}");
+    for (id, trigger) in [
+      (conflict.id, &conflict.trigger),
+      (conflict.other_id, &conflict.other_trigger),
+    ] {
+      let description = descriptions.get(&id).copied().unwrap_or_default();
+      println!("  {trigger} - {}", description.replace('\n', " "));
+    }
+  }
+
+  Ok(())
+}
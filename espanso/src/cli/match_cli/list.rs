@@ -37,13 +37,19 @@ pub fn list_main(
   let title = cli_args.value_of("title");
   let exec = cli_args.value_of("exec");
 
-  let config = config_store.active(&AppProperties { title, class, exec });
+  let app = AppProperties { title, class, exec };
+  let config = config_store.active(&app);
   let match_set = match_store.query(config.match_paths());
+  let matches: Vec<&Match> = match_set
+    .matches
+    .into_iter()
+    .filter(|m| m.app_filter.is_match(&app))
+    .collect();
 
   if cli_args.is_present("json") {
-    print_matches_as_json(&match_set.matches)?;
+    print_matches_as_json(&matches)?;
   } else {
-    print_matches_as_plain(&match_set.matches, only_triggers, preserve_newlines)?;
+    print_matches_as_plain(&matches, only_triggers, preserve_newlines)?;
   }
 
   Ok(())
@@ -59,6 +65,7 @@ pub fn print_matches_as_plain(
       MatchCause::None => vec!["(none)".to_string()],
       MatchCause::Trigger(trigger_cause) => trigger_cause.triggers.clone(),
       MatchCause::Regex(regex_cause) => vec![regex_cause.regex.clone()],
+      MatchCause::Hotkey(hotkey_cause) => vec![hotkey_cause.hotkey.clone()],
     };
 
     for trigger in triggers {
@@ -103,6 +110,7 @@ pub fn print_matches_as_json(match_list: &[&Match]) -> Result<()> {
       MatchCause::None => vec!["(none)".to_string()],
       MatchCause::Trigger(trigger_cause) => trigger_cause.triggers.clone(),
       MatchCause::Regex(regex_cause) => vec![regex_cause.regex.clone()],
+      MatchCause::Hotkey(hotkey_cause) => vec![hotkey_cause.hotkey.clone()],
     };
 
     entries.push(JsonMatchEntry {
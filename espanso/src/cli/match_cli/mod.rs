@@ -17,15 +17,20 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use anyhow::Result;
+use espanso_config::{config::ConfigStore, matches::store::MatchStore};
+use espanso_path::Paths;
+
 use super::{CliModule, CliModuleArgs};
 
+mod check;
+mod conflicts;
 mod exec;
 mod list;
 
 pub fn new() -> CliModule {
   CliModule {
     requires_paths: true,
-    requires_config: true,
     subcommand: "match".to_string(),
     entry: match_main,
     ..Default::default()
@@ -34,12 +39,14 @@ pub fn new() -> CliModule {
 
 fn match_main(args: CliModuleArgs) -> i32 {
   let cli_args = args.cli_args.expect("missing cli_args");
-  let config_store = args.config_store.expect("missing config_store");
-  let match_store = args.match_store.expect("missing match_store");
   let paths = args.paths.expect("missing paths");
 
+  // The config is loaded by each subcommand, as `check` must also be able
+  // to report the errors that prevent it from loading
   if let Some(sub_args) = cli_args.subcommand_matches("list") {
-    if let Err(err) = list::list_main(sub_args, config_store, match_store) {
+    let result = load_stores(&paths)
+      .and_then(|(config_store, match_store)| list::list_main(sub_args, config_store, match_store));
+    if let Err(err) = result {
       eprintln!("unable to list matches: {err:?}");
       return 1;
     }
@@ -48,6 +55,17 @@ fn match_main(args: CliModuleArgs) -> i32 {
       eprintln!("unable to exec match: {err:?}");
       return 1;
     }
+  } else if cli_args.subcommand_matches("conflicts").is_some() {
+    let result = load_stores(&paths)
+      .and_then(|(config_store, match_store)| conflicts::conflicts_main(config_store, match_store));
+    if let Err(err) = result {
+      eprintln!("unable to list conflicts: {err:?}");
+      return 1;
+    }
+  } else if cli_args.subcommand_matches("check").is_some() {
+    if check::check_main(&paths) > 0 {
+      return 1;
+    }
   } else {
     eprintln!("Invalid use, please run 'espanso match --help' to get more information.");
     return 1;
@@ -55,3 +73,8 @@ fn match_main(args: CliModuleArgs) -> i32 {
 
   0
 }
+
+fn load_stores(paths: &Paths) -> Result<(Box<dyn ConfigStore>, Box<dyn MatchStore>)> {
+  let config_result = crate::load_config(&paths.config, &paths.packages)?;
+  Ok((config_result.config_store, config_result.match_store))
+}
@@ -27,6 +27,7 @@ pub mod cmd;
 pub mod daemon;
 pub mod edit;
 pub mod env_path;
+pub mod kvs;
 pub mod launcher;
 pub mod log;
 pub mod match_cli;
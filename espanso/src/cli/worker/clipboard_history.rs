@@ -0,0 +1,224 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+  collections::VecDeque,
+  path::Path,
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use anyhow::Result;
+use espanso_clipboard::{Clipboard, ClipboardOperationOptions, ClipboardOptions};
+use log::{error, info};
+
+// Larger clipboard contents are not recorded, to keep the memory usage low
+const MAX_ENTRY_LENGTH: usize = 100_000;
+
+pub struct ClipboardHistory {
+  max_size: usize,
+  entries: Mutex<VecDeque<String>>,
+
+  // The last content set by espanso itself (for example, when injecting an expansion
+  // through the clipboard), which shouldn't be recorded in the history
+  ignored: Mutex<Option<String>>,
+}
+
+impl ClipboardHistory {
+  pub fn new(max_size: usize) -> Self {
+    Self {
+      max_size,
+      entries: Mutex::new(VecDeque::new()),
+      ignored: Mutex::new(None),
+    }
+  }
+
+  pub fn entries(&self) -> Vec<String> {
+    let entries = self
+      .entries
+      .lock()
+      .expect("unable to obtain clipboard history lock");
+    entries.iter().cloned().collect()
+  }
+
+  fn record(&self, text: &str) {
+    if text.trim().is_empty() || text.len() > MAX_ENTRY_LENGTH {
+      return;
+    }
+
+    let mut entries = self
+      .entries
+      .lock()
+      .expect("unable to obtain clipboard history lock");
+    entries.retain(|entry| entry != text);
+    entries.push_front(text.to_string());
+    entries.truncate(self.max_size);
+  }
+
+  fn ignore(&self, text: &str) {
+    let mut ignored = self
+      .ignored
+      .lock()
+      .expect("unable to obtain clipboard history lock");
+    *ignored = Some(text.to_string());
+  }
+
+  // Returns true if the given content should be recorded, clearing the
+  // ignored content once the clipboard has changed to something else
+  fn should_record(&self, text: &str) -> bool {
+    let mut ignored = self
+      .ignored
+      .lock()
+      .expect("unable to obtain clipboard history lock");
+    if ignored.as_deref() == Some(text) {
+      false
+    } else {
+      *ignored = None;
+      true
+    }
+  }
+}
+
+// Every check reads the whole clipboard content (spawning a process on Wayland or
+// with the xclip backend), so the interval should be kept reasonably high
+pub fn initialize_and_spawn(
+  history: Arc<ClipboardHistory>,
+  clipboard_options: ClipboardOptions,
+  use_xclip_backend: bool,
+  poll_interval: Duration,
+) -> Result<()> {
+  std::thread::Builder::new()
+    .name("clipboard-history".to_string())
+    .spawn(move || {
+      // The monitor uses a separate clipboard instance, as the one
+      // used by the engine is not shareable across threads
+      match espanso_clipboard::get_clipboard(clipboard_options) {
+        Ok(clipboard) => {
+          clipboard_history_main(&history, &*clipboard, use_xclip_backend, poll_interval);
+        }
+        Err(err) => error!("unable to initialize clipboard for history: {}", err),
+      }
+    })?;
+
+  Ok(())
+}
+
+fn clipboard_history_main(
+  history: &ClipboardHistory,
+  clipboard: &dyn Clipboard,
+  use_xclip_backend: bool,
+  poll_interval: Duration,
+) {
+  info!("monitoring clipboard to record its history");
+
+  let options = ClipboardOperationOptions { use_xclip_backend };
+  let mut last_content: Option<String> = None;
+  loop {
+    let content = clipboard.get_text(&options);
+    if content != last_content {
+      if let Some(content) = &content {
+        if history.should_record(content) {
+          history.record(content);
+        }
+      }
+      last_content = content;
+    }
+
+    std::thread::sleep(poll_interval);
+  }
+}
+
+/// Clipboard wrapper used by espanso to inject contents, which prevents
+/// them from being recorded in the clipboard history.
+pub struct HistoryAwareClipboard<'a> {
+  clipboard: &'a dyn Clipboard,
+  history: Option<&'a ClipboardHistory>,
+}
+
+impl<'a> HistoryAwareClipboard<'a> {
+  pub fn new(clipboard: &'a dyn Clipboard, history: Option<&'a ClipboardHistory>) -> Self {
+    Self { clipboard, history }
+  }
+
+  fn ignore(&self, text: &str) {
+    if let Some(history) = self.history {
+      history.ignore(text);
+    }
+  }
+}
+
+impl<'a> Clipboard for HistoryAwareClipboard<'a> {
+  fn get_text(&self, options: &ClipboardOperationOptions) -> Option<String> {
+    self.clipboard.get_text(options)
+  }
+
+  fn set_text(&self, text: &str, options: &ClipboardOperationOptions) -> Result<()> {
+    self.ignore(text);
+    self.clipboard.set_text(text, options)
+  }
+
+  fn set_image(&self, image_path: &Path, options: &ClipboardOperationOptions) -> Result<()> {
+    self.clipboard.set_image(image_path, options)
+  }
+
+  fn set_html(
+    &self,
+    html: &str,
+    fallback_text: Option<&str>,
+    options: &ClipboardOperationOptions,
+  ) -> Result<()> {
+    if let Some(fallback_text) = fallback_text {
+      self.ignore(fallback_text);
+    }
+    self.clipboard.set_html(html, fallback_text, options)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn history_is_size_limited() {
+    let history = ClipboardHistory::new(2);
+    history.record("first");
+    history.record("second");
+    history.record("third");
+    assert_eq!(history.entries(), vec!["third", "second"]);
+  }
+
+  #[test]
+  fn duplicate_entries_are_moved_to_front() {
+    let history = ClipboardHistory::new(5);
+    history.record("first");
+    history.record("second");
+    history.record("first");
+    history.record("  ");
+    assert_eq!(history.entries(), vec!["first", "second"]);
+  }
+
+  #[test]
+  fn ignored_content_is_not_recorded_until_clipboard_changes() {
+    let history = ClipboardHistory::new(5);
+    history.ignore("expansion");
+    assert!(!history.should_record("expansion"));
+    assert!(history.should_record("copied"));
+    assert!(history.should_record("expansion"));
+  }
+}
@@ -28,18 +28,19 @@ use espanso_info::{AppInfo, AppInfoProvider};
 use super::{
   builtin::is_builtin_match,
   engine::process::middleware::render::extension::clipboard::ClipboardOperationOptionsProvider,
+  reloadable::Reloadable,
 };
 
 pub struct ConfigManager<'a> {
   config_store: &'a dyn ConfigStore,
-  match_store: &'a dyn MatchStore,
+  match_store: &'a Reloadable<'a, Box<dyn MatchStore>>,
   app_info_provider: &'a dyn AppInfoProvider,
 }
 
 impl<'a> ConfigManager<'a> {
   pub fn new(
     config_store: &'a dyn ConfigStore,
-    match_store: &'a dyn MatchStore,
+    match_store: &'a Reloadable<'a, Box<dyn MatchStore>>,
     app_info_provider: &'a dyn AppInfoProvider,
   ) -> Self {
     Self {
@@ -58,7 +59,7 @@ impl<'a> ConfigManager<'a> {
   pub fn active_context(&self) -> (Arc<dyn Config>, MatchSet) {
     let config = self.active();
     let match_paths = config.match_paths();
-    (config.clone(), self.match_store.query(match_paths))
+    (config.clone(), self.match_store.get().query(match_paths))
   }
 
   pub fn default(&self) -> Arc<dyn Config> {
@@ -78,12 +79,16 @@ fn to_app_properties(info: &AppInfo) -> AppProperties {
 impl<'a> espanso_engine::process::MatchFilter for ConfigManager<'a> {
   fn filter_active(&self, matches_ids: &[i32]) -> Vec<i32> {
     let ids_set: HashSet<i32> = matches_ids.iter().copied().collect::<HashSet<_>>();
-    let (_, match_set) = self.active_context();
+    let current_app = self.app_info_provider.get_info();
+    let info = to_app_properties(&current_app);
+    let config = self.config_store.active(&info);
+    let match_set = self.match_store.get().query(config.match_paths());
 
+    // Matches can further restrict the applications in which they are active
     let active_user_defined_matches: Vec<i32> = match_set
       .matches
       .iter()
-      .filter(|m| ids_set.contains(&m.id))
+      .filter(|m| ids_set.contains(&m.id) && m.app_filter.is_match(&info))
       .map(|m| m.id)
       .collect();
 
@@ -106,7 +111,7 @@ impl<'a> super::engine::process::middleware::render::ConfigProvider<'a> for Conf
       .configs()
       .into_iter()
       .map(|config| {
-        let match_set = self.match_store.query(config.match_paths());
+        let match_set = self.match_store.get().query(config.match_paths());
         (config, match_set)
       })
       .collect()
@@ -174,6 +179,18 @@ impl<'a> espanso_engine::process::MatcherMiddlewareConfigProvider for ConfigMana
   fn max_history_size(&self) -> usize {
     self.default().backspace_limit()
   }
+
+  fn buffer_reset_policy(&self) -> espanso_engine::process::BufferResetPolicy {
+    let config = self.default();
+    let idle_timeout = config.reset_buffer_idle_timeout();
+
+    espanso_engine::process::BufferResetPolicy {
+      reset_on_mouse_click: config.reset_buffer_on_click(),
+      reset_on_focus_change: config.reset_buffer_on_focus_change(),
+      idle_timeout: (idle_timeout > 0)
+        .then(|| std::time::Duration::from_millis(idle_timeout as u64)),
+    }
+  }
 }
 
 impl<'a> espanso_engine::process::UndoEnabledProvider for ConfigManager<'a> {
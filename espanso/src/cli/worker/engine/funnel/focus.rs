@@ -0,0 +1,84 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crossbeam::channel::{Receiver, Select, SelectedOperation};
+
+use crate::cli::worker::focus_monitor::FocusChangedEvent;
+use espanso_engine::{
+  event::{Event, EventType},
+  funnel,
+};
+
+use super::sequencer::Sequencer;
+
+pub struct FocusSource<'a> {
+  pub receiver: Receiver<FocusChangedEvent>,
+  pub sequencer: &'a Sequencer,
+}
+
+impl<'a> FocusSource<'a> {
+  pub fn new(receiver: Receiver<FocusChangedEvent>, sequencer: &'a Sequencer) -> Self {
+    FocusSource {
+      receiver,
+      sequencer,
+    }
+  }
+}
+
+impl<'a> funnel::Source<'a> for FocusSource<'a> {
+  fn register(&'a self, select: &mut Select<'a>) -> usize {
+    select.recv(&self.receiver)
+  }
+
+  fn receive(&self, op: SelectedOperation) -> Option<Event> {
+    op.recv(&self.receiver)
+      .expect("unable to select data from FocusSource receiver");
+
+    Some(Event {
+      source_id: self.sequencer.next_id(),
+      etype: EventType::FocusChanged,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crossbeam::channel::unbounded;
+  use espanso_engine::funnel::{Funnel, FunnelResult, Source};
+
+  #[test]
+  fn focus_change_is_forwarded_to_the_engine() {
+    let (sender, receiver) = unbounded();
+    let sequencer = Sequencer::new();
+    let source = FocusSource::new(receiver, &sequencer);
+    let sources: &[&dyn Source] = &[&source];
+    let funnel = funnel::default(sources);
+
+    sender.send(FocusChangedEvent).unwrap();
+
+    match funnel.receive() {
+      FunnelResult::Event(event) => {
+        assert!(matches!(event.etype, EventType::FocusChanged));
+        assert_eq!(event.source_id, 0);
+      }
+      _ => panic!("expected a focus changed event"),
+    }
+  }
+}
@@ -71,6 +71,7 @@ fn is_event_type_allowed(event: &EventType) -> bool {
   matches!(
     event,
     EventType::MatchExecRequest(_)
+      | EventType::MatchesReloadRequest(_)
       | EventType::ShowSearchBar
       | EventType::ShowConfigFolder
       | EventType::DisableRequest
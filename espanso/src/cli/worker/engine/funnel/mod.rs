@@ -35,6 +35,7 @@ use self::{
 
 pub mod detect;
 pub mod exit;
+pub mod focus;
 pub mod ipc;
 pub mod key_state;
 pub mod modifier;
@@ -17,7 +17,7 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::thread::JoinHandle;
+use std::{sync::Arc, thread::JoinHandle};
 
 use anyhow::Result;
 use crossbeam::channel::Receiver;
@@ -29,9 +29,11 @@ use espanso_inject::{InjectorCreationOptions, KeyboardStateProvider};
 use espanso_path::Paths;
 use espanso_ui::{event::UIEvent, UIRemote};
 use log::{debug, error, info, warn};
+use typed_arena::Arena;
 
 use crate::{
   cli::worker::{
+    clipboard_history::{ClipboardHistory, HistoryAwareClipboard},
     context::Context,
     engine::{
       dispatch::executor::{
@@ -50,15 +52,18 @@ use crate::{
         },
         multiplex::MultiplexAdapter,
         open_config::ConfigPathProviderAdapter,
+        reload::MatchReloaderAdapter,
         render::{
           extension::{
-            choice::ChoiceSelectorAdapter, clipboard::ClipboardAdapter, form::FormProviderAdapter,
+            choice::ChoiceSelectorAdapter, clipboard::ClipboardAdapter,
+            counter::CounterStoreAdapter, form::FormProviderAdapter, store::ValueStoreAdapter,
           },
           RendererAdapter,
         },
       },
     },
     match_cache::{CombinedMatchCache, MatchCache},
+    reloadable::Reloadable,
     ui::notification::NotificationManager,
   },
   common_flags::{
@@ -68,6 +73,7 @@ use crate::{
   preferences::Preferences,
 };
 
+use super::focus_monitor::FocusChangedEvent;
 use super::secure_input::SecureInputEvent;
 
 mod caches;
@@ -85,6 +91,7 @@ pub fn initialize_and_spawn(
   exit_signal: Receiver<ExitMode>,
   ui_event_receiver: Receiver<UIEvent>,
   secure_input_receiver: Receiver<SecureInputEvent>,
+  focus_receiver: Receiver<FocusChangedEvent>,
   use_evdev_backend: bool,
   start_reason: Option<String>,
   ipc_event_receiver: Receiver<EventType>,
@@ -96,6 +103,15 @@ pub fn initialize_and_spawn(
       let preferences =
         crate::preferences::get_default(&paths.runtime).expect("unable to load preferences");
 
+      // When some match files change, the match store and everything derived
+      // from it are replaced in place. These arenas keep the previous versions
+      // alive until the engine terminates (see `Reloadable` for more information)
+      let match_store_arena = Arena::new();
+      let rolling_matcher_arena = Arena::new();
+      let regex_matcher_arena = Arena::new();
+      let template_cache_arena = Arena::new();
+      let match_store = Reloadable::new(&match_store_arena, match_store);
+
       let app_info_provider =
         espanso_info::get_provider().expect("unable to initialize app info provider");
       // TODO: read interval from configs?
@@ -104,8 +120,8 @@ pub fn initialize_and_spawn(
         std::time::Duration::from_millis(400),
       );
       let config_manager =
-        super::config::ConfigManager::new(&*config_store, &*match_store, &cached_app_info_provider);
-      let match_cache = MatchCache::load(&*config_store, &*match_store);
+        super::config::ConfigManager::new(&*config_store, &match_store, &cached_app_info_provider);
+      let match_cache = MatchCache::load(&*config_store, match_store.get().as_ref());
       let default_config = &*config_manager.default();
 
       let modulo_manager = crate::gui::modulo::manager::ModuloManager::new();
@@ -122,7 +138,8 @@ pub fn initialize_and_spawn(
       let builtin_matches = super::builtin::get_builtin_matches(&*config_manager.default());
       let combined_match_cache = CombinedMatchCache::load(&match_cache, &builtin_matches);
 
-      let match_converter = MatchConverter::new(&*config_store, &*match_store, &builtin_matches);
+      let match_converter =
+        MatchConverter::new(&*config_store, match_store.get().as_ref(), &builtin_matches);
 
       let has_granted_capabilities = grant_linux_capabilities(use_evdev_backend);
 
@@ -147,21 +164,30 @@ pub fn initialize_and_spawn(
         secure_input_receiver,
         &sequencer,
       );
+      let focus_source = super::engine::funnel::focus::FocusSource::new(focus_receiver, &sequencer);
       let mut sources: Vec<&dyn espanso_engine::funnel::Source> =
         vec![&detect_source, &exit_source, &ui_source, &ipc_event_source];
       if cfg!(target_os = "macos") {
         sources.push(&secure_input_source);
       }
+      // The focus monitor is only spawned when needed, so the channel
+      // would be disconnected otherwise
+      if default_config.reset_buffer_on_focus_change() {
+        sources.push(&focus_source);
+      }
       let funnel = espanso_engine::funnel::default(&sources);
 
       let rolling_matcher = RollingMatcherAdapter::new(
+        &rolling_matcher_arena,
         &match_converter.get_rolling_matches(),
         RollingMatcherAdapterOptions {
           char_word_separators: config_manager.default().word_separators(),
         },
       );
       let regex_matcher = RegexMatcherAdapter::new(
+        &regex_matcher_arena,
         &match_converter.get_regex_matches(),
+        match_converter.get_regex_captures(),
         &RegexMatcherAdapterOptions {
           max_buffer_size: 30, // TODO: load from configs
         },
@@ -184,18 +210,52 @@ pub fn initialize_and_spawn(
         ..Default::default()
       })
       .expect("failed to initialize injector module"); // TODO: handle the options
-      let clipboard = espanso_clipboard::get_clipboard(ClipboardOptions::default())
-        .expect("failed to initialize clipboard module"); // TODO: handle options
 
-      let clipboard_adapter = ClipboardAdapter::new(&*clipboard, &config_manager);
-      let clipboard_extension =
-        espanso_render::extension::clipboard::ClipboardExtension::new(&clipboard_adapter);
+      // TODO: handle options
+      let clipboard_options = ClipboardOptions::default();
+      let native_clipboard = espanso_clipboard::get_clipboard(clipboard_options.clone())
+        .expect("failed to initialize clipboard module");
+      let clipboard_history = if default_config.clipboard_history() {
+        let history = Arc::new(ClipboardHistory::new(
+          default_config.clipboard_history_size(),
+        ));
+        if let Err(err) = super::clipboard_history::initialize_and_spawn(
+          history.clone(),
+          clipboard_options,
+          default_config.x11_use_xclip_backend(),
+          std::time::Duration::from_millis(default_config.clipboard_history_interval() as u64),
+        ) {
+          error!("unable to initialize clipboard history: {}", err);
+        }
+        Some(history)
+      } else {
+        None
+      };
+      let clipboard = HistoryAwareClipboard::new(&*native_clipboard, clipboard_history.as_deref());
+
+      let choice_adapter = ChoiceSelectorAdapter::new(&modulo_search_ui);
+      let clipboard_adapter =
+        ClipboardAdapter::new(&clipboard, &config_manager, clipboard_history.as_deref());
+      let clipboard_extension = espanso_render::extension::clipboard::ClipboardExtension::new(
+        &clipboard_adapter,
+        &choice_adapter,
+      );
       let locale_provider = espanso_render::extension::date::DefaultLocaleProvider::new();
       let date_extension = espanso_render::extension::date::DateExtension::new(&locale_provider);
       let echo_extension = espanso_render::extension::echo::EchoExtension::new();
       // For backwards compatiblity purposes, the echo extension can also be called with "dummy" type
       let dummy_extension = espanso_render::extension::echo::EchoExtension::new_with_alias("dummy");
       let random_extension = espanso_render::extension::random::RandomExtension::new();
+      let http_extension = espanso_render::extension::http::HttpExtension::new();
+      let expr_extension = espanso_render::extension::expr::ExprExtension::new();
+      let transform_extension = espanso_render::extension::transform::TransformExtension::new();
+      let kvs = espanso_kvs::get_persistent(&paths.runtime).expect("unable to initialize kvs");
+      let counter_store = CounterStoreAdapter::new(kvs.clone());
+      let counter_extension =
+        espanso_render::extension::counter::CounterExtension::new(&counter_store);
+      let value_store = ValueStoreAdapter::new(kvs);
+      let store_extension = espanso_render::extension::store::StoreExtension::new(&value_store);
+      let recall_extension = espanso_render::extension::store::RecallExtension::new(&value_store);
       let home_path = dirs::home_dir().expect("unable to obtain home dir path");
       let script_extension = espanso_render::extension::script::ScriptExtension::new(
         &paths.config,
@@ -203,9 +263,9 @@ pub fn initialize_and_spawn(
         &paths.packages,
       );
       let shell_extension = espanso_render::extension::shell::ShellExtension::new(&paths.config);
+      let file_extension = espanso_render::extension::file::FileExtension::new(&paths.config);
       let form_adapter = FormProviderAdapter::new(&modulo_form_ui);
       let form_extension = espanso_render::extension::form::FormExtension::new(&form_adapter);
-      let choice_adapter = ChoiceSelectorAdapter::new(&modulo_search_ui);
       let choice_extension =
         espanso_render::extension::choice::ChoiceExtension::new(&choice_adapter);
       let renderer = espanso_render::create(vec![
@@ -214,12 +274,33 @@ pub fn initialize_and_spawn(
         &echo_extension,
         &dummy_extension,
         &random_extension,
+        &http_extension,
+        &expr_extension,
+        &transform_extension,
+        &counter_extension,
+        &store_extension,
+        &recall_extension,
         &script_extension,
         &shell_extension,
+        &file_extension,
         &form_extension,
         &choice_extension,
       ]);
-      let renderer_adapter = RendererAdapter::new(&match_cache, &config_manager, &renderer);
+      let renderer_adapter = RendererAdapter::new(
+        &template_cache_arena,
+        &match_cache,
+        &config_manager,
+        &renderer,
+      );
+      let match_reloader = MatchReloaderAdapter::new(
+        &*config_store,
+        &match_store,
+        &builtin_matches,
+        &match_cache,
+        &rolling_matcher,
+        &regex_matcher,
+        &renderer_adapter,
+      );
       let path_provider = PathProviderAdapter::new(&paths);
       let config_path_provider = ConfigPathProviderAdapter::new(&paths);
 
@@ -248,11 +329,12 @@ pub fn initialize_and_spawn(
         &combined_match_cache,
         &notification_manager,
         &config_manager,
+        &match_reloader,
       );
 
       let event_injector = EventInjectorAdapter::new(&*injector, &config_manager);
       let clipboard_injector =
-        ClipboardInjectorAdapter::new(&*injector, &*clipboard, &config_manager);
+        ClipboardInjectorAdapter::new(&*injector, &clipboard, &config_manager);
       let key_injector = KeyInjectorAdapter::new(&*injector, &config_manager);
       let context_menu_adapter = ContextMenuHandlerAdapter::new(&*ui_remote);
       let icon_adapter = IconHandlerAdapter::new(&*ui_remote);
@@ -17,9 +17,12 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::{collections::HashMap, time::Duration};
+
 use espanso_config::{
   config::ConfigStore,
   matches::{
+    capture::{resolve_captures, RegexCapture},
     store::{MatchSet, MatchStore},
     MatchCause,
   },
@@ -27,9 +30,10 @@ use espanso_config::{
 use espanso_detect::hotkey::HotKey;
 use espanso_match::{
   regex::RegexMatch,
-  rolling::{RollingMatch, StringMatchOptions},
+  rolling::{RollingMatch, StringMatchOptions, TypingInterval},
+  MatchResult,
 };
-use log::error;
+use log::{error, warn};
 
 use crate::cli::worker::builtin::BuiltInMatch;
 
@@ -68,6 +72,11 @@ impl<'a> MatchConverter<'a> {
               case_insensitive: cause.propagate_case,
               left_word: cause.left_word,
               right_word: cause.right_word,
+              fuzzy: cause.fuzzy,
+              typing_interval: TypingInterval {
+                min: cause.min_typing_interval_ms.map(duration_from_millis),
+                max: cause.max_typing_interval_ms.map(duration_from_millis),
+              },
             },
           ));
         }
@@ -102,10 +111,39 @@ impl<'a> MatchConverter<'a> {
     matches
   }
 
+  pub fn get_regex_captures(&self) -> HashMap<i32, Vec<RegexCapture>> {
+    let match_set = self.global_match_set();
+
+    match_set
+      .matches
+      .into_iter()
+      .filter_map(|m| match &m.cause {
+        MatchCause::Regex(cause) if !cause.captures.is_empty() => {
+          Some((m.id, cause.captures.clone()))
+        }
+        _ => None,
+      })
+      .collect()
+  }
+
   pub fn get_hotkeys(&self) -> Vec<HotKey> {
+    let match_set = self.global_match_set();
     let mut hotkeys = Vec::new();
 
-    // TODO: read user-defined matches
+    // First convert configuration (user-defined) matches
+    for m in match_set.matches {
+      if let MatchCause::Hotkey(cause) = &m.cause {
+        match HotKey::new(m.id, &cause.hotkey) {
+          Ok(hotkey) => hotkeys.push(hotkey),
+          Err(err) => {
+            error!(
+              "unable to register hotkey: {}, with error: {}",
+              cause.hotkey, err
+            );
+          }
+        }
+      }
+    }
 
     // Then convert built-in ones
     for m in self.builtin_matches {
@@ -129,3 +167,42 @@ impl<'a> MatchConverter<'a> {
       .query(&paths.into_iter().collect::<Vec<_>>())
   }
 }
+
+/// Validate the named captures of a regex match against their declared types,
+/// adding the defaults of the missing ones. Matches with invalid captures are
+/// discarded, so that they never reach the renderer.
+/// The typed values are passed along as vars, with their fields (such as the
+/// year of a date) named `capture.field`, so that the renderer can expose them
+/// without resolving the captures again.
+pub fn convert_regex_result(
+  mut result: MatchResult<i32>,
+  captures: &[RegexCapture],
+) -> Option<MatchResult<i32>> {
+  let values = match resolve_captures(captures, &result.vars) {
+    Ok(values) => values,
+    Err(err) => {
+      warn!(
+        "discarding regex match for trigger '{}', {}",
+        result.trigger, err
+      );
+      return None;
+    }
+  };
+
+  for capture in captures {
+    if let Some(value) = values.get(&capture.name) {
+      result.vars.insert(capture.name.clone(), value.to_string());
+      for (field, field_value) in value.fields() {
+        result
+          .vars
+          .insert(format!("{}.{field}", capture.name), field_value);
+      }
+    }
+  }
+
+  Some(result)
+}
+
+fn duration_from_millis(millis: usize) -> Duration {
+  Duration::from_millis(millis as u64)
+}
@@ -53,6 +53,7 @@ pub fn convert_to_engine_result(result: espanso_match::MatchResult<i32>) -> Matc
     left_separator: result.left_separator,
     right_separator: result.right_separator,
     args: result.vars,
+    distance: result.distance,
   }
 }
 
@@ -17,40 +17,62 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::{cell::RefCell, collections::HashMap};
+
+use espanso_config::matches::capture::RegexCapture;
 use espanso_engine::process::{MatchResult, Matcher, MatcherEvent};
 use espanso_match::regex::{RegexMatch, RegexMatcher, RegexMatcherOptions};
+use typed_arena::Arena;
 
-use super::{convert_to_engine_result, convert_to_match_event, MatcherState};
+use super::{
+  convert::convert_regex_result, convert_to_engine_result, convert_to_match_event, MatcherState,
+};
+use crate::cli::worker::reloadable::Reloadable;
 
 pub struct RegexMatcherAdapterOptions {
   pub max_buffer_size: usize,
 }
 
-pub struct RegexMatcherAdapter {
-  matcher: RegexMatcher<i32>,
+pub struct RegexMatcherAdapter<'a> {
+  matcher: Reloadable<'a, RegexMatcher<i32>>,
+  captures: RefCell<HashMap<i32, Vec<RegexCapture>>>,
+  max_buffer_size: usize,
 }
 
-impl RegexMatcherAdapter {
-  pub fn new(matches: &[RegexMatch<i32>], options: &RegexMatcherAdapterOptions) -> Self {
-    let matcher = RegexMatcher::new(
-      matches,
-      RegexMatcherOptions {
-        max_buffer_size: options.max_buffer_size,
-      },
-    );
+impl<'a> RegexMatcherAdapter<'a> {
+  pub fn new(
+    arena: &'a Arena<RegexMatcher<i32>>,
+    matches: &[RegexMatch<i32>],
+    captures: HashMap<i32, Vec<RegexCapture>>,
+    options: &RegexMatcherAdapterOptions,
+  ) -> Self {
+    let matcher = Reloadable::new(arena, create_matcher(matches, options.max_buffer_size));
+
+    Self {
+      matcher,
+      captures: RefCell::new(captures),
+      max_buffer_size: options.max_buffer_size,
+    }
+  }
 
-    Self { matcher }
+  pub fn reload(&self, matches: &[RegexMatch<i32>], captures: HashMap<i32, Vec<RegexCapture>>) {
+    self
+      .matcher
+      .replace(create_matcher(matches, self.max_buffer_size));
+    *self.captures.borrow_mut() = captures;
   }
 }
 
-impl<'a> Matcher<'a, MatcherState<'a>> for RegexMatcherAdapter {
+fn create_matcher(matches: &[RegexMatch<i32>], max_buffer_size: usize) -> RegexMatcher<i32> {
+  RegexMatcher::new(matches, RegexMatcherOptions { max_buffer_size })
+}
+
+impl<'a> Matcher<'a, MatcherState<'a>> for RegexMatcherAdapter<'a> {
   fn process(
     &'a self,
     prev_state: Option<&MatcherState<'a>>,
     event: &MatcherEvent,
   ) -> (MatcherState<'a>, Vec<MatchResult>) {
-    use espanso_match::Matcher;
-
     let prev_state = prev_state.map(|state| {
       if let Some(state) = state.as_regex() {
         state
@@ -60,7 +82,14 @@ impl<'a> Matcher<'a, MatcherState<'a>> for RegexMatcherAdapter {
     });
     let event = convert_to_match_event(event);
 
-    let (state, results) = self.matcher.process(prev_state, event);
+    let captures = self.captures.borrow();
+    let (state, results) = self
+      .matcher
+      .get()
+      .process_filtered(prev_state, event, |result| match captures.get(&result.id) {
+        Some(captures) => convert_regex_result(result, captures),
+        None => Some(result),
+      });
 
     let enum_state = MatcherState::Regex(state);
     let results: Vec<MatchResult> = results.into_iter().map(convert_to_engine_result).collect();
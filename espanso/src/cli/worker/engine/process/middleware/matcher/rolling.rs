@@ -23,32 +23,61 @@ use espanso_match::rolling::{
 };
 
 use espanso_engine::process::{MatchResult, Matcher, MatcherEvent};
+use log::warn;
+use typed_arena::Arena;
 
 use super::{convert_to_engine_result, convert_to_match_event, MatcherState};
+use crate::cli::worker::reloadable::Reloadable;
 
 pub struct RollingMatcherAdapterOptions {
   pub char_word_separators: Vec<String>,
 }
 
-pub struct RollingMatcherAdapter {
-  matcher: RollingMatcher<i32>,
+pub struct RollingMatcherAdapter<'a> {
+  matcher: Reloadable<'a, RollingMatcher<i32>>,
+  options: RollingMatcherAdapterOptions,
 }
 
-impl RollingMatcherAdapter {
-  pub fn new(matches: &[RollingMatch<i32>], options: RollingMatcherAdapterOptions) -> Self {
-    let matcher = RollingMatcher::new(
-      matches,
-      RollingMatcherOptions {
-        char_word_separators: options.char_word_separators,
-        key_word_separators: vec![], // TODO?
-      },
-    );
+impl<'a> RollingMatcherAdapter<'a> {
+  pub fn new(
+    arena: &'a Arena<RollingMatcher<i32>>,
+    matches: &[RollingMatch<i32>],
+    options: RollingMatcherAdapterOptions,
+  ) -> Self {
+    let matcher = Reloadable::new(arena, create_matcher(matches, &options));
+
+    Self { matcher, options }
+  }
 
-    Self { matcher }
+  pub fn reload(&self, matches: &[RollingMatch<i32>]) {
+    self.matcher.replace(create_matcher(matches, &self.options));
   }
 }
 
-impl<'a> Matcher<'a, MatcherState<'a>> for RollingMatcherAdapter {
+fn create_matcher(
+  matches: &[RollingMatch<i32>],
+  options: &RollingMatcherAdapterOptions,
+) -> RollingMatcher<i32> {
+  let matcher = RollingMatcher::new(
+    matches,
+    RollingMatcherOptions {
+      char_word_separators: options.char_word_separators.clone(),
+      key_word_separators: vec![], // TODO?
+    },
+  );
+
+  let conflict_count = matcher.conflicts().len();
+  if conflict_count > 0 {
+    warn!(
+      "detected {} conflicting triggers, run 'espanso match conflicts' for more information",
+      conflict_count
+    );
+  }
+
+  matcher
+}
+
+impl<'a> Matcher<'a, MatcherState<'a>> for RollingMatcherAdapter<'a> {
   fn process(
     &'a self,
     prev_state: Option<&MatcherState<'a>>,
@@ -65,7 +94,7 @@ impl<'a> Matcher<'a, MatcherState<'a>> for RollingMatcherAdapter {
     });
     let event = convert_to_match_event(event);
 
-    let (state, results) = self.matcher.process(prev_state, event);
+    let (state, results) = self.matcher.get().process(prev_state, event);
 
     let enum_state = MatcherState::Rolling(state);
     let results: Vec<MatchResult> = results.into_iter().map(convert_to_engine_result).collect();
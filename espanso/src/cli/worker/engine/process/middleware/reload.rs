@@ -0,0 +1,159 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cell::Cell, collections::HashSet, path::Path};
+
+use espanso_config::{config::ConfigStore, matches::store::MatchStore};
+use espanso_engine::process::{MatchReloadResult, MatchReloader};
+use log::{debug, info};
+
+use super::{
+  matcher::{convert::MatchConverter, regex::RegexMatcherAdapter, rolling::RollingMatcherAdapter},
+  render::RendererAdapter,
+};
+use crate::cli::worker::{builtin::BuiltInMatch, match_cache::MatchCache, reloadable::Reloadable};
+
+// Every reload keeps the previous versions of the matches alive (see `Reloadable`),
+// so the worker is restarted to reclaim that memory once the matches retained by
+// all the versions exceed this budget. A match, along with its matcher nodes and
+// cache entries, takes in the order of a kilobyte, so this caps the overhead at
+// about 20 MB: small configs can be reloaded hundreds of times, while huge ones
+// are restarted after a few reloads.
+const RETAINED_MATCHES_BUDGET: usize = 20_000;
+
+pub struct MatchReloaderAdapter<'a> {
+  config_store: &'a dyn ConfigStore,
+  match_store: &'a Reloadable<'a, Box<dyn MatchStore>>,
+  builtin_matches: &'a [BuiltInMatch],
+  match_cache: &'a MatchCache<'a>,
+  rolling_matcher: &'a RollingMatcherAdapter<'a>,
+  regex_matcher: &'a RegexMatcherAdapter<'a>,
+  renderer: &'a RendererAdapter<'a>,
+
+  // Number of matches held by all the versions of the match store
+  retained_matches: Cell<usize>,
+}
+
+impl<'a> MatchReloaderAdapter<'a> {
+  pub fn new(
+    config_store: &'a dyn ConfigStore,
+    match_store: &'a Reloadable<'a, Box<dyn MatchStore>>,
+    builtin_matches: &'a [BuiltInMatch],
+    match_cache: &'a MatchCache<'a>,
+    rolling_matcher: &'a RollingMatcherAdapter<'a>,
+    regex_matcher: &'a RegexMatcherAdapter<'a>,
+    renderer: &'a RendererAdapter<'a>,
+  ) -> Self {
+    let retained_matches = count_matches(config_store, match_store.get().as_ref());
+    Self {
+      config_store,
+      match_store,
+      builtin_matches,
+      match_cache,
+      rolling_matcher,
+      regex_matcher,
+      renderer,
+      retained_matches: Cell::new(retained_matches),
+    }
+  }
+
+  fn can_reload_in_place(&self, paths: &[String]) -> bool {
+    // Only files that are already known can be reloaded in place, as new or
+    // deleted files might change the paths resolved by the configs
+    let known_paths: HashSet<String> = self
+      .match_store
+      .get()
+      .loaded_paths()
+      .into_iter()
+      .chain(self.config_store.get_all_match_paths())
+      .collect();
+
+    paths.iter().all(|path| {
+      let is_known = known_paths.contains(path) && Path::new(path).is_file();
+      if !is_known {
+        debug!("changed file {:?} is not a loaded match file", path);
+      }
+      is_known
+    })
+  }
+}
+
+impl<'a> MatchReloader for MatchReloaderAdapter<'a> {
+  fn reload_matches(&self, paths: &[String]) -> MatchReloadResult {
+    if !self.can_reload_in_place(paths) {
+      return MatchReloadResult::RestartRequired;
+    }
+
+    let current_store: &'a dyn MatchStore = self.match_store.get().as_ref();
+    let (new_store, non_fatal_errors) = match current_store.reload(paths) {
+      Some(result) => result,
+      None => return MatchReloadResult::RestartRequired,
+    };
+
+    // Hotkeys are registered by the detection source when the worker
+    // starts, so they can't be changed in place
+    let current_hotkeys =
+      MatchConverter::new(self.config_store, current_store, self.builtin_matches).get_hotkeys();
+    let new_hotkeys =
+      MatchConverter::new(self.config_store, new_store.as_ref(), self.builtin_matches)
+        .get_hotkeys();
+    if current_hotkeys != new_hotkeys {
+      info!("hotkeys have changed, the matches can't be reloaded in place");
+      return MatchReloadResult::RestartRequired;
+    }
+
+    let retained_matches =
+      self.retained_matches.get() + count_matches(self.config_store, new_store.as_ref());
+    if retained_matches > RETAINED_MATCHES_BUDGET {
+      debug!("reached the memory budget of in-place reloads");
+      return MatchReloadResult::RestartRequired;
+    }
+    self.retained_matches.set(retained_matches);
+
+    self.match_store.replace(new_store);
+    let match_store: &'a dyn MatchStore = self.match_store.get().as_ref();
+
+    self.match_cache.reload(self.config_store, match_store);
+
+    let match_converter = MatchConverter::new(self.config_store, match_store, self.builtin_matches);
+    self
+      .rolling_matcher
+      .reload(&match_converter.get_rolling_matches());
+    self.regex_matcher.reload(
+      &match_converter.get_regex_matches(),
+      match_converter.get_regex_captures(),
+    );
+
+    self.renderer.reload();
+
+    crate::config::log_non_fatal_errors(&non_fatal_errors);
+
+    MatchReloadResult::Reloaded {
+      has_errors: !non_fatal_errors.is_empty(),
+    }
+  }
+}
+
+fn count_matches(config_store: &dyn ConfigStore, match_store: &dyn MatchStore) -> usize {
+  let paths = config_store.get_all_match_paths();
+  match_store
+    .query(&paths.into_iter().collect::<Vec<_>>())
+    .matches
+    .len()
+}
@@ -20,6 +20,8 @@
 use espanso_clipboard::{Clipboard, ClipboardOperationOptions};
 use espanso_render::extension::clipboard::ClipboardProvider;
 
+use crate::cli::worker::clipboard_history::ClipboardHistory;
+
 pub trait ClipboardOperationOptionsProvider {
   fn get_operation_options(&self) -> ClipboardOperationOptions;
 }
@@ -27,16 +29,19 @@ pub trait ClipboardOperationOptionsProvider {
 pub struct ClipboardAdapter<'a> {
   clipboard: &'a dyn Clipboard,
   clipboard_operation_options_provider: &'a dyn ClipboardOperationOptionsProvider,
+  history: Option<&'a ClipboardHistory>,
 }
 
 impl<'a> ClipboardAdapter<'a> {
   pub fn new(
     clipboard: &'a dyn Clipboard,
     clipboard_operation_options_provider: &'a dyn ClipboardOperationOptionsProvider,
+    history: Option<&'a ClipboardHistory>,
   ) -> Self {
     Self {
       clipboard,
       clipboard_operation_options_provider,
+      history,
     }
   }
 }
@@ -49,4 +54,8 @@ impl<'a> ClipboardProvider for ClipboardAdapter<'a> {
         .get_operation_options(),
     )
   }
+
+  fn get_history(&self) -> Option<Vec<String>> {
+    self.history.map(ClipboardHistory::entries)
+  }
 }
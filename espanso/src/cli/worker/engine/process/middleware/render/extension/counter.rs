@@ -0,0 +1,69 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use espanso_kvs::KVS;
+use espanso_render::extension::counter::{CounterState, CounterStore};
+use serde::{Deserialize, Serialize};
+
+// Counters share the KVS with other components, so their keys are namespaced
+const COUNTER_KEY_PREFIX: &str = "counter_";
+
+#[derive(Serialize, Deserialize)]
+struct StoredCounter {
+  value: i64,
+  period: Option<String>,
+}
+
+pub struct CounterStoreAdapter<KVSType: KVS> {
+  kvs: KVSType,
+}
+
+impl<KVSType: KVS> CounterStoreAdapter<KVSType> {
+  pub fn new(kvs: KVSType) -> Self {
+    Self { kvs }
+  }
+}
+
+impl<KVSType: KVS> CounterStore for CounterStoreAdapter<KVSType> {
+  fn update(
+    &self,
+    key: &str,
+    update: &mut dyn FnMut(Option<CounterState>) -> CounterState,
+  ) -> Result<CounterState> {
+    let stored = self.kvs.update(
+      &format!("{COUNTER_KEY_PREFIX}{key}"),
+      |current: Option<StoredCounter>| {
+        let state = update(current.map(|current| CounterState {
+          value: current.value,
+          period: current.period,
+        }));
+        StoredCounter {
+          value: state.value,
+          period: state.period,
+        }
+      },
+    )?;
+
+    Ok(CounterState {
+      value: stored.value,
+      period: stored.period,
+    })
+  }
+}
@@ -19,4 +19,6 @@
 
 pub mod choice;
 pub mod clipboard;
+pub mod counter;
 pub mod form;
+pub mod store;
@@ -0,0 +1,52 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use espanso_kvs::KVS;
+use espanso_render::extension::store::ValueStore;
+
+// Stored values share the KVS with other components, so their keys are namespaced
+pub const STORE_KEY_PREFIX: &str = "store_";
+
+pub struct ValueStoreAdapter<KVSType: KVS> {
+  kvs: KVSType,
+}
+
+impl<KVSType: KVS> ValueStoreAdapter<KVSType> {
+  pub fn new(kvs: KVSType) -> Self {
+    Self { kvs }
+  }
+}
+
+impl<KVSType: KVS> ValueStore for ValueStoreAdapter<KVSType> {
+  fn get(&self, key: &str) -> Result<Option<String>> {
+    // Values edited through the CLI might not be strings
+    let value: Option<serde_json::Value> = self.kvs.get(&format!("{STORE_KEY_PREFIX}{key}"))?;
+    Ok(value.map(|value| match value {
+      serde_json::Value::String(value) => value,
+      value => value.to_string(),
+    }))
+  }
+
+  fn set(&self, key: &str, value: &str) -> Result<()> {
+    self
+      .kvs
+      .set(&format!("{STORE_KEY_PREFIX}{key}"), value.to_string())
+  }
+}
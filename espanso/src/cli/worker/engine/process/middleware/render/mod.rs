@@ -25,9 +25,12 @@ use espanso_config::{
   config::Config,
   matches::{store::MatchSet, Match, MatchCause, MatchEffect, UpperCasingStyle},
 };
-use espanso_render::{CasingStyle, Context, RenderOptions, Template, Value, Variable};
+use espanso_render::{CasingStyle, Context, Params, RenderOptions, Template, Value, Variable};
 
 use espanso_engine::process::{Renderer, RendererError};
+use typed_arena::Arena;
+
+use crate::cli::worker::reloadable::Reloadable;
 
 pub trait MatchProvider<'a> {
   fn matches(&self) -> Vec<&'a Match>;
@@ -44,30 +47,55 @@ pub struct RendererAdapter<'a> {
   match_provider: &'a dyn MatchProvider<'a>,
   config_provider: &'a dyn ConfigProvider<'a>,
 
+  templates: Reloadable<'a, TemplateCache>,
+
+  context_cache: RefCell<HashMap<i32, Context<'a>>>,
+}
+
+pub struct TemplateCache {
   template_map: HashMap<i32, Option<Template>>,
   global_vars_map: HashMap<i32, Variable>,
+}
 
-  context_cache: RefCell<HashMap<i32, Context<'a>>>,
+impl TemplateCache {
+  fn generate(match_provider: &dyn MatchProvider, config_provider: &dyn ConfigProvider) -> Self {
+    Self {
+      template_map: generate_template_map(match_provider),
+      global_vars_map: generate_global_vars_map(config_provider),
+    }
+  }
 }
 
 impl<'a> RendererAdapter<'a> {
   pub fn new(
+    arena: &'a Arena<TemplateCache>,
     match_provider: &'a dyn MatchProvider<'a>,
     config_provider: &'a dyn ConfigProvider<'a>,
     renderer: &'a dyn espanso_render::Renderer,
   ) -> Self {
-    let template_map = generate_template_map(match_provider);
-    let global_vars_map = generate_global_vars_map(config_provider);
+    let templates = Reloadable::new(
+      arena,
+      TemplateCache::generate(match_provider, config_provider),
+    );
 
     Self {
       renderer,
       config_provider,
       match_provider,
-      template_map,
-      global_vars_map,
+      templates,
       context_cache: RefCell::new(HashMap::new()),
     }
   }
+
+  /// Regenerate the templates from the current matches, which must be
+  /// called after the match provider has been reloaded.
+  pub fn reload(&self) {
+    self.templates.replace(TemplateCache::generate(
+      self.match_provider,
+      self.config_provider,
+    ));
+    self.context_cache.borrow_mut().clear();
+  }
 }
 
 // TODO: test
@@ -193,13 +221,18 @@ impl<'a> Renderer<'a> for RendererAdapter<'a> {
     trigger: Option<&str>,
     trigger_vars: HashMap<String, String>,
   ) -> anyhow::Result<String> {
-    if let Some(Some(template)) = self.template_map.get(&match_id) {
+    let templates = self.templates.get();
+    if let Some(Some(template)) = templates.template_map.get(&match_id) {
       let (config, match_set) = self.config_provider.active();
 
       let mut context_cache = self.context_cache.borrow_mut();
-      let context = context_cache
-        .entry(config.id())
-        .or_insert_with(|| generate_context(&match_set, &self.template_map, &self.global_vars_map));
+      let context = context_cache.entry(config.id()).or_insert_with(|| {
+        generate_context(
+          &match_set,
+          &templates.template_map,
+          &templates.global_vars_map,
+        )
+      });
 
       let raw_match = self.match_provider.get(match_id);
       let propagate_case = raw_match.is_some_and(is_propagate_case);
@@ -220,9 +253,7 @@ impl<'a> Renderer<'a> for RendererAdapter<'a> {
         None
       } else {
         let mut augmented = template.clone();
-        for (name, value) in trigger_vars {
-          let mut params = espanso_render::Params::new();
-          params.insert("echo".to_string(), Value::String(value));
+        for (name, params) in convert_trigger_vars(raw_match, trigger_vars) {
           augmented.vars.insert(
             0,
             Variable {
@@ -254,6 +285,45 @@ impl<'a> Renderer<'a> for RendererAdapter<'a> {
   }
 }
 
+// Convert the trigger vars into the params of echo variables. The fields of
+// the typed regex captures are passed by the matcher as `capture.field` vars,
+// and exposed as the fields of the capture variable (as in {{date.year}})
+fn convert_trigger_vars(
+  m: Option<&Match>,
+  trigger_vars: HashMap<String, String>,
+) -> Vec<(String, Params)> {
+  let captures = match m.map(|m| &m.cause) {
+    Some(MatchCause::Regex(cause)) => cause.captures.as_slice(),
+    _ => &[],
+  };
+
+  let mut values = Vec::new();
+  let mut fields: HashMap<String, Params> = HashMap::new();
+  for (name, value) in trigger_vars {
+    match name.split_once('.') {
+      Some((capture, field)) if captures.iter().any(|c| c.name == capture) => {
+        fields
+          .entry(capture.to_string())
+          .or_default()
+          .insert(field.to_string(), Value::String(value));
+      }
+      _ => values.push((name, value)),
+    }
+  }
+
+  values
+    .into_iter()
+    .map(|(name, value)| {
+      let mut params = Params::new();
+      params.insert("echo".to_string(), Value::String(value));
+      if let Some(fields) = fields.remove(&name) {
+        params.insert("fields".to_string(), Value::Object(fields));
+      }
+      (name, params)
+    })
+    .collect()
+}
+
 fn extract_uppercasing_style(m: &Match) -> Option<UpperCasingStyle> {
   if let MatchCause::Trigger(cause) = &m.cause {
     Some(cause.uppercase_style.clone())
@@ -0,0 +1,125 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use anyhow::Result;
+use crossbeam::channel::Sender;
+use espanso_info::AppInfo;
+use log::{error, info};
+
+pub struct FocusChangedEvent;
+
+pub fn initialize_and_spawn(
+  focus_sender: Sender<FocusChangedEvent>,
+  watch_interval: Duration,
+) -> Result<()> {
+  std::thread::Builder::new()
+    .name("focus-monitor".to_string())
+    .spawn(move || {
+      // The provider is created here, as it might not be safe to share it between threads
+      match espanso_info::get_provider() {
+        Ok(app_info_provider) => {
+          focus_monitor_main(&*app_info_provider, &focus_sender, watch_interval);
+        }
+        Err(error) => {
+          error!(
+            "unable to initialize app info provider for focus monitor: {}",
+            error
+          );
+        }
+      }
+    })?;
+
+  Ok(())
+}
+
+fn focus_monitor_main(
+  app_info_provider: &dyn espanso_info::AppInfoProvider,
+  focus_sender: &Sender<FocusChangedEvent>,
+  watch_interval: Duration,
+) {
+  info!("monitoring focus changes");
+
+  let mut tracker = FocusTracker::default();
+  loop {
+    if tracker.update(app_info_provider.get_info()) {
+      if let Err(error) = focus_sender.send(FocusChangedEvent) {
+        error!("unable to send focus changed event: {}", error);
+        return;
+      }
+    }
+
+    std::thread::sleep(watch_interval);
+  }
+}
+
+// The title is part of the focus, so that switching between windows or tabs
+// of the same application is detected as well
+type Focus = (Option<String>, Option<String>, Option<String>);
+
+#[derive(Default)]
+struct FocusTracker {
+  last_focus: Option<Focus>,
+}
+
+impl FocusTracker {
+  // Returns true if the focus changed since the last update
+  fn update(&mut self, info: AppInfo) -> bool {
+    let focus = (info.exec, info.class, info.title);
+    let changed = self.last_focus.as_ref().is_some_and(|last| *last != focus);
+    self.last_focus = Some(focus);
+    changed
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn info(exec: &str, title: &str) -> AppInfo {
+    AppInfo {
+      title: Some(title.to_string()),
+      exec: Some(exec.to_string()),
+      class: None,
+    }
+  }
+
+  #[test]
+  fn first_update_is_not_a_change() {
+    let mut tracker = FocusTracker::default();
+    assert!(!tracker.update(info("code", "main.rs")));
+    assert!(!tracker.update(info("code", "main.rs")));
+  }
+
+  #[test]
+  fn app_change_is_detected() {
+    let mut tracker = FocusTracker::default();
+    tracker.update(info("code", "main.rs"));
+    assert!(tracker.update(info("firefox", "main.rs")));
+  }
+
+  #[test]
+  fn title_change_is_detected() {
+    let mut tracker = FocusTracker::default();
+    tracker.update(info("firefox", "Inbox"));
+    assert!(tracker.update(info("firefox", "Calendar")));
+    assert!(!tracker.update(info("firefox", "Calendar")));
+  }
+}
@@ -21,7 +21,10 @@ use std::path::Path;
 
 use anyhow::Result;
 use crossbeam::channel::Sender;
-use espanso_engine::event::{external::MatchExecRequestEvent, EventType, ExitMode};
+use espanso_engine::event::{
+  external::{MatchExecRequestEvent, MatchesReloadRequestEvent},
+  EventType, ExitMode,
+};
 use espanso_ipc::{EventHandlerResponse, IPCServer};
 use log::{error, warn};
 
@@ -71,6 +74,12 @@ pub fn initialize_and_spawn(
               args: payload.args,
             }),
           ),
+          IPCEvent::ReloadMatches(payload) => send_event(
+            &event_notify,
+            EventType::MatchesReloadRequest(MatchesReloadRequestEvent {
+              paths: payload.paths,
+            }),
+          ),
           #[allow(unreachable_patterns)]
           unexpected_event => {
             warn!(
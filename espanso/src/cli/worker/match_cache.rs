@@ -17,7 +17,7 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap};
 
 use espanso_config::{
   config::ConfigStore,
@@ -28,41 +28,62 @@ use espanso_engine::event::internal::DetectedMatch;
 use super::{builtin::BuiltInMatch, engine::process::middleware::match_select::MatchSummary};
 
 pub struct MatchCache<'a> {
-  cache: HashMap<i32, &'a Match>,
+  cache: RefCell<HashMap<i32, &'a Match>>,
 }
 
 impl<'a> MatchCache<'a> {
   pub fn load(config_store: &'a dyn ConfigStore, match_store: &'a dyn MatchStore) -> Self {
-    let mut cache = HashMap::new();
-
-    let paths = config_store.get_all_match_paths();
-    let global_set = match_store.query(&paths.into_iter().collect::<Vec<_>>());
-
-    for m in global_set.matches {
-      cache.insert(m.id, m);
+    Self {
+      cache: RefCell::new(build_cache(config_store, match_store)),
     }
+  }
 
-    Self { cache }
+  pub fn reload(&self, config_store: &'a dyn ConfigStore, match_store: &'a dyn MatchStore) {
+    *self.cache.borrow_mut() = build_cache(config_store, match_store);
   }
 
   fn ids(&self) -> Vec<i32> {
-    self.cache.keys().copied().collect()
+    self.cache.borrow().keys().copied().collect()
+  }
+
+  fn get_match(&self, id: i32) -> Option<&'a Match> {
+    self.cache.borrow().get(&id).copied()
+  }
+
+  fn all_matches(&self) -> Vec<&'a Match> {
+    self.cache.borrow().values().copied().collect()
   }
 }
 
+fn build_cache<'a>(
+  config_store: &'a dyn ConfigStore,
+  match_store: &'a dyn MatchStore,
+) -> HashMap<i32, &'a Match> {
+  let mut cache = HashMap::new();
+
+  let paths = config_store.get_all_match_paths();
+  let global_set = match_store.query(&paths.into_iter().collect::<Vec<_>>());
+
+  for m in global_set.matches {
+    cache.insert(m.id, m);
+  }
+
+  cache
+}
+
 impl<'a> super::engine::process::middleware::render::MatchProvider<'a> for MatchCache<'a> {
   fn matches(&self) -> Vec<&'a Match> {
-    self.cache.values().copied().collect()
+    self.all_matches()
   }
 
   fn get(&self, id: i32) -> Option<&'a Match> {
-    self.cache.get(&id).copied()
+    self.get_match(id)
   }
 }
 
 impl<'a> espanso_engine::process::MatchInfoProvider for MatchCache<'a> {
   fn get_force_mode(&self, match_id: i32) -> Option<espanso_engine::event::effect::TextInjectMode> {
-    let m = self.cache.get(&match_id)?;
+    let m = self.get_match(match_id)?;
     if let MatchEffect::Text(text_effect) = &m.effect {
       if let Some(force_mode) = &text_effect.force_mode {
         match force_mode {
@@ -105,7 +126,7 @@ impl<'a> CombinedMatchCache<'a> {
   }
 
   pub fn get(&self, match_id: i32) -> Option<MatchVariant<'a>> {
-    if let Some(user_match) = self.user_match_cache.cache.get(&match_id).copied() {
+    if let Some(user_match) = self.user_match_cache.get_match(match_id) {
       return Some(MatchVariant::User(user_match));
     }
 
@@ -172,8 +193,8 @@ impl<'a> espanso_engine::process::MatchResolver for CombinedMatchCache<'a> {
   fn find_matches_from_trigger(&self, trigger: &str) -> Vec<DetectedMatch> {
     let user_matches: Vec<DetectedMatch> = self
       .user_match_cache
-      .cache
-      .values()
+      .all_matches()
+      .into_iter()
       .filter_map(|m| {
         if let MatchCause::Trigger(trigger_cause) = &m.cause {
           if trigger_cause.triggers.iter().any(|t| t == trigger) {
@@ -34,16 +34,25 @@ use self::ui::util::convert_icon_paths_to_tray_vec;
 
 use super::{CliModule, CliModuleArgs};
 
-mod builtin;
+pub mod builtin;
+mod clipboard_history;
 mod config;
 mod context;
 mod daemon_monitor;
-mod engine;
+pub mod engine;
+mod focus_monitor;
 mod ipc;
 mod match_cache;
+mod reloadable;
 mod secure_input;
 mod ui;
 
+// How often the focus monitor polls the active application. The app info
+// providers query the window system on each call, so polling faster would only
+// add overhead, while 250ms is still short enough for the buffer to be reset
+// before the user starts typing in the newly focused window.
+const FOCUS_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
 pub fn new() -> CliModule {
   #[allow(clippy::needless_update)]
   CliModule {
@@ -118,6 +127,8 @@ fn worker_main(args: CliModuleArgs) -> i32 {
   let (ipc_event_notify, ipc_event_receiver) = unbounded();
   let (engine_ui_event_sender, engine_ui_event_receiver) = unbounded();
   let (engine_secure_input_sender, engine_secure_input_receiver) = unbounded();
+  let (engine_focus_sender, engine_focus_receiver) = unbounded();
+  let reset_buffer_on_focus_change = config_store.default().reset_buffer_on_focus_change();
 
   // Initialize the engine on another thread and start it
   let engine_handle = engine::initialize_and_spawn(
@@ -128,6 +139,7 @@ fn worker_main(args: CliModuleArgs) -> i32 {
     engine_exit_receiver,
     engine_ui_event_receiver,
     engine_secure_input_receiver,
+    engine_focus_receiver,
     use_evdev_backend,
     start_reason,
     ipc_event_receiver,
@@ -150,6 +162,15 @@ fn worker_main(args: CliModuleArgs) -> i32 {
   secure_input::initialize_and_spawn(engine_secure_input_sender)
     .expect("unable to initialize secure input watcher");
 
+  // A sender is kept alive for the whole lifetime of the worker, as the engine
+  // would otherwise keep receiving from a disconnected channel if the focus
+  // monitor terminated (for example, if the app info provider failed)
+  let _focus_sender = engine_focus_sender.clone();
+  if reset_buffer_on_focus_change {
+    focus_monitor::initialize_and_spawn(engine_focus_sender, FOCUS_WATCH_INTERVAL)
+      .expect("unable to initialize focus monitor");
+  }
+
   eventloop
     .run(Box::new(move |event| {
       if let Err(error) = engine_ui_event_sender.send(event) {
@@ -0,0 +1,53 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019-2021 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::cell::Cell;
+
+use typed_arena::Arena;
+
+/// A value that can be replaced while the worker is running, for example
+/// when a match file changes and the matches are reloaded in place.
+///
+/// Replaced values can't be dropped right away, because other components
+/// might still hold references to them (the matcher states point to the nodes
+/// of the matcher that produced them, the match caches point to the matches
+/// of the store they were built from, etc).
+/// For this reason, each version is allocated in an arena and lives until
+/// the arena itself is dropped, at the end of the engine thread.
+pub struct Reloadable<'a, T> {
+  arena: &'a Arena<T>,
+  current: Cell<&'a T>,
+}
+
+impl<'a, T> Reloadable<'a, T> {
+  pub fn new(arena: &'a Arena<T>, value: T) -> Self {
+    Self {
+      arena,
+      current: Cell::new(&*arena.alloc(value)),
+    }
+  }
+
+  pub fn get(&self) -> &'a T {
+    self.current.get()
+  }
+
+  pub fn replace(&self, value: T) {
+    self.current.set(&*self.arena.alloc(value));
+  }
+}
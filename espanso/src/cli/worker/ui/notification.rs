@@ -73,4 +73,14 @@ impl<'a> espanso_engine::process::NotificationManager for NotificationManager<'a
     self
       .notify("An error occurred during rendering, please examine the logs for more information.");
   }
+
+  fn notify_matches_reloaded(&self, has_errors: bool) {
+    if has_errors {
+      self.notify(
+        "Matches reloaded, but some files contain errors. Their previous version is still in use, please examine the logs for more information.",
+      );
+    } else {
+      self.notify("Matches reloaded!");
+    }
+  }
 }
@@ -95,23 +95,7 @@ pub fn load_config(config_path: &Path, packages_path: &Path) -> Result<ConfigLoa
       espanso_config::load(config_path).context("unable to load config")?;
 
     // TODO: add an option to avoid dumping the errors in the logs
-    if !non_fatal_errors.is_empty() {
-      warn!("------- detected some errors in the configuration: -------");
-      for non_fatal_error_set in &non_fatal_errors {
-        warn!(
-          ">>> {}",
-          non_fatal_error_set.file.to_string_lossy().to_string()
-        );
-        for record in &non_fatal_error_set.errors {
-          if record.level == ErrorLevel::Error {
-            error!("{:?}", record.error);
-          } else {
-            warn!("{:?}", record.error);
-          }
-        }
-      }
-      warn!("-----------------------------------------------------------");
-    }
+    log_non_fatal_errors(&non_fatal_errors);
 
     Ok(ConfigLoadResult {
       // Apply the built-in patches
@@ -122,3 +106,25 @@ pub fn load_config(config_path: &Path, packages_path: &Path) -> Result<ConfigLoa
     })
   }
 }
+
+pub fn log_non_fatal_errors(non_fatal_errors: &[NonFatalErrorSet]) {
+  if non_fatal_errors.is_empty() {
+    return;
+  }
+
+  warn!("------- detected some errors in the configuration: -------");
+  for non_fatal_error_set in non_fatal_errors {
+    warn!(
+      ">>> {}",
+      non_fatal_error_set.file.to_string_lossy().to_string()
+    );
+    for record in &non_fatal_error_set.errors {
+      if record.level == ErrorLevel::Error {
+        error!("{:?}", record.error);
+      } else {
+        warn!("{:?}", record.error);
+      }
+    }
+  }
+  warn!("-----------------------------------------------------------");
+}
@@ -34,6 +34,7 @@ pub enum IPCEvent {
   OpenConfigFolder,
 
   RequestMatchExpansion(RequestMatchExpansionPayload),
+  ReloadMatches(ReloadMatchesPayload),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -42,6 +43,11 @@ pub struct RequestMatchExpansionPayload {
   pub args: HashMap<String, String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReloadMatchesPayload {
+  pub paths: Vec<String>,
+}
+
 pub fn create_daemon_ipc_server(runtime_dir: &Path) -> Result<impl IPCServer<IPCEvent>> {
   create_ipc_server(runtime_dir, "daemonv2")
 }
@@ -72,6 +72,7 @@ lazy_static! {
     cli::workaround::new(),
     cli::package::new(),
     cli::match_cli::new(),
+    cli::kvs::new(),
     cli::cmd::new(),
   ];
   static ref ALIASES: Vec<CliAlias> = vec![
@@ -370,7 +371,7 @@ For example, specifying 'email' is equivalent to 'match/email.yml'."#))
     .subcommand(stop_subcommand)
     .subcommand(status_subcommand)
     .subcommand(SubCommand::with_name("match")
-        .about("List and execute matches from the CLI")
+        .about("List, execute and check matches from the CLI")
         .subcommand(SubCommand::with_name("list")
             .about("Print matches to standard output")
             .arg(Arg::with_name("json")
@@ -431,6 +432,33 @@ For example, specifying 'email' is equivalent to 'match/email.yml'."#))
                 .number_of_values(1)
             )
         )
+        .subcommand(SubCommand::with_name("conflicts")
+            .about("List the triggers that shadow or duplicate each other")
+        )
+        .subcommand(SubCommand::with_name("check")
+            .about("Check match files for problems, exiting with a non-zero status if any is found")
+        )
+    )
+    .subcommand(
+      SubCommand::with_name("kvs")
+        .about("Inspect and edit the values persisted by espanso, such as the ones saved with the store extension (prefixed with 'store_') and counters (prefixed with 'counter_')")
+        .subcommand(SubCommand::with_name("list").about("Print all the stored keys with their values"))
+        .subcommand(
+          SubCommand::with_name("get")
+            .about("Print the value of the given key")
+            .arg(Arg::with_name("key").required(true)),
+        )
+        .subcommand(
+          SubCommand::with_name("set")
+            .about("Set the value of the given key, which is parsed as JSON if valid")
+            .arg(Arg::with_name("key").required(true))
+            .arg(Arg::with_name("value").required(true)),
+        )
+        .subcommand(
+          SubCommand::with_name("delete")
+            .about("Delete the given key")
+            .arg(Arg::with_name("key").required(true)),
+        ),
     )
     .subcommand(
       SubCommand::with_name("package")
@@ -94,6 +94,18 @@ macro_rules! generate_patchable_config {
         fn secure_input_notification(&self) -> bool {
           self.base.secure_input_notification()
         }
+
+        fn clipboard_history(&self) -> bool {
+          self.base.clipboard_history()
+        }
+
+        fn clipboard_history_size(&self) -> usize {
+          self.base.clipboard_history_size()
+        }
+
+        fn clipboard_history_interval(&self) -> usize {
+          self.base.clipboard_history_interval()
+        }
       }
   };
 }
@@ -46,6 +46,9 @@ generate_patchable_config!(
   evdev_modifier_delay -> Option<usize>,
   word_separators -> Vec<String>,
   backspace_limit -> usize,
+  reset_buffer_on_click -> bool,
+  reset_buffer_on_focus_change -> bool,
+  reset_buffer_idle_timeout -> usize,
   apply_patch -> bool,
   undo_backspace -> bool,
   post_form_delay -> usize,